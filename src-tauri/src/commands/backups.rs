@@ -1,17 +1,48 @@
 /// Backup management Tauri commands
 use crate::commands::config::ConfigState;
-use crate::models::Backup;
+use crate::commands::settings::SettingsState;
+use crate::commands::shortcuts::emit_validation;
+use crate::models::{Backup, BackupTrigger, ConfigFile};
+use crate::parser::parse_config;
 use crate::services::backup::{
-    create_backup as create_backup_service, list_backups as list_backups_service,
-    restore_backup as restore_backup_service,
+    list_backups as list_backups_service, prune_backups, RetentionPolicy,
 };
-use tauri::State;
+use crate::services::backup_archive::{export_backups_in, import_backups_in, ImportResult};
+use crate::services::backup_audit::{
+    audit_backups_in, quarantine_backups_in, AuditReport, BackupIssueKind, QuarantineResult,
+};
+use crate::services::backup_diff::ConfigDiff;
+use crate::services::file_io::read_config_safe;
+use crate::services::history::{ConfigHistory, HistorySettings};
+use crate::services::partial_restore::{
+    compare_shortcuts, restore_shortcuts as restore_shortcuts_service, BackupShortcut,
+    ConflictResolution, ShortcutRestoreResult,
+};
+use crate::services::settings::AppPaths;
+use std::collections::BTreeMap;
+use tauri::{AppHandle, State};
+
+/// Request to restore selected bindings of a backup
+#[derive(serde::Deserialize)]
+pub struct RestoreShortcutsRequest {
+    /// ID of the backup
+    pub backup_id: String,
+    /// Line numbers of the bindings in the backup (see `get_backup_shortcuts`)
+    pub line_numbers: Vec<usize>,
+    /// Resolution for bindings whose key combination is already used
+    #[serde(default)]
+    pub on_conflict: ConflictResolution,
+    /// Resolutions overriding `on_conflict`, by backup line number
+    #[serde(default)]
+    pub resolutions: BTreeMap<usize, ConflictResolution>,
+}
 
 /// Create a backup of the current configuration
 ///
 /// # Arguments
 /// * `description` - Optional description for the backup
 /// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
 /// * `Ok(Backup)` - Created backup metadata
@@ -20,6 +51,7 @@ use tauri::State;
 pub fn create_backup(
     description: Option<String>,
     state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
 ) -> Result<Backup, String> {
     let file_path = {
         let config_guard = state.config.lock().unwrap();
@@ -27,31 +59,54 @@ pub fn create_backup(
         config.file_path.clone()
     }; // Lock released here
 
-    create_backup_service(&file_path, description)
+    open_history(&app_settings.paths(), &file_path)?
+        .create(description)
         .map_err(|e| format!("Failed to create backup: {}", e))
 }
 
-/// List all available backups
+/// List the backups of the loaded configuration (all backups if none is loaded)
+///
+/// # Arguments
+/// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
-/// * `Ok(Vec<Backup>)` - List of backups
+/// * `Ok(Vec<Backup>)` - List of backups, newest first
 /// * `Err(String)` - Error message
 #[tauri::command]
-pub fn list_backups() -> Result<Vec<Backup>, String> {
-    list_backups_service().map_err(|e| format!("Failed to list backups: {}", e))
+pub fn list_backups(
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<Vec<Backup>, String> {
+    let file_path = {
+        let config_guard = state.config.lock().unwrap();
+        config_guard.as_ref().map(|config| config.file_path.clone())
+    }; // Lock released here
+
+    let paths = app_settings.paths();
+    match file_path {
+        Some(file_path) => open_history(&paths, &file_path)?.list(),
+        None => list_backups_service(&paths),
+    }
+    .map_err(|e| format!("Failed to list backups: {}", e))
 }
 
 /// Restore configuration from a backup
 ///
 /// # Arguments
-/// * `backup_path` - Path to backup file
+/// * `backup_id` - ID of the backup
 /// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
 /// * `Ok(())` on success
 /// * `Err(String)` on failure
 #[tauri::command]
-pub fn restore_backup(backup_path: String, state: State<'_, ConfigState>) -> Result<(), String> {
+pub fn restore_backup(
+    backup_id: String,
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<(), String> {
     let target_path = {
         let config_guard = state.config.lock().unwrap();
         let config = config_guard.as_ref().ok_or("No config loaded")?;
@@ -59,16 +114,361 @@ pub fn restore_backup(backup_path: String, state: State<'_, ConfigState>) -> Res
     }; // Lock released here
 
     // Find the backup
-    let backups = list_backups_service().map_err(|e| format!("Failed to list backups: {}", e))?;
+    let history = open_history(&app_settings.paths(), &target_path)?;
+    let backup = find_backup(&history, &backup_id)?;
 
-    let backup = backups
-        .iter()
-        .find(|b| b.file_path.to_string_lossy() == backup_path)
-        .ok_or("Backup not found")?;
+    // Keep the current file in case the restore was a mistake
+    history
+        .before_overwrite(BackupTrigger::BeforeRestore)
+        .map_err(|e| format!("Failed to back up config before restoring: {}", e))?;
 
     // Restore
-    restore_backup_service(backup, Some(&target_path))
+    history
+        .restore(&backup)
         .map_err(|e| format!("Failed to restore backup: {}", e))?;
 
+    record_write_logged(&history, &format!("Restore backup {}", backup.created_at));
+
     Ok(())
 }
+
+/// Diff two versions of the loaded configuration
+///
+/// Pass `old_id: None` and a backup as `new_id` to preview what restoring
+/// that backup changes.
+///
+/// # Arguments
+/// * `old_id` - Backup to diff from (None for the live config file)
+/// * `new_id` - Backup to diff to (None for the live config file)
+/// * `state` - Application state
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(ConfigDiff)` - Line diff and binding diff
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn diff_backups(
+    old_id: Option<String>,
+    new_id: Option<String>,
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<ConfigDiff, String> {
+    let live_path = {
+        let config_guard = state.config.lock().unwrap();
+        let config = config_guard.as_ref().ok_or("No config loaded")?;
+        config.file_path.clone()
+    }; // Lock released here
+
+    let history = open_history(&app_settings.paths(), &live_path)?;
+    let old = version_text(&history, old_id.as_deref(), &live_path)?;
+    let new = version_text(&history, new_id.as_deref(), &live_path)?;
+
+    Ok(ConfigDiff::between(&old, &new))
+}
+
+/// List the bindings of a backup, compared with the loaded configuration
+///
+/// # Arguments
+/// * `backup_id` - ID of the backup
+/// * `state` - Application state
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(Vec<BackupShortcut>)` - Bindings of the backup with their conflicts
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn get_backup_shortcuts(
+    backup_id: String,
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<Vec<BackupShortcut>, String> {
+    let current = {
+        let config_guard = state.config.lock().unwrap();
+        config_guard.as_ref().ok_or("No config loaded")?.clone()
+    }; // Lock released here
+
+    let history = open_history(&app_settings.paths(), &current.file_path)?;
+    let backup = backup_config(&history, &backup_id)?;
+    Ok(compare_shortcuts(&backup, &current))
+}
+
+/// Restore selected bindings of a backup into the loaded configuration
+///
+/// Only the in-memory configuration changes; it is marked modified and saved
+/// like any other edit. Emits a `validation-changed` event with `reset` set.
+///
+/// # Arguments
+/// * `request` - Backup, selected lines and conflict resolutions
+/// * `state` - Application state
+/// * `app_settings` - Application settings
+/// * `app_handle` - Tauri AppHandle for the validation event
+///
+/// # Returns
+/// * `Ok(ShortcutRestoreResult)` - Added, replaced and skipped bindings
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn restore_shortcuts(
+    request: RestoreShortcutsRequest,
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+    app_handle: AppHandle,
+) -> Result<ShortcutRestoreResult, String> {
+    let mut config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_mut().ok_or("No config loaded")?;
+
+    let history = open_history(&app_settings.paths(), &config.file_path)?;
+    let backup = backup_config(&history, &request.backup_id)?;
+    let result = restore_shortcuts_service(config, &backup, &request.line_numbers, |line| {
+        request
+            .resolutions
+            .get(&line)
+            .copied()
+            .unwrap_or(request.on_conflict)
+    })?;
+
+    state.invalidate_validation();
+    emit_validation(&app_handle, &state.revalidate(config, None, None));
+
+    Ok(result)
+}
+
+/// Get the backup retention policy
+///
+/// # Arguments
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(RetentionPolicy)` - Current policy (defaults if none was set)
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn get_backup_retention(
+    app_settings: State<'_, SettingsState>,
+) -> Result<RetentionPolicy, String> {
+    RetentionPolicy::load(&app_settings.paths().config_dir)
+        .map_err(|e| format!("Failed to read retention policy: {}", e))
+}
+
+/// Set the backup retention policy and prune backups it no longer keeps
+///
+/// # Arguments
+/// * `policy` - New retention policy
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(Vec<Backup>)` - Backups removed by the new policy
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn set_backup_retention(
+    policy: RetentionPolicy,
+    app_settings: State<'_, SettingsState>,
+) -> Result<Vec<Backup>, String> {
+    let paths = app_settings.paths();
+    policy
+        .save(&paths.config_dir)
+        .map_err(|e| format!("Failed to save retention policy: {}", e))?;
+
+    prune_backups(&paths).map_err(|e| format!("Failed to prune backups: {}", e))
+}
+
+/// Export all backups with their metadata to an archive chosen in a save dialog
+///
+/// # Arguments
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(String)` - Path of the written archive
+/// * `Err(String)` - "Export cancelled" if user closes dialog, or IO errors
+#[tauri::command]
+pub async fn export_backups(app_settings: State<'_, SettingsState>) -> Result<String, String> {
+    let backup_dir = app_settings.paths().backup_dir;
+    let file_name = format!(
+        "skhd-backups-{}.tar.gz",
+        chrono::Local::now().format("%Y%m%d")
+    );
+    let file = rfd::AsyncFileDialog::new()
+        .set_title("Export Backups")
+        .set_file_name(&file_name)
+        .set_directory(dirs::home_dir().unwrap_or_default())
+        .save_file()
+        .await;
+
+    // Handle user cancellation
+    let file = match file {
+        Some(f) => f,
+        None => return Err("Export cancelled".to_string()),
+    };
+
+    let path = file.path();
+    export_backups_in(&backup_dir, path, &dirs::home_dir().unwrap_or_default())
+        .map_err(|e| format!("Failed to export backups: {}", e))?;
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Import the backups of an archive chosen in a file dialog
+///
+/// Every backup's SHA-256 is verified before any is added; backups already
+/// present are skipped.
+///
+/// # Arguments
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(ImportResult)` - Imported backups and the number skipped
+/// * `Err(String)` - "Import cancelled" if user closes dialog, or IO/verification errors
+#[tauri::command]
+pub async fn import_backups(
+    app_settings: State<'_, SettingsState>,
+) -> Result<ImportResult, String> {
+    let backup_dir = app_settings.paths().backup_dir;
+    let file = rfd::AsyncFileDialog::new()
+        .set_title("Import Backups")
+        .add_filter("Backup archive", &["gz", "tgz"])
+        .set_directory(dirs::home_dir().unwrap_or_default())
+        .pick_file()
+        .await;
+
+    // Handle user cancellation
+    let file = match file {
+        Some(f) => f,
+        None => return Err("Import cancelled".to_string()),
+    };
+
+    import_backups_in(
+        &backup_dir,
+        file.path(),
+        &dirs::home_dir().unwrap_or_default(),
+    )
+    .map_err(|e| format!("Failed to import backups: {}", e))
+}
+
+/// Check every backup for missing, corrupted or unparseable content
+///
+/// # Arguments
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(AuditReport)` - Number of backups checked and the problems found
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn audit_backups(app_settings: State<'_, SettingsState>) -> Result<AuditReport, String> {
+    audit_backups_in(&app_settings.paths().backup_dir)
+        .map_err(|e| format!("Failed to audit backups: {}", e))
+}
+
+/// Move problem backups out of the backup directory
+///
+/// # Arguments
+/// * `kinds` - Problems to quarantine (missing, corrupt and orphaned if None)
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(QuarantineResult)` - Quarantine directory and the problems moved there
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn quarantine_backups(
+    kinds: Option<Vec<BackupIssueKind>>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<QuarantineResult, String> {
+    let kinds = kinds.unwrap_or_else(|| {
+        vec![
+            BackupIssueKind::Missing,
+            BackupIssueKind::Corrupt,
+            BackupIssueKind::Orphaned,
+        ]
+    });
+    quarantine_backups_in(&app_settings.paths().backup_dir, &kinds)
+        .map_err(|e| format!("Failed to quarantine backups: {}", e))
+}
+
+/// Get how the history of config files is kept
+///
+/// # Arguments
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(HistorySettings)` - Current settings (the backup store if none were set)
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn get_history_settings(
+    app_settings: State<'_, SettingsState>,
+) -> Result<HistorySettings, String> {
+    HistorySettings::load(&app_settings.paths().config_dir)
+        .map_err(|e| format!("Failed to read history settings: {}", e))
+}
+
+/// Set how the history of config files is kept
+///
+/// Switching backends does not move existing backups; each backend lists its own.
+///
+/// # Arguments
+/// * `settings` - New history settings
+/// * `app_settings` - Application settings
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn set_history_settings(
+    settings: HistorySettings,
+    app_settings: State<'_, SettingsState>,
+) -> Result<(), String> {
+    settings
+        .save(&app_settings.paths().config_dir)
+        .map_err(|e| format!("Failed to save history settings: {}", e))
+}
+
+/// Open the history of a config file
+pub(crate) fn open_history(paths: &AppPaths, config_path: &str) -> Result<ConfigHistory, String> {
+    ConfigHistory::open(paths, config_path)
+        .map_err(|e| format!("Failed to open backup history: {}", e))
+}
+
+/// Record a write of a config file in its history
+///
+/// Failures are logged; the write they follow already succeeded.
+pub(crate) fn record_write_logged(history: &ConfigHistory, description: &str) {
+    if let Err(e) = history.after_write(description) {
+        eprintln!("Failed to record config write: {}", e);
+    }
+}
+
+/// Find a backup of a config file by its ID
+///
+/// Backups of other config files are not found, so they cannot be restored over it.
+fn find_backup(history: &ConfigHistory, backup_id: &str) -> Result<Backup, String> {
+    history
+        .find(backup_id)
+        .map_err(|e| format!("Failed to list backups: {}", e))?
+        .ok_or_else(|| "Backup not found".to_string())
+}
+
+/// Text of a backup, or of the live config file (empty if it does not exist yet)
+fn version_text(
+    history: &ConfigHistory,
+    backup_id: Option<&str>,
+    live_path: &str,
+) -> Result<String, String> {
+    match backup_id {
+        Some(id) => {
+            let content = history
+                .read(&find_backup(history, id)?)
+                .map_err(|e| format!("Failed to read backup: {}", e))?;
+            Ok(String::from_utf8_lossy(&content).to_string())
+        }
+        None if !std::path::Path::new(live_path).exists() => Ok(String::new()),
+        None => read_config_safe(live_path).map_err(|e| format!("Failed to read config: {}", e)),
+    }
+}
+
+/// Parse a backup of a config file
+fn backup_config(history: &ConfigHistory, backup_id: &str) -> Result<ConfigFile, String> {
+    let backup = find_backup(history, backup_id)?;
+    let content = history
+        .read(&backup)
+        .map_err(|e| format!("Failed to read backup: {}", e))?;
+    let parsed = parse_config(&String::from_utf8_lossy(&content))
+        .map_err(|errors| format!("Failed to parse backup: {} errors", errors.len()))?;
+
+    Ok(parsed.to_config_file(backup.original_path.to_string_lossy().to_string()))
+}
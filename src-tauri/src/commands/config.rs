@@ -1,26 +1,105 @@
 /// Configuration management Tauri commands
-use crate::models::{ConfigFile, Shortcut};
-use crate::parser::parse_config;
+use crate::commands::backups::{open_history, record_write_logged};
+use crate::commands::settings::SettingsState;
+use crate::models::{
+    BackupTrigger, ConfigFile, KeyCombination, ModeDeclaration, Shortcut, WILDCARD_PROCESS,
+};
+use crate::parser::{parse_config, ALLOW_DIRECTIVE, DESCRIPTION_DIRECTIVE, TAGS_DIRECTIVE};
 use crate::services::file_io::{read_config_safe, write_config_atomic};
-use crate::utils::path::{expand_path, get_default_config_path};
+use crate::services::key_index::KeyQueryResult;
+use crate::services::system_shortcuts::{load_system_shortcuts, SystemShortcutDb};
+use crate::services::validation::ValidationResult;
+use crate::services::validation_cache::{ValidationCache, ValidationDelta};
+use crate::utils::path::{expand_path, get_config_dir, get_default_config_path};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::State;
 
 /// Global state for the current configuration
 pub struct ConfigState {
+    /// skhd config directory, holding the system shortcut overrides
+    config_dir: PathBuf,
+
     pub config: Mutex<Option<ConfigFile>>,
+
+    /// Diagnostics of the current configuration, updated as shortcuts are edited
+    pub validation: Mutex<Option<ValidationCache>>,
 }
 
 impl Default for ConfigState {
     fn default() -> Self {
-        Self::new()
+        Self::new(get_config_dir())
     }
 }
 
 impl ConfigState {
-    pub fn new() -> Self {
+    /// State of a config directory, with no configuration loaded
+    pub fn new(config_dir: PathBuf) -> Self {
         Self {
+            config_dir,
             config: Mutex::new(None),
+            validation: Mutex::new(None),
+        }
+    }
+
+    /// skhd config directory
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
+    }
+
+    /// System shortcut database with the overrides of the config directory
+    pub fn system_shortcuts(&self) -> SystemShortcutDb {
+        load_system_shortcuts(&self.config_dir)
+    }
+
+    /// Drop cached diagnostics (after the configuration was replaced)
+    pub fn invalidate_validation(&self) {
+        *self.validation.lock().unwrap() = None;
+    }
+
+    /// Revalidate after one shortcut of `config` changed
+    ///
+    /// `before`/`after` are the shortcut before and after the edit (None when it was
+    /// created/deleted). Without a current cache for `config` (none, another file, or
+    /// changed settings files), everything is validated.
+    pub fn revalidate(
+        &self,
+        config: &ConfigFile,
+        before: Option<&Shortcut>,
+        after: Option<&Shortcut>,
+    ) -> ValidationDelta {
+        let mut cache = self.validation.lock().unwrap();
+        match cache.as_mut() {
+            Some(cache) if cache.is_current(config) => cache.apply(config, before, after),
+            _ => {
+                let rebuilt = ValidationCache::build(config, &self.config_dir);
+                let delta = rebuilt.reset_delta();
+                *cache = Some(rebuilt);
+                delta
+            }
+        }
+    }
+
+    /// Cached diagnostics of `config`, validating it first if needed
+    pub fn validation_result(&self, config: &ConfigFile) -> ValidationResult {
+        self.with_cache(config, ValidationCache::result)
+    }
+
+    /// Bindings for a combination in `config`, from the cached key index
+    pub fn find_bindings(
+        &self,
+        config: &ConfigFile,
+        combination: &KeyCombination,
+    ) -> KeyQueryResult {
+        self.with_cache(config, |cache| cache.query(combination, config))
+    }
+
+    /// Run `f` on the cache of `config`, building it first if needed
+    fn with_cache<T>(&self, config: &ConfigFile, f: impl FnOnce(&ValidationCache) -> T) -> T {
+        let mut cache = self.validation.lock().unwrap();
+        match cache.as_ref() {
+            Some(cache) if cache.is_current(config) => f(cache),
+            _ => f(cache.insert(ValidationCache::build(config, &self.config_dir))),
         }
     }
 }
@@ -41,29 +120,11 @@ fn load_config_from_path(
         .map_err(|errors| format!("Failed to parse config: {} errors", errors.len()))?;
 
     // Convert parsed config to ConfigFile
-    let path_str = path.to_string_lossy().to_string();
-    let mut config = ConfigFile::new(path_str);
-
-    for parsed_shortcut in parsed.shortcuts() {
-        let shortcut = Shortcut::new(
-            parsed_shortcut.modifiers.clone(),
-            parsed_shortcut.key.clone(),
-            parsed_shortcut.command.clone(),
-            parsed_shortcut.line_number,
-        );
-        config.add_shortcut(shortcut);
-    }
-
-    // Extract comments
-    for parsed_comment in parsed.comments() {
-        config.global_comments.push(parsed_comment.text.clone());
-    }
-
-    // Reset modified flag since we just loaded
-    config.is_modified = false;
+    let config = parsed.to_config_file(path.to_string_lossy().to_string());
 
     // Update state
     *state.config.lock().unwrap() = Some(config.clone());
+    state.invalidate_validation();
 
     Ok(config)
 }
@@ -171,12 +232,18 @@ pub async fn import_config(state: State<'_, ConfigState>) -> Result<ConfigFile,
 ///
 /// # Arguments
 /// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
 /// * `Ok(String)` - Path where configuration was exported
 /// * `Err(String)` - "Export cancelled" if user closes dialog, or validation/IO errors
 #[tauri::command]
-pub async fn export_config(state: State<'_, ConfigState>) -> Result<String, String> {
+pub async fn export_config(
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<String, String> {
+    let paths = app_settings.paths();
+
     // Get current config and serialize it (in separate scope to drop lock before await)
     let content = {
         let locked_config = state.config.lock().unwrap();
@@ -215,8 +282,15 @@ pub async fn export_config(state: State<'_, ConfigState>) -> Result<String, Stri
 
     let path = file.path();
 
+    // Back up the file being replaced, if any
+    let history = open_history(&paths, &path.to_string_lossy())?;
+    history
+        .before_overwrite(BackupTrigger::BeforeSave)
+        .map_err(|e| format!("Failed to back up config before saving: {}", e))?;
+
     // Write atomically
     write_config_atomic(path, &content).map_err(|e| format!("Failed to write config: {}", e))?;
+    record_write_logged(&history, "Save configuration");
 
     Ok(path.to_string_lossy().to_string())
 }
@@ -226,23 +300,36 @@ pub async fn export_config(state: State<'_, ConfigState>) -> Result<String, Stri
 /// # Arguments
 /// * `config` - Configuration to save
 /// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
 /// * `Ok(())` on success
 /// * `Err(String)` on failure
 #[tauri::command]
-pub fn save_config(config: ConfigFile, state: State<'_, ConfigState>) -> Result<(), String> {
+pub fn save_config(
+    config: ConfigFile,
+    state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
+) -> Result<(), String> {
     // Serialize config back to skhd format
     let content = serialize_config(&config);
 
+    // Back up the version on disk before it is overwritten
+    let history = open_history(&app_settings.paths(), &config.file_path)?;
+    history
+        .before_overwrite(BackupTrigger::BeforeSave)
+        .map_err(|e| format!("Failed to back up config before saving: {}", e))?;
+
     // Write atomically
     write_config_atomic(&config.file_path, &content)
         .map_err(|e| format!("Failed to write config: {}", e))?;
+    record_write_logged(&history, "Save configuration");
 
     // Update state with saved version
     let mut saved_config = config.clone();
     saved_config.is_modified = false;
     *state.config.lock().unwrap() = Some(saved_config);
+    state.invalidate_validation();
 
     Ok(())
 }
@@ -255,6 +342,7 @@ pub fn save_config(config: ConfigFile, state: State<'_, ConfigState>) -> Result<
 /// # Arguments
 /// * `config` - Configuration to save
 /// * `state` - Application state
+/// * `app_settings` - Application settings
 ///
 /// # Returns
 /// * `Ok(ConfigFile)` - Updated config with new file_path on success
@@ -263,7 +351,10 @@ pub fn save_config(config: ConfigFile, state: State<'_, ConfigState>) -> Result<
 pub async fn save_as_config(
     mut config: ConfigFile,
     state: State<'_, ConfigState>,
+    app_settings: State<'_, SettingsState>,
 ) -> Result<ConfigFile, String> {
+    let paths = app_settings.paths();
+
     // Serialize config back to skhd format
     let content = serialize_config(&config);
 
@@ -284,8 +375,15 @@ pub async fn save_as_config(
     let path = file.path();
     let path_str = path.to_string_lossy().to_string();
 
+    // Back up the file being replaced, if any
+    let history = open_history(&paths, &path.to_string_lossy())?;
+    history
+        .before_overwrite(BackupTrigger::BeforeSave)
+        .map_err(|e| format!("Failed to back up config before saving: {}", e))?;
+
     // Write atomically
     write_config_atomic(path, &content).map_err(|e| format!("Failed to write config: {}", e))?;
+    record_write_logged(&history, "Save configuration");
 
     // Update config with new file path and mark as saved
     config.file_path = path_str.clone();
@@ -294,6 +392,7 @@ pub async fn save_as_config(
 
     // Update state with saved version
     *state.config.lock().unwrap() = Some(config.clone());
+    state.invalidate_validation();
 
     Ok(config)
 }
@@ -340,8 +439,18 @@ pub fn serialize_config(config: &ConfigFile) -> String {
     let mut shortcuts = config.shortcuts.clone();
     shortcuts.sort_by_key(|s| s.line_number);
 
+    // Mode declarations are written in line order between the shortcuts
+    let mut modes = config.modes.clone();
+    modes.sort_by_key(|m| m.line_number);
+    let mut modes = modes.into_iter().peekable();
+
     // Serialize each shortcut
-    for shortcut in shortcuts {
+    let mut shortcuts = shortcuts.into_iter().peekable();
+    while let Some(shortcut) = shortcuts.next() {
+        while let Some(mode) = modes.next_if(|m| m.line_number < shortcut.line_number) {
+            output.push_str(&serialize_mode(&mode));
+        }
+
         // Add comment if present
         if let Some(comment) = &shortcut.comment {
             output.push_str("# ");
@@ -349,6 +458,27 @@ pub fn serialize_config(config: &ConfigFile) -> String {
             output.push('\n');
         }
 
+        // Add metadata directives (read back by the parser)
+        if let Some(description) = &shortcut.description {
+            output.push_str(&format!("# {} {}\n", DESCRIPTION_DIRECTIVE, description));
+        }
+
+        if !shortcut.tags.is_empty() {
+            output.push_str(&format!(
+                "# {} {}\n",
+                TAGS_DIRECTIVE,
+                shortcut.tags.join(" ")
+            ));
+        }
+
+        if !shortcut.allowed_rules.is_empty() {
+            output.push_str(&format!(
+                "# {} {}\n",
+                ALLOW_DIRECTIVE,
+                shortcut.allowed_rules.join(" ")
+            ));
+        }
+
         // Build modifier string
         let modifier_str = if shortcut.modifiers.is_empty() {
             String::new()
@@ -358,16 +488,88 @@ pub fn serialize_config(config: &ConfigFile) -> String {
             format!("{} ", mods.join(" + "))
         };
 
-        // Write shortcut line: [modifiers] - key : command
-        output.push_str(&format!(
-            "{}- {} : {}\n",
-            modifier_str, shortcut.key, shortcut.command
-        ));
+        let mode_str = match shortcut.mode.as_deref() {
+            Some(mode) if !mode.trim().is_empty() => format!("{} < ", mode),
+            _ => String::new(),
+        };
+
+        // Consecutive process list entries for the same key are written as one list
+        if shortcut.process.is_some() {
+            let mut entries = vec![shortcut];
+            while let Some(next) = shortcuts.next_if(|s| is_same_process_list(&entries[0], s)) {
+                entries.push(next);
+            }
+
+            output.push_str(&format!(
+                "{}{}- {} [\n",
+                mode_str, modifier_str, entries[0].key
+            ));
+            for entry in &entries {
+                output.push_str(&serialize_process_entry(entry));
+            }
+            output.push_str("]\n");
+            continue;
+        }
+
+        // Write shortcut line: [modes <] [modifiers] - key : command (or ; mode)
+        match &shortcut.activates {
+            Some(target) => output.push_str(&format!(
+                "{}{}- {} ; {}\n",
+                mode_str, modifier_str, shortcut.key, target
+            )),
+            None => output.push_str(&format!(
+                "{}{}- {} : {}\n",
+                mode_str, modifier_str, shortcut.key, shortcut.command
+            )),
+        }
+    }
+
+    for mode in modes {
+        output.push_str(&serialize_mode(&mode));
     }
 
     output
 }
 
+/// Check whether `next` is another entry of the process list started by `first`
+fn is_same_process_list(first: &Shortcut, next: &Shortcut) -> bool {
+    let mut first_mods = first.modifiers.clone();
+    let mut next_mods = next.modifiers.clone();
+    first_mods.sort();
+    next_mods.sort();
+
+    next.process.is_some()
+        && first.key == next.key
+        && first.mode == next.mode
+        && first_mods == next_mods
+}
+
+/// Serialize a process list entry: `"app" : command`, `* : command` or `"app" ~`
+fn serialize_process_entry(entry: &Shortcut) -> String {
+    let process = match entry.process.as_deref() {
+        Some(WILDCARD_PROCESS) | None => WILDCARD_PROCESS.to_string(),
+        Some(name) => format!("\"{}\"", name),
+    };
+    if entry.passthrough {
+        format!("    {} ~\n", process)
+    } else {
+        format!("    {} : {}\n", process, entry.command)
+    }
+}
+
+/// Serialize a mode declaration: `:: name [@] [: command]`
+fn serialize_mode(mode: &ModeDeclaration) -> String {
+    let mut line = format!(":: {}", mode.name);
+    if mode.capture {
+        line.push_str(" @");
+    }
+    if let Some(command) = &mode.command {
+        line.push_str(&format!(" : {}", command));
+    }
+    line.push('\n');
+    line
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -417,4 +619,102 @@ mod tests {
         let serialized = serialize_config(&config);
         assert!(serialized.contains("- f1 : echo test"));
     }
+
+    #[test]
+    fn test_serialize_modes_and_process_lists_round_trip() {
+        let text = ":: window @ : echo window\nalt - w ; window\nwindow < - escape ; default\nwindow < - h : yabai -m window --focus west\n:: resize\nhyper - t [\n    \"iTerm2\" : echo term\n    \"Finder\" ~\n    * : open -a iTerm\n]\nalt - t : echo after\n";
+        let parsed = parse_config(text).unwrap();
+
+        let mut config = ConfigFile::new("/test/path".to_string());
+        for parsed_shortcut in parsed.shortcuts() {
+            let mut shortcut = Shortcut::new(
+                parsed_shortcut.modifiers.clone(),
+                parsed_shortcut.key.clone(),
+                parsed_shortcut.command.clone(),
+                parsed_shortcut.line_number,
+            );
+            shortcut.mode = parsed_shortcut.mode.clone();
+            shortcut.activates = parsed_shortcut.activates.clone();
+            shortcut.process = parsed_shortcut.process.clone();
+            shortcut.passthrough = parsed_shortcut.passthrough;
+            config.add_shortcut(shortcut);
+        }
+        for parsed_mode in parsed.modes() {
+            config.modes.push(ModeDeclaration {
+                name: parsed_mode.name.clone(),
+                capture: parsed_mode.capture,
+                command: parsed_mode.command.clone(),
+                line_number: parsed_mode.line_number,
+            });
+        }
+
+        assert_eq!(serialize_config(&config), text);
+    }
+
+    #[test]
+    fn test_serialize_keeps_modes_after_inserted_shortcut() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+
+        let original = Shortcut::new(
+            vec!["alt".to_string()],
+            "a".to_string(),
+            "echo a".to_string(),
+            1,
+        );
+        let original_id = original.id.clone();
+        config.add_shortcut(original);
+        config.modes.push(ModeDeclaration {
+            name: "window".to_string(),
+            capture: false,
+            command: None,
+            line_number: 2,
+        });
+        let mut in_mode = Shortcut::new(vec![], "h".to_string(), "echo h".to_string(), 3);
+        in_mode.mode = Some("window".to_string());
+        config.add_shortcut(in_mode);
+
+        let copy = Shortcut::new(
+            vec!["alt".to_string()],
+            "b".to_string(),
+            "echo a".to_string(),
+            0,
+        );
+        assert!(config.insert_shortcut_after(&original_id, copy));
+
+        assert_eq!(config.modes[0].line_number, 3);
+        assert_eq!(
+            serialize_config(&config),
+            "alt - a : echo a\nalt - b : echo a\n:: window\nwindow < - h : echo h\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize_metadata_round_trip() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+
+        let mut shortcut = Shortcut::new(
+            vec!["alt".to_string()],
+            "h".to_string(),
+            "yabai -m window --focus west".to_string(),
+            1,
+        );
+        shortcut.tags = vec!["work".to_string(), "yabai".to_string()];
+        shortcut.description = Some("Focus the west window".to_string());
+        shortcut.allowed_rules = vec!["SKHD002".to_string()];
+        config.add_shortcut(shortcut);
+
+        let serialized = serialize_config(&config);
+        assert!(serialized.contains(
+            "# @description Focus the west window\n# @tags work yabai\n# @allow SKHD002\nalt - h"
+        ));
+
+        let parsed = parse_config(&serialized).unwrap();
+        let shortcuts = parsed.shortcuts();
+        assert_eq!(shortcuts[0].tags, vec!["work", "yabai"]);
+        assert_eq!(
+            shortcuts[0].description,
+            Some("Focus the west window".to_string())
+        );
+        assert_eq!(shortcuts[0].allowed_rules, vec!["SKHD002"]);
+    }
 }
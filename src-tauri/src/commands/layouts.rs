@@ -0,0 +1,54 @@
+/// Keyboard layout Tauri commands
+use crate::commands::config::ConfigState;
+use crate::models::KeyboardLayout;
+use crate::services::keyboard_layout::{self, KeyDescription, LayoutWarning};
+use tauri::State;
+
+/// Get all known keyboard layouts
+///
+/// # Returns
+/// * `Ok(Vec<KeyboardLayout>)` - Layout tables, in data file order
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn get_keyboard_layouts() -> Result<Vec<KeyboardLayout>, String> {
+    keyboard_layout::get_layouts()
+}
+
+/// Describe what a key means on each keyboard layout
+///
+/// # Arguments
+/// * `key` - Key as written in the config (e.g. `[` or `return`)
+/// * `active_layout` - ID of the layout skhd runs with
+///
+/// # Returns
+/// * `Ok(KeyDescription)` - Physical key on the active layout and per-layout meaning
+/// * `Err(String)` - Unknown layout
+#[tauri::command]
+pub fn describe_key_on_layouts(
+    key: String,
+    active_layout: String,
+) -> Result<KeyDescription, String> {
+    let layouts = keyboard_layout::get_layouts()?;
+    keyboard_layout::describe_key(&key, &active_layout, &layouts)
+}
+
+/// Find shortcuts in the current configuration whose key does not exist on a layout
+///
+/// # Arguments
+/// * `layout_id` - ID of the layout to check against
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(Vec<LayoutWarning>)` - One warning per affected shortcut
+/// * `Err(String)` - Unknown layout or no config loaded
+#[tauri::command]
+pub fn check_keyboard_layout(
+    layout_id: String,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<LayoutWarning>, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    let layouts = keyboard_layout::get_layouts()?;
+    keyboard_layout::check_layout(config, &layout_id, &layouts)
+}
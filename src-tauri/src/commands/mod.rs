@@ -3,9 +3,13 @@ pub mod applications;
 pub mod backups;
 pub mod config;
 pub mod file_picker;
+pub mod layouts;
 pub mod logs;
+pub mod search;
 pub mod service;
+pub mod settings;
 pub mod shortcuts;
+pub mod tags;
 pub mod templates;
 pub mod testing;
 pub mod theme;
@@ -13,13 +17,17 @@ pub mod validation;
 
 // Re-export commands for easy access
 pub use applications::get_installed_applications;
-pub use backups::{create_backup, list_backups, restore_backup};
+pub use backups::{audit_backups, create_backup, diff_backups, export_backups, get_backup_retention, get_backup_shortcuts, get_history_settings, import_backups, list_backups, quarantine_backups, restore_backup, restore_shortcuts, set_backup_retention, set_history_settings};
 pub use config::{detect_active_config, export_config, import_config, load_config, reload_config, save_config};
 pub use file_picker::{check_file_executable, detect_script_interpreter, escape_path_for_shell, open_file_picker};
+pub use layouts::{check_keyboard_layout, describe_key_on_layouts, get_keyboard_layouts};
 pub use logs::{is_log_stream_running, start_log_stream, stop_log_stream};
+pub use search::{find_key_bindings, suggest_free_keys};
 pub use service::{get_service_status, reload_service};
-pub use shortcuts::{create_shortcut, delete_shortcut, update_shortcut};
+pub use settings::{get_app_settings, set_app_settings};
+pub use shortcuts::{create_shortcut, delete_shortcut, duplicate_shortcut, update_shortcut};
+pub use tags::{filter_shortcuts_by_tags, list_tags};
 pub use templates::{generate_command_from_template, get_command_categories, get_command_templates};
-pub use testing::{cancel_shortcut_execution, execute_shortcut_command, execute_test_command, test_shortcut};
+pub use testing::{cancel_shortcut_execution, check_command_safety, execute_shortcut_command, execute_test_command, test_shortcut};
 pub use theme::{get_system_theme, start_theme_monitor, stop_theme_monitor};
-pub use validation::{validate_config, validate_shortcut};
+pub use validation::{check_config_syntax, get_config_diagnostics, get_lint_rules, get_system_shortcuts, validate_config, validate_config_text, validate_shortcut};
@@ -0,0 +1,51 @@
+/// Key combination search Tauri commands
+use crate::commands::config::ConfigState;
+use crate::models::KeyCombination;
+use crate::services::free_keys::{
+    suggest_free_keys as suggest_free_keys_service, FreeKeyRequest, FreeKeySuggestion,
+};
+use crate::services::key_index::KeyQueryResult;
+use tauri::State;
+
+/// Find every binding for a key chord ("what is bound to X?")
+///
+/// Looks the chord up in the key index kept with the cached diagnostics.
+///
+/// # Arguments
+/// * `chord` - Key chord such as `shift + cmd - f` or `hyper - space` (any modifier order)
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(KeyQueryResult)` - Exact matches across modes plus near-matches differing by one modifier
+/// * `Err(String)` - Invalid chord or no config loaded
+#[tauri::command]
+pub fn find_key_bindings(
+    chord: String,
+    state: State<'_, ConfigState>,
+) -> Result<KeyQueryResult, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    let combination = KeyCombination::parse(&chord)?;
+    Ok(state.find_bindings(config, &combination))
+}
+
+/// Suggest unused key combinations for a new shortcut
+///
+/// # Arguments
+/// * `request` - Acceptable modifier sets, optional mode, command and result limit
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(Vec<FreeKeySuggestion>)` - Free combinations, best first
+/// * `Err(String)` - Invalid request or no config loaded
+#[tauri::command]
+pub fn suggest_free_keys(
+    request: FreeKeyRequest,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<FreeKeySuggestion>, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    suggest_free_keys_service(config, &state.system_shortcuts(), &request)
+}
@@ -0,0 +1,101 @@
+/// Application settings Tauri commands
+use crate::services::backup::migrate_legacy_backups;
+use crate::services::settings::{AppPaths, AppSettings};
+use crate::utils::path::get_config_dir;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Global state for the application settings
+pub struct SettingsState {
+    config_dir: PathBuf,
+    settings: Mutex<AppSettings>,
+}
+
+impl Default for SettingsState {
+    fn default() -> Self {
+        Self::load()
+    }
+}
+
+impl SettingsState {
+    /// Settings of a config directory
+    pub fn new(config_dir: PathBuf, settings: AppSettings) -> Self {
+        Self {
+            config_dir,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// Settings of the user's config directory (defaults if they cannot be read)
+    ///
+    /// Backups of older versions in the backup directory are moved into its store.
+    pub fn load() -> Self {
+        let config_dir = get_config_dir();
+        let settings = AppSettings::load(&config_dir).unwrap_or_else(|e| {
+            eprintln!("Failed to load settings, using defaults: {}", e);
+            AppSettings::default()
+        });
+        let state = Self::new(config_dir, settings);
+        migrate_backups(&state.paths());
+        state
+    }
+
+    /// Current settings
+    pub fn settings(&self) -> AppSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Paths following the current settings
+    pub fn paths(&self) -> AppPaths {
+        AppPaths::with_settings(self.config_dir.clone(), &self.settings.lock().unwrap())
+    }
+}
+
+/// Move backups of older versions into the store, reporting failures
+fn migrate_backups(paths: &AppPaths) {
+    if let Err(e) = migrate_legacy_backups(paths) {
+        eprintln!("Failed to migrate old backups: {}", e);
+    }
+}
+
+/// Get the application settings
+///
+/// # Arguments
+/// * `state` - Settings state
+///
+/// # Returns
+/// * `Ok(AppSettings)` - Current settings
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn get_app_settings(state: State<'_, SettingsState>) -> Result<AppSettings, String> {
+    Ok(state.settings())
+}
+
+/// Change the application settings
+///
+/// A new backup directory is used for backups from now on; existing backups
+/// stay where they are (export and import them to move them). Backups of older
+/// versions in the new directory are moved into its store.
+///
+/// # Arguments
+/// * `settings` - New settings
+/// * `state` - Settings state
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(String)` - Invalid settings or IO errors
+#[tauri::command]
+pub fn set_app_settings(
+    settings: AppSettings,
+    state: State<'_, SettingsState>,
+) -> Result<(), String> {
+    settings.validate()?;
+    settings
+        .save(&state.config_dir)
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    *state.settings.lock().unwrap() = settings;
+    migrate_backups(&state.paths());
+    Ok(())
+}
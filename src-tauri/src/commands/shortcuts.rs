@@ -1,7 +1,8 @@
 /// Shortcut management Tauri commands
 use crate::commands::config::ConfigState;
-use crate::models::Shortcut;
-use tauri::State;
+use crate::models::{normalize_tag, ConfigFile, Shortcut};
+use crate::services::validation_cache::{ValidationDelta, VALIDATION_EVENT};
+use tauri::{AppHandle, Emitter, State};
 
 /// Request to create a new shortcut
 #[derive(serde::Deserialize)]
@@ -10,7 +11,16 @@ pub struct CreateShortcutRequest {
     pub key: String,
     pub command: String,
     pub mode: Option<String>,
+    /// Mode to switch to instead of running a command
+    #[serde(default)]
+    pub activates: Option<String>,
     pub comment: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub allowed_rules: Vec<String>,
 }
 
 /// Request to update an existing shortcut
@@ -20,15 +30,74 @@ pub struct UpdateShortcutRequest {
     pub modifiers: Vec<String>,
     pub key: String,
     pub command: String,
+    /// Mode (keeps the existing one when omitted, `"default"` for the default mode)
+    #[serde(default)]
     pub mode: Option<String>,
+    /// Mode to switch to (keeps the existing one when omitted and `command` is empty)
+    #[serde(default)]
+    pub activates: Option<String>,
     pub comment: Option<String>,
+    /// Tags (keeps the existing ones when omitted)
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Description (keeps the existing one when omitted, cleared when empty)
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Suppressed lint rules (keeps the existing ones when omitted)
+    #[serde(default)]
+    pub allowed_rules: Option<Vec<String>>,
+}
+
+/// Request to duplicate a shortcut into another mode or key combination
+#[derive(serde::Deserialize)]
+pub struct DuplicateShortcutRequest {
+    /// ID of the shortcut to copy
+    pub id: String,
+    /// Target modifiers (keeps the original's when omitted)
+    #[serde(default)]
+    pub modifiers: Option<Vec<String>>,
+    /// Target key (keeps the original's when omitted)
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Target mode (keeps the original's when omitted, `"default"` for the default mode)
+    #[serde(default)]
+    pub mode: Option<String>,
+}
+
+/// Validate a shortcut and check it does not clash with existing bindings
+///
+/// Shared by create, update and duplicate so all three apply the same rules.
+fn check_shortcut(config: &ConfigFile, shortcut: &Shortcut) -> Result<(), String> {
+    shortcut
+        .validate()
+        .map_err(|e| format!("Invalid shortcut: {}", e))?;
+
+    let duplicates = config.find_duplicates(shortcut);
+    if !duplicates.is_empty() {
+        return Err(format!(
+            "Duplicate key combination: {}",
+            shortcut.key_combination_string()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Send the diagnostics changed by an edit to the frontend
+pub(crate) fn emit_validation(app_handle: &AppHandle, delta: &ValidationDelta) {
+    if let Err(e) = app_handle.emit(VALIDATION_EVENT, delta) {
+        eprintln!("Failed to emit {} event: {}", VALIDATION_EVENT, e);
+    }
 }
 
 /// Create a new shortcut
 ///
+/// Emits a `validation-changed` event with the diagnostics the new shortcut changed.
+///
 /// # Arguments
 /// * `request` - Shortcut creation request
 /// * `state` - Application state
+/// * `app_handle` - Tauri AppHandle for the validation event
 ///
 /// # Returns
 /// * `Ok(Shortcut)` - Created shortcut
@@ -37,6 +106,7 @@ pub struct UpdateShortcutRequest {
 pub fn create_shortcut(
     request: CreateShortcutRequest,
     state: State<'_, ConfigState>,
+    app_handle: AppHandle,
 ) -> Result<Shortcut, String> {
     let mut config_guard = state.config.lock().unwrap();
     let config = config_guard.as_mut().ok_or("No config loaded")?;
@@ -52,34 +122,35 @@ pub fn create_shortcut(
     let mut shortcut = Shortcut::new(request.modifiers, request.key, request.command, line_number);
 
     shortcut.mode = request.mode;
+    shortcut.activates = request.activates;
     shortcut.comment = request.comment;
+    shortcut.tags = request.tags.iter().map(|t| normalize_tag(t)).collect();
+    shortcut.description = request.description;
+    shortcut.allowed_rules = request.allowed_rules;
 
-    // Validate
-    shortcut
-        .validate()
-        .map_err(|e| format!("Invalid shortcut: {}", e))?;
-
-    // Check for duplicates
-    let duplicates = config.find_duplicates(&shortcut);
-    if !duplicates.is_empty() {
-        return Err(format!(
-            "Duplicate key combination: {}",
-            shortcut.key_combination_string()
-        ));
-    }
+    // Validate and check for duplicates
+    check_shortcut(config, &shortcut)?;
 
     // Add to config
     config.add_shortcut(shortcut.clone());
     config.is_modified = true;
 
+    emit_validation(
+        &app_handle,
+        &state.revalidate(config, None, Some(&shortcut)),
+    );
+
     Ok(shortcut)
 }
 
 /// Update an existing shortcut
 ///
+/// Emits a `validation-changed` event with the diagnostics the edit changed.
+///
 /// # Arguments
 /// * `request` - Shortcut update request
 /// * `state` - Application state
+/// * `app_handle` - Tauri AppHandle for the validation event
 ///
 /// # Returns
 /// * `Ok(Shortcut)` - Updated shortcut
@@ -88,6 +159,7 @@ pub fn create_shortcut(
 pub fn update_shortcut(
     request: UpdateShortcutRequest,
     state: State<'_, ConfigState>,
+    app_handle: AppHandle,
 ) -> Result<Shortcut, String> {
     let mut config_guard = state.config.lock().unwrap();
     let config = config_guard.as_mut().ok_or("No config loaded")?;
@@ -95,58 +167,165 @@ pub fn update_shortcut(
     // Find existing shortcut
     let existing = config
         .find_shortcut(&request.id)
-        .ok_or("Shortcut not found")?;
+        .ok_or("Shortcut not found")?
+        .clone();
+
+    let updated = build_update(&existing, request);
+
+    // Validate and check for duplicates (excluding this shortcut)
+    check_shortcut(config, &updated)?;
+
+    // Update in config
+    config.update_shortcut(updated.clone());
+    config.is_modified = true;
+
+    emit_validation(
+        &app_handle,
+        &state.revalidate(config, Some(&existing), Some(&updated)),
+    );
 
-    // Create updated shortcut (preserving line number)
+    Ok(updated)
+}
+
+/// Build the edited version of a shortcut described by an update request
+fn build_update(existing: &Shortcut, request: UpdateShortcutRequest) -> Shortcut {
+    // Preserve the ID and line number
     let mut updated = Shortcut::with_id(
-        request.id.clone(),
+        request.id,
         request.modifiers,
         request.key,
         request.command,
         existing.line_number,
     );
 
-    updated.mode = request.mode;
+    updated.mode = match request.mode.as_deref() {
+        None => existing.mode.clone(),
+        Some("") | Some("default") => None,
+        Some(mode) => Some(mode.to_string()),
+    };
+    updated.activates = match request.activates {
+        Some(mode) => Some(mode),
+        None if updated.command.trim().is_empty() => existing.activates.clone(),
+        None => None,
+    };
+    // The process list entry is not editable here; keep its app and pass-through flag
+    updated.process = existing.process.clone();
+    updated.passthrough = existing.passthrough;
     updated.comment = request.comment;
+    updated.tags = match request.tags {
+        Some(tags) => tags.iter().map(|t| normalize_tag(t)).collect(),
+        None => existing.tags.clone(),
+    };
+    updated.description = match request.description {
+        Some(description) if description.trim().is_empty() => None,
+        Some(description) => Some(description),
+        None => existing.description.clone(),
+    };
+    updated.allowed_rules = request
+        .allowed_rules
+        .unwrap_or_else(|| existing.allowed_rules.clone());
 
-    // Validate
     updated
-        .validate()
-        .map_err(|e| format!("Invalid shortcut: {}", e))?;
+}
 
-    // Check for duplicates (excluding this shortcut)
-    let duplicates = config.find_duplicates(&updated);
-    if !duplicates.is_empty() {
-        return Err(format!(
-            "Duplicate key combination: {}",
-            updated.key_combination_string()
-        ));
-    }
+/// Duplicate a shortcut into another mode or modifier set
+///
+/// The copy gets a new ID, is validated and checked for conflicts like
+/// `create_shortcut`, and is inserted directly below the original.
+///
+/// Later shortcuts move down a line, so the validation cache is rebuilt and a
+/// `validation-changed` event with `reset` set is emitted.
+///
+/// # Arguments
+/// * `request` - Original shortcut ID and target mode/modifiers/key
+/// * `state` - Application state
+/// * `app_handle` - Tauri AppHandle for the validation event
+///
+/// # Returns
+/// * `Ok(Shortcut)` - The new copy
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn duplicate_shortcut(
+    request: DuplicateShortcutRequest,
+    state: State<'_, ConfigState>,
+    app_handle: AppHandle,
+) -> Result<Shortcut, String> {
+    let mut config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_mut().ok_or("No config loaded")?;
 
-    // Update in config
-    config.update_shortcut(updated.clone());
+    let original = config
+        .find_shortcut(&request.id)
+        .ok_or("Shortcut not found")?
+        .clone();
+
+    let copy = build_duplicate(&original, request);
+
+    // Validate and check for duplicates in the target mode
+    check_shortcut(config, &copy)?;
+
+    // Insert directly below the original
+    config.insert_shortcut_after(&original.id, copy.clone());
     config.is_modified = true;
 
-    Ok(updated)
+    state.invalidate_validation();
+    emit_validation(&app_handle, &state.revalidate(config, None, Some(&copy)));
+
+    Ok(copy)
+}
+
+/// Build the copy of a shortcut described by a duplicate request
+fn build_duplicate(original: &Shortcut, request: DuplicateShortcutRequest) -> Shortcut {
+    let mut copy = Shortcut::new(
+        request
+            .modifiers
+            .unwrap_or_else(|| original.modifiers.clone()),
+        request.key.unwrap_or_else(|| original.key.clone()),
+        original.command.clone(),
+        original.line_number + 1,
+    );
+
+    copy.mode = match request.mode.as_deref() {
+        None => original.mode.clone(),
+        Some("") | Some("default") => None,
+        Some(mode) => Some(mode.to_string()),
+    };
+    copy.activates = original.activates.clone();
+    copy.process = original.process.clone();
+    copy.passthrough = original.passthrough;
+    copy.comment = original.comment.clone();
+    copy.tags = original.tags.clone();
+    copy.description = original.description.clone();
+    copy.allowed_rules = original.allowed_rules.clone();
+
+    copy
 }
 
 /// Delete a shortcut by ID
 ///
+/// Emits a `validation-changed` event with the diagnostics the deletion changed.
+///
 /// # Arguments
 /// * `id` - Shortcut ID
 /// * `state` - Application state
+/// * `app_handle` - Tauri AppHandle for the validation event
 ///
 /// # Returns
 /// * `Ok(())` on success
 /// * `Err(String)` on failure
 #[tauri::command]
-pub fn delete_shortcut(id: String, state: State<'_, ConfigState>) -> Result<(), String> {
+pub fn delete_shortcut(
+    id: String,
+    state: State<'_, ConfigState>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
     let mut config_guard = state.config.lock().unwrap();
     let config = config_guard.as_mut().ok_or("No config loaded")?;
 
-    config.remove_shortcut(&id).ok_or("Shortcut not found")?;
+    let removed = config.remove_shortcut(&id).ok_or("Shortcut not found")?;
     config.is_modified = true;
 
+    emit_validation(&app_handle, &state.revalidate(config, Some(&removed), None));
+
     Ok(())
 }
 
@@ -161,7 +340,11 @@ mod tests {
             key: "return".to_string(),
             command: "open -a Terminal".to_string(),
             mode: None,
+            activates: None,
             comment: Some("Terminal shortcut".to_string()),
+            tags: vec!["work".to_string()],
+            description: None,
+            allowed_rules: Vec::new(),
         };
 
         assert_eq!(request.modifiers, vec!["cmd"]);
@@ -176,10 +359,193 @@ mod tests {
             key: "f".to_string(),
             command: "open ~".to_string(),
             mode: None,
+            activates: None,
             comment: None,
+            tags: None,
+            description: Some("Open home folder".to_string()),
+            allowed_rules: None,
         };
 
         assert_eq!(request.id, "test-id");
         assert_eq!(request.modifiers.len(), 2);
     }
+
+    fn update_request(existing: &Shortcut, key: &str, command: &str) -> UpdateShortcutRequest {
+        UpdateShortcutRequest {
+            id: existing.id.clone(),
+            modifiers: existing.modifiers.clone(),
+            key: key.to_string(),
+            command: command.to_string(),
+            mode: existing.mode.clone(),
+            activates: None,
+            comment: None,
+            tags: None,
+            description: None,
+            allowed_rules: None,
+        }
+    }
+
+    #[test]
+    fn test_build_update_keeps_mode() {
+        let mut existing = Shortcut::new(
+            Vec::new(),
+            "h".to_string(),
+            "yabai -m window --focus west".to_string(),
+            4,
+        );
+        existing.mode = Some("window".to_string());
+
+        // The edit form sends no mode
+        let mut request = update_request(&existing, "h", "yabai -m window --swap west");
+        request.mode = None;
+        let updated = build_update(&existing, request);
+        assert_eq!(updated.mode, Some("window".to_string()));
+
+        let mut request = update_request(&existing, "h", "yabai -m window --swap west");
+        request.mode = Some("resize".to_string());
+        assert_eq!(
+            build_update(&existing, request).mode,
+            Some("resize".to_string())
+        );
+
+        let mut request = update_request(&existing, "h", "yabai -m window --swap west");
+        request.mode = Some("default".to_string());
+        assert_eq!(build_update(&existing, request).mode, None);
+    }
+
+    #[test]
+    fn test_build_update_keeps_tags_and_description() {
+        let mut existing = Shortcut::new(
+            vec!["cmd".to_string()],
+            "t".to_string(),
+            "open -a Terminal".to_string(),
+            1,
+        );
+        existing.tags = vec!["apps".to_string()];
+        existing.description = Some("Open a terminal".to_string());
+
+        // Omitted fields keep their values
+        let updated = build_update(&existing, update_request(&existing, "t", "open -a iTerm"));
+        assert_eq!(updated.tags, vec!["apps".to_string()]);
+        assert_eq!(updated.description, Some("Open a terminal".to_string()));
+
+        // Given fields replace them, and an empty description clears it
+        let mut request = update_request(&existing, "t", "open -a iTerm");
+        request.tags = Some(vec!["Terminal".to_string()]);
+        request.description = Some(String::new());
+        let updated = build_update(&existing, request);
+        assert_eq!(updated.tags, vec![normalize_tag("Terminal")]);
+        assert_eq!(updated.description, None);
+    }
+
+    #[test]
+    fn test_build_update_keeps_mode_activation() {
+        let mut existing =
+            Shortcut::new(vec!["alt".to_string()], "w".to_string(), String::new(), 3);
+        existing.activates = Some("window".to_string());
+
+        let updated = build_update(&existing, update_request(&existing, "e", ""));
+        assert_eq!(updated.id, existing.id);
+        assert_eq!(updated.line_number, 3);
+        assert_eq!(updated.activates, Some("window".to_string()));
+        assert!(updated.validate().is_ok());
+
+        // Giving a command turns the activation into a regular binding
+        let updated = build_update(&existing, update_request(&existing, "w", "open ~"));
+        assert_eq!(updated.activates, None);
+        assert!(updated.validate().is_ok());
+    }
+
+    #[test]
+    fn test_build_update_keeps_process_entry() {
+        let mut existing = Shortcut::new(
+            vec!["cmd".to_string()],
+            "t".to_string(),
+            "open -a iTerm".to_string(),
+            2,
+        );
+        existing.process = Some("Finder".to_string());
+
+        let updated = build_update(&existing, update_request(&existing, "n", "open ~"));
+        assert_eq!(updated.process, Some("Finder".to_string()));
+        assert!(!updated.passthrough);
+        assert_eq!(updated.key, "n");
+
+        let mut passthrough = existing.clone();
+        passthrough.command = String::new();
+        passthrough.passthrough = true;
+        let updated = build_update(&passthrough, update_request(&passthrough, "t", ""));
+        assert!(updated.passthrough);
+        assert!(updated.validate().is_ok());
+
+        let copy = build_duplicate(
+            &passthrough,
+            DuplicateShortcutRequest {
+                id: passthrough.id.clone(),
+                modifiers: None,
+                key: Some("y".to_string()),
+                mode: None,
+            },
+        );
+        assert_eq!(copy.process, Some("Finder".to_string()));
+        assert!(copy.passthrough);
+    }
+
+    #[test]
+    fn test_build_duplicate() {
+        let mut original = Shortcut::new(
+            vec!["alt".to_string()],
+            "h".to_string(),
+            "yabai -m window --focus west".to_string(),
+            4,
+        );
+        original.tags = vec!["yabai".to_string()];
+
+        let copy = build_duplicate(
+            &original,
+            DuplicateShortcutRequest {
+                id: original.id.clone(),
+                modifiers: None,
+                key: None,
+                mode: Some("window".to_string()),
+            },
+        );
+
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.modifiers, original.modifiers);
+        assert_eq!(copy.key, "h");
+        assert_eq!(copy.command, original.command);
+        assert_eq!(copy.mode, Some("window".to_string()));
+        assert_eq!(copy.tags, original.tags);
+        assert_eq!(copy.line_number, 5);
+
+        let moved = build_duplicate(
+            &copy,
+            DuplicateShortcutRequest {
+                id: copy.id.clone(),
+                modifiers: Some(vec!["alt".to_string(), "shift".to_string()]),
+                key: Some("l".to_string()),
+                mode: Some("default".to_string()),
+            },
+        );
+        assert_eq!(moved.modifiers, vec!["alt", "shift"]);
+        assert_eq!(moved.key, "l");
+        assert_eq!(moved.mode, None);
+
+        // Mode activations have no command of their own
+        let mut activation =
+            Shortcut::new(vec!["alt".to_string()], "w".to_string(), String::new(), 7);
+        activation.activates = Some("window".to_string());
+        let copy = build_duplicate(
+            &activation,
+            DuplicateShortcutRequest {
+                id: activation.id.clone(),
+                modifiers: None,
+                key: Some("e".to_string()),
+                mode: None,
+            },
+        );
+        assert_eq!(copy.activates, Some("window".to_string()));
+        assert!(copy.validate().is_ok());
+    }
 }
@@ -0,0 +1,46 @@
+/// Tag query Tauri commands
+use crate::commands::config::ConfigState;
+use crate::models::{Shortcut, TagSummary};
+use tauri::State;
+
+/// List all tags used in the current configuration
+///
+/// # Arguments
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(Vec<TagSummary>)` - Tags with usage counts, sorted by tag
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn list_tags(state: State<'_, ConfigState>) -> Result<Vec<TagSummary>, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    Ok(config.tag_summaries())
+}
+
+/// Filter shortcuts in the current configuration by tag
+///
+/// # Arguments
+/// * `tags` - Tags to filter by (leading `@` optional, case-insensitive)
+/// * `match_all` - Require every tag instead of any one (defaults to false)
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(Vec<Shortcut>)` - Matching shortcuts in config order
+/// * `Err(String)` - Error message
+#[tauri::command]
+pub fn filter_shortcuts_by_tags(
+    tags: Vec<String>,
+    match_all: Option<bool>,
+    state: State<'_, ConfigState>,
+) -> Result<Vec<Shortcut>, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    Ok(config
+        .shortcuts_with_tags(&tags, match_all.unwrap_or(false))
+        .into_iter()
+        .cloned()
+        .collect())
+}
@@ -1,5 +1,7 @@
 use crate::commands::config::ConfigState;
 use crate::models::{Shortcut, TestResult};
+use crate::services::command_safety::{assess_command, ensure_confirmed, CommandSafety};
+use crate::services::shell_syntax::check_command_syntax;
 use std::collections::HashMap;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
@@ -99,7 +101,10 @@ pub fn test_shortcut(
     })
 }
 
-/// Execute a shortcut's command in a safe test mode (echo only, no actual execution)
+/// Preview a shortcut's command in test mode, without executing it
+///
+/// The syntax is checked with the built-in shell tokenizer and the preview lists
+/// what would run, including any risks `execute_shortcut_command` would ask to confirm.
 #[tauri::command]
 pub fn execute_test_command(
     shortcut_id: String,
@@ -115,32 +120,31 @@ pub fn execute_test_command(
         .find(|s| s.id == shortcut_id)
         .ok_or("Shortcut not found")?;
 
-    // Execute the command with echo to show what would run
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(format!(
-            "echo 'Would execute: {}' && {}",
-            shortcut.command, &shortcut.command
-        ))
-        .output()
-        .map_err(|e| format!("Failed to execute test: {}", e))?;
+    Ok(dry_run(shortcut))
+}
 
-    let success = output.status.success();
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+/// Build the test-mode result for a shortcut; nothing is executed
+fn dry_run(shortcut: &Shortcut) -> TestResult {
+    let syntax_error = check_command_syntax(&shortcut.command)
+        .err()
+        .map(|e| format!("{} (column {})", e.message, e.column(&shortcut.command)));
 
-    let preview = format!(
-        "Exit Code: {}\n\nStdout:\n{}\n\nStderr:\n{}",
-        output.status.code().unwrap_or(-1),
-        stdout,
-        if stderr.is_empty() { "(none)" } else { &stderr }
-    );
+    let mut preview = format!("Would execute: {}\n\n", shortcut.command);
+    preview.push_str(&format_command_preview(shortcut));
 
-    Ok(TestResult {
+    let safety = assess_command(&shortcut.command);
+    if safety.requires_confirmation {
+        preview.push_str("\nRequires confirmation before running:\n");
+        for risk in &safety.risks {
+            preview.push_str(&format!("• {}\n", risk.message));
+        }
+    }
+
+    TestResult {
         shortcut_id: shortcut.id.clone(),
         command: shortcut.command.clone(),
-        syntax_valid: success,
-        syntax_error: if !success { Some(stderr.clone()) } else { None },
+        syntax_valid: syntax_error.is_none(),
+        syntax_error,
         preview,
         timestamp: chrono::Local::now().to_rfc3339(),
         executed: false,
@@ -151,13 +155,29 @@ pub fn execute_test_command(
         cancelled: false,
         timed_out: false,
         output_truncated: false,
-    })
+    }
+}
+
+/// Assess whether a command is destructive or needs administrator privileges
+///
+/// # Arguments
+/// * `command` - Command to assess
+///
+/// # Returns
+/// * `CommandSafety` - Risks found and whether execution must be confirmed
+#[tauri::command]
+pub fn check_command_safety(command: String) -> Result<CommandSafety, String> {
+    Ok(assess_command(&command))
 }
 
 /// Execute a shortcut's command and return detailed execution results
+///
+/// Commands that look destructive (see `check_command_safety`) are refused unless
+/// `confirm_dangerous` is true.
 #[tauri::command]
 pub async fn execute_shortcut_command(
     shortcut_id: String,
+    confirm_dangerous: Option<bool>,
     state: State<'_, ConfigState>,
     exec_state: State<'_, ExecutionState>,
 ) -> Result<TestResult, String> {
@@ -173,6 +193,9 @@ pub async fn execute_shortcut_command(
             .ok_or("Shortcut not found")?
     }; // Lock released here
 
+    // Refuse destructive commands the user has not confirmed
+    ensure_confirmed(&shortcut.command, confirm_dangerous.unwrap_or(false))?;
+
     // 2. Check if already running
     {
         let senders = exec_state.cancel_senders.lock().unwrap();
@@ -451,7 +474,13 @@ mod tests {
             command: "open -a Terminal".to_string(),
             comment: Some("Open Terminal".to_string()),
             mode: None,
+            activates: None,
+            process: None,
+            passthrough: false,
             line_number: 1,
+            tags: Vec::new(),
+            description: None,
+            allowed_rules: Vec::new(),
         };
 
         let preview = format_command_preview(&shortcut);
@@ -460,6 +489,33 @@ mod tests {
         assert!(preview.contains("Description: Open Terminal"));
     }
 
+    #[test]
+    fn test_dry_run_does_not_execute() {
+        let marker = std::env::temp_dir().join("keybinder_dry_run_marker");
+        let _ = std::fs::remove_file(&marker);
+        let shortcut = Shortcut::new(
+            vec!["alt".to_string()],
+            "t".to_string(),
+            format!("touch {} && killall Dock", marker.display()),
+            1,
+        );
+
+        let result = dry_run(&shortcut);
+        assert!(!marker.exists());
+        assert!(!result.executed);
+        assert!(result.syntax_valid);
+        assert!(result.preview.starts_with("Would execute: touch"));
+        assert!(result.preview.contains("Terminates processes by name"));
+
+        let shortcut = Shortcut::new(vec![], "t".to_string(), "echo 'oops".to_string(), 1);
+        let result = dry_run(&shortcut);
+        assert!(!result.syntax_valid);
+        assert!(result
+            .syntax_error
+            .unwrap()
+            .contains("Unterminated single quote"));
+    }
+
     #[test]
     fn test_format_command_preview_chained() {
         let shortcut = Shortcut {
@@ -469,7 +525,13 @@ mod tests {
             command: "echo hello && echo world".to_string(),
             comment: None,
             mode: None,
+            activates: None,
+            process: None,
+            passthrough: false,
             line_number: 1,
+            tags: Vec::new(),
+            description: None,
+            allowed_rules: Vec::new(),
         };
 
         let preview = format_command_preview(&shortcut);
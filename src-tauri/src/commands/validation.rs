@@ -1,30 +1,144 @@
 /// Validation Tauri commands
+use crate::commands::config::ConfigState;
 use crate::models::{ConfigFile, Shortcut};
+use crate::services::shell_syntax::{
+    check_config_syntax as check_config_syntax_service, ShortcutSyntaxError,
+};
+use crate::services::system_shortcuts::SystemShortcutCatalog;
 use crate::services::validation::{
-    validate_config as validate_config_service, validate_shortcut as validate_shortcut_service,
+    validate_config as validate_config_service, validate_config_text_with,
+    validate_shortcut as validate_shortcut_service, LintRuleInfo, LintSettings, Linter,
     ValidationResult,
 };
+use tauri::State;
 
 /// Validate a shortcut
 ///
 /// # Arguments
 /// * `shortcut` - Shortcut to validate
+/// * `state` - Application state
 ///
 /// # Returns
-/// * `ValidationResult` with errors and warnings
+/// * `ValidationResult` with diagnostics (code, severity, span, fixes)
 #[tauri::command]
-pub fn validate_shortcut(shortcut: Shortcut) -> Result<ValidationResult, String> {
-    Ok(validate_shortcut_service(&shortcut))
+pub fn validate_shortcut(
+    shortcut: Shortcut,
+    state: State<'_, ConfigState>,
+) -> Result<ValidationResult, String> {
+    Ok(validate_shortcut_service(&shortcut, state.config_dir()))
 }
 
 /// Validate entire configuration
 ///
 /// # Arguments
 /// * `config` - Configuration to validate
+/// * `state` - Application state
+///
+/// # Returns
+/// * `ValidationResult` with diagnostics (code, severity, span, fixes)
+#[tauri::command]
+pub fn validate_config(
+    config: ConfigFile,
+    state: State<'_, ConfigState>,
+) -> Result<ValidationResult, String> {
+    Ok(validate_config_service(&config, state.config_dir()))
+}
+
+/// Validate configuration text, as it would be written to disk
+///
+/// Uses the lint settings of the loaded config. With `"parser": "skhd"` in the
+/// settings the installed skhd checks the syntax (built-in parser if skhd is absent);
+/// skhd runs until its timeout, so validation runs on a blocking worker thread.
+///
+/// # Arguments
+/// * `text` - skhd configuration text
+/// * `state` - Application state
+///
+/// # Returns
+/// * `ValidationResult` with diagnostics; spans refer to lines of `text`
+#[tauri::command]
+pub async fn validate_config_text(
+    text: String,
+    state: State<'_, ConfigState>,
+) -> Result<ValidationResult, String> {
+    let settings = {
+        let config_guard = state.config.lock().unwrap();
+        match config_guard.as_ref() {
+            Some(config) => LintSettings::for_config(config)?,
+            None => LintSettings::default(),
+        }
+    };
+
+    let config_dir = state.config_dir().to_path_buf();
+    tokio::task::spawn_blocking(move || validate_config_text_with(&text, &settings, &config_dir))
+        .await
+        .map_err(|e| format!("Validation task failed: {}", e))
+}
+
+/// Get the diagnostics of the loaded configuration
+///
+/// Served from the validation cache that `create_shortcut`, `update_shortcut` and
+/// `delete_shortcut` keep up to date (they emit `validation-changed` deltas).
+///
+/// # Arguments
+/// * `state` - Application state
 ///
 /// # Returns
-/// * `ValidationResult` with errors and warnings
+/// * `Ok(ValidationResult)` - Diagnostics in file order
+/// * `Err(String)` - No config loaded
 #[tauri::command]
-pub fn validate_config(config: ConfigFile) -> Result<ValidationResult, String> {
-    Ok(validate_config_service(&config))
+pub fn get_config_diagnostics(state: State<'_, ConfigState>) -> Result<ValidationResult, String> {
+    let config_guard = state.config.lock().unwrap();
+    let config = config_guard.as_ref().ok_or("No config loaded")?;
+
+    Ok(state.validation_result(config))
+}
+
+/// Check the shell syntax of every command in a configuration
+///
+/// Uses the built-in shell tokenizer, so no shell is spawned.
+///
+/// # Arguments
+/// * `config` - Configuration to check
+///
+/// # Returns
+/// * `Vec<ShortcutSyntaxError>` - One entry per shortcut with a syntax error, with its config line
+#[tauri::command]
+pub fn check_config_syntax(config: ConfigFile) -> Result<Vec<ShortcutSyntaxError>, String> {
+    Ok(check_config_syntax_service(&config))
+}
+
+/// Get the system shortcut conflict database
+///
+/// Includes the user's entries from `system_shortcuts.json` in the config directory.
+///
+/// # Arguments
+/// * `state` - Application state
+///
+/// # Returns
+/// * `SystemShortcutCatalog` with categories and entries (disabled entries included)
+#[tauri::command]
+pub fn get_system_shortcuts(
+    state: State<'_, ConfigState>,
+) -> Result<SystemShortcutCatalog, String> {
+    Ok(state.system_shortcuts().catalog().clone())
+}
+
+/// List lint rules with their levels from the loaded config's settings file
+///
+/// # Arguments
+/// * `state` - Application state
+///
+/// # Returns
+/// * `Ok(Vec<LintRuleInfo>)` - Rules in reporting order
+/// * `Err(String)` - Unreadable settings file
+#[tauri::command]
+pub fn get_lint_rules(state: State<'_, ConfigState>) -> Result<Vec<LintRuleInfo>, String> {
+    let config_guard = state.config.lock().unwrap();
+    let settings = match config_guard.as_ref() {
+        Some(config) => LintSettings::for_config(config)?,
+        None => LintSettings::default(),
+    };
+
+    Ok(Linter::new(settings).rules())
 }
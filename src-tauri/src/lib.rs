@@ -11,6 +11,7 @@ pub mod utils;
 
 use commands::config::ConfigState;
 use commands::logs::LogStreamState;
+use commands::settings::SettingsState;
 use commands::testing::ExecutionState;
 use services::{ServiceManager, ThemeMonitorState};
 
@@ -18,10 +19,11 @@ use services::{ServiceManager, ThemeMonitorState};
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(ConfigState::new())
+        .manage(ConfigState::default())
         .manage(ExecutionState::default())
         .manage(LogStreamState::default())
         .manage(ServiceManager::new())
+        .manage(SettingsState::load())
         .manage(ThemeMonitorState::new())
         .invoke_handler(tauri::generate_handler![
             commands::applications::get_installed_applications,
@@ -35,14 +37,39 @@ pub fn run() {
             commands::shortcuts::create_shortcut,
             commands::shortcuts::update_shortcut,
             commands::shortcuts::delete_shortcut,
+            commands::shortcuts::duplicate_shortcut,
+            commands::tags::list_tags,
+            commands::tags::filter_shortcuts_by_tags,
+            commands::search::find_key_bindings,
+            commands::search::suggest_free_keys,
+            commands::layouts::get_keyboard_layouts,
+            commands::layouts::describe_key_on_layouts,
+            commands::layouts::check_keyboard_layout,
             commands::validation::validate_shortcut,
             commands::validation::validate_config,
+            commands::validation::validate_config_text,
+            commands::validation::get_config_diagnostics,
+            commands::validation::check_config_syntax,
+            commands::validation::get_system_shortcuts,
+            commands::validation::get_lint_rules,
             commands::backups::create_backup,
             commands::backups::list_backups,
             commands::backups::restore_backup,
+            commands::backups::diff_backups,
+            commands::backups::get_backup_shortcuts,
+            commands::backups::restore_shortcuts,
+            commands::backups::get_backup_retention,
+            commands::backups::set_backup_retention,
+            commands::backups::get_history_settings,
+            commands::backups::set_history_settings,
+            commands::backups::export_backups,
+            commands::backups::import_backups,
+            commands::backups::audit_backups,
+            commands::backups::quarantine_backups,
             commands::testing::test_shortcut,
             commands::testing::execute_test_command,
             commands::testing::execute_shortcut_command,
+            commands::testing::check_command_safety,
             commands::testing::cancel_shortcut_execution,
             commands::testing::get_execution_config,
             commands::logs::start_log_stream,
@@ -51,6 +78,8 @@ pub fn run() {
             commands::logs::get_recent_logs,
             commands::service::get_service_status,
             commands::service::reload_service,
+            commands::settings::get_app_settings,
+            commands::settings::set_app_settings,
             commands::templates::get_command_templates,
             commands::templates::get_command_categories,
             commands::templates::generate_command_from_template,
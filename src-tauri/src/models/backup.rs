@@ -1,16 +1,77 @@
 /// Backup model for config file versioning
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Key grouping the backups of one config file: its file name and a path hash
+///
+/// e.g. `skhdrc-3f0c1e2a9b7d4c55` for `/Users/me/.config/skhd/skhdrc`
+pub fn namespace_key(original_path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(original_path.to_string_lossy().as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+
+    let name: String = original_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config".to_string())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}-{}", name, &hash[..16])
+}
+
+/// Why a backup was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupTrigger {
+    /// Requested by the user
+    #[default]
+    Manual,
+
+    /// Taken automatically before a save overwrote the file
+    BeforeSave,
+
+    /// Taken automatically before a backup was restored over the file
+    BeforeRestore,
+
+    /// Found in the backup directory without a manifest entry
+    Migrated,
+
+    /// Committed to git history after a save wrote the file
+    Saved,
+}
+
+impl BackupTrigger {
+    /// Whether the backup was taken without the user asking for it
+    pub fn is_automatic(self) -> bool {
+        matches!(self, Self::BeforeSave | Self::BeforeRestore | Self::Saved)
+    }
+}
 
 /// Represents a backup of a configuration file
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Backup {
-    /// Absolute path to the backup file
+    /// Unique identifier of the backup
+    pub id: String,
+
+    /// Absolute path to the file holding the backup content (the repository for git history)
     pub file_path: PathBuf,
 
     /// Path to the original config file
     pub original_path: PathBuf,
 
+    /// Backups of the same config file share a namespace (see `namespace_key`)
+    #[serde(default)]
+    pub namespace: String,
+
     /// Timestamp when backup was created (ISO 8601)
     pub created_at: String,
 
@@ -22,6 +83,10 @@ pub struct Backup {
 
     /// File size in bytes
     pub size_bytes: u64,
+
+    /// What created the backup
+    #[serde(default)]
+    pub trigger: BackupTrigger,
 }
 
 impl Backup {
@@ -33,12 +98,15 @@ impl Backup {
         size_bytes: u64,
     ) -> Self {
         Self {
+            id: Uuid::new_v4().to_string(),
             file_path,
+            namespace: namespace_key(&original_path),
             original_path,
             created_at: chrono::Utc::now().to_rfc3339(),
             checksum,
             description: None,
             size_bytes,
+            trigger: BackupTrigger::Manual,
         }
     }
 
@@ -51,12 +119,15 @@ impl Backup {
         description: String,
     ) -> Self {
         Self {
+            id: Uuid::new_v4().to_string(),
             file_path,
+            namespace: namespace_key(&original_path),
             original_path,
             created_at: chrono::Utc::now().to_rfc3339(),
             checksum,
             description: Some(description),
             size_bytes,
+            trigger: BackupTrigger::Manual,
         }
     }
 
@@ -102,6 +173,7 @@ mod tests {
         );
         assert_eq!(backup.checksum, "abc123");
         assert_eq!(backup.size_bytes, 1024);
+        assert_eq!(backup.namespace, namespace_key(&backup.original_path));
         assert!(backup.description.is_none());
         assert!(!backup.created_at.is_empty());
     }
@@ -135,6 +207,19 @@ mod tests {
         assert!(!backup.verify_checksum("xyz789"));
     }
 
+    #[test]
+    fn test_namespace_key() {
+        let key = namespace_key(Path::new("/home/user/.config/skhd/skhdrc"));
+        assert!(key.starts_with("skhdrc-"));
+        assert_eq!(key.len(), "skhdrc-".len() + 16);
+        assert_eq!(
+            key,
+            namespace_key(Path::new("/home/user/.config/skhd/skhdrc"))
+        );
+        assert_ne!(key, namespace_key(Path::new("/home/user/dotfiles/skhdrc")));
+        assert!(namespace_key(Path::new("/x/my rc")).starts_with("my_rc-"));
+    }
+
     #[test]
     fn test_age_seconds() {
         let backup = Backup::new(
@@ -0,0 +1,303 @@
+/// Binding conflict model: duplicates and shadowed bindings
+///
+/// Two bindings in a common mode can reach the same keystroke without being written
+/// the same way. Each conflict records which binding skhd runs and why.
+use super::key_combination::KeyCombination;
+use super::shortcut::{Shortcut, WILDCARD_PROCESS};
+use serde::{Deserialize, Serialize};
+
+/// Keys laptop keyboards send for `fn` + key
+pub const FN_TRANSLATIONS: [(&str, &str); 5] = [
+    ("left", "home"),
+    ("right", "end"),
+    ("up", "pageup"),
+    ("down", "pagedown"),
+    ("backspace", "delete"),
+];
+
+/// How one binding hides another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Same chord written the same way
+    Duplicate,
+
+    /// Same chord written with `hyper`/`meh` or spelled-out modifiers
+    ModifierAlias,
+
+    /// `fn` + key that laptop keyboards send as another key (`fn - left` is `home`)
+    FnVariant,
+
+    /// Global binding hidden by a process list entry
+    ProcessShadow,
+}
+
+impl ConflictKind {
+    /// Whether both bindings name the same chord (only one can ever be defined)
+    pub fn is_duplicate(self) -> bool {
+        matches!(self, Self::Duplicate | Self::ModifierAlias)
+    }
+}
+
+/// A binding hidden, fully or partly, by another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BindingConflict {
+    pub kind: ConflictKind,
+
+    /// Binding that runs
+    pub winner_id: String,
+    pub winner_line: usize,
+
+    /// Binding that is hidden
+    pub shadowed_id: String,
+    pub shadowed_line: usize,
+
+    /// Chord of the hidden binding, as written
+    pub combination: String,
+
+    /// Which binding wins and why
+    pub explanation: String,
+}
+
+/// Find duplicate and shadowed bindings, ordered by the line of the later binding
+pub fn find_conflicts(shortcuts: &[Shortcut]) -> Vec<BindingConflict> {
+    let mut ordered: Vec<&Shortcut> = shortcuts.iter().collect();
+    ordered.sort_by_key(|s| s.line_number);
+
+    let mut conflicts = Vec::new();
+    for (i, second) in ordered.iter().enumerate() {
+        for first in &ordered[..i] {
+            if first.shares_mode(second) {
+                conflicts.extend(pair_conflict(first, second));
+            }
+        }
+    }
+    conflicts
+}
+
+/// Conflict between two bindings, `first` defined before `second`
+fn pair_conflict(first: &Shortcut, second: &Shortcut) -> Option<BindingConflict> {
+    let first_combination = KeyCombination::from_shortcut(first);
+    let second_combination = KeyCombination::from_shortcut(second);
+
+    if !first.same_process(second) {
+        if first_combination != second_combination {
+            return None;
+        }
+        // Entries of one process list for different apps complement each other
+        let (global, entry) = match (&first.process, &second.process) {
+            (None, Some(_)) => (first, second),
+            (Some(_), None) => (second, first),
+            _ => return None,
+        };
+        let process = entry.process.as_deref().unwrap_or(WILDCARD_PROCESS);
+        let explanation = if process == WILDCARD_PROCESS {
+            format!(
+                "the `*` entry at line {} handles every application without its own entry, so the global binding at line {} never runs",
+                entry.line_number, global.line_number
+            )
+        } else {
+            format!(
+                "while {} is focused the process-specific binding at line {} runs instead of the global binding at line {}",
+                process, entry.line_number, global.line_number
+            )
+        };
+        return Some(conflict(
+            ConflictKind::ProcessShadow,
+            entry,
+            global,
+            explanation,
+        ));
+    }
+
+    if first_combination == second_combination {
+        let (kind, explanation) = if same_spelling(first, second) {
+            (
+                ConflictKind::Duplicate,
+                format!(
+                    "skhd keeps the first definition of a chord, so line {} wins and line {} never runs",
+                    first.line_number, second.line_number
+                ),
+            )
+        } else {
+            (
+                ConflictKind::ModifierAlias,
+                format!(
+                    "'{}' and '{}' both expand to '{}'; skhd keeps the first definition, so line {} wins",
+                    first.key_combination_string(),
+                    second.key_combination_string(),
+                    second_combination,
+                    first.line_number
+                ),
+            )
+        };
+        return Some(conflict(kind, first, second, explanation));
+    }
+
+    let (fn_binding, translated) = if is_fn_variant(&first_combination, &second_combination) {
+        (first, second)
+    } else if is_fn_variant(&second_combination, &first_combination) {
+        (second, first)
+    } else {
+        return None;
+    };
+    let explanation = format!(
+        "laptop keyboards send fn + {} as {}, so '{}' at line {} receives the keystroke and '{}' at line {} never runs",
+        fn_binding.key.to_lowercase(),
+        translated.key.to_lowercase(),
+        translated.key_combination_string(),
+        translated.line_number,
+        fn_binding.key_combination_string(),
+        fn_binding.line_number
+    );
+    Some(conflict(
+        ConflictKind::FnVariant,
+        translated,
+        fn_binding,
+        explanation,
+    ))
+}
+
+/// Check whether `fn_combination` is `fn` + a key sent as `translated` on laptops
+fn is_fn_variant(fn_combination: &KeyCombination, translated: &KeyCombination) -> bool {
+    let Some(&(_, sent)) = FN_TRANSLATIONS
+        .iter()
+        .find(|(key, _)| *key == fn_combination.key)
+    else {
+        return false;
+    };
+
+    let without_fn: Vec<&String> = fn_combination
+        .modifiers
+        .iter()
+        .filter(|m| *m != "fn")
+        .collect();
+    fn_combination.modifiers.len() != without_fn.len()
+        && translated.key == sent
+        && translated.modifiers.iter().collect::<Vec<_>>() == without_fn
+}
+
+/// Check whether two bindings spell their modifiers and key the same way
+fn same_spelling(a: &Shortcut, b: &Shortcut) -> bool {
+    let mut a_mods: Vec<String> = a.modifiers.iter().map(|m| m.to_lowercase()).collect();
+    let mut b_mods: Vec<String> = b.modifiers.iter().map(|m| m.to_lowercase()).collect();
+    a_mods.sort();
+    b_mods.sort();
+    a_mods == b_mods && a.key.eq_ignore_ascii_case(&b.key)
+}
+
+fn conflict(
+    kind: ConflictKind,
+    winner: &Shortcut,
+    shadowed: &Shortcut,
+    explanation: String,
+) -> BindingConflict {
+    BindingConflict {
+        kind,
+        winner_id: winner.id.clone(),
+        winner_line: winner.line_number,
+        shadowed_id: shadowed.id.clone(),
+        shadowed_line: shadowed.line_number,
+        combination: shadowed.key_combination_string(),
+        explanation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(modifiers: &[&str], key: &str, line: usize) -> Shortcut {
+        Shortcut::new(
+            modifiers.iter().map(|m| m.to_string()).collect(),
+            key.to_string(),
+            format!("echo {}", line),
+            line,
+        )
+    }
+
+    fn kinds(shortcuts: &[Shortcut]) -> Vec<(ConflictKind, usize, usize)> {
+        find_conflicts(shortcuts)
+            .into_iter()
+            .map(|c| (c.kind, c.winner_line, c.shadowed_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicates_and_aliases() {
+        let shortcuts = vec![
+            shortcut(&["hyper"], "a", 1),
+            shortcut(&["ctrl", "shift", "alt", "cmd"], "a", 2),
+            shortcut(&["cmd"], "b", 3),
+            shortcut(&["cmd"], "b", 4),
+        ];
+        assert_eq!(
+            kinds(&shortcuts),
+            vec![
+                (ConflictKind::ModifierAlias, 1, 2),
+                (ConflictKind::Duplicate, 3, 4),
+            ]
+        );
+
+        let alias = &find_conflicts(&shortcuts)[0];
+        assert!(alias.explanation.contains("'hyper - a'"));
+        assert!(alias.explanation.contains("line 1 wins"));
+    }
+
+    #[test]
+    fn test_fn_variants() {
+        let shortcuts = vec![
+            shortcut(&["cmd", "fn"], "left", 1),
+            shortcut(&["cmd"], "home", 2),
+            shortcut(&["fn"], "up", 3),
+            shortcut(&["alt"], "pageup", 4),
+        ];
+        assert_eq!(kinds(&shortcuts), vec![(ConflictKind::FnVariant, 2, 1)]);
+        assert!(find_conflicts(&shortcuts)[0]
+            .explanation
+            .starts_with("laptop keyboards send fn + left as home"));
+    }
+
+    #[test]
+    fn test_process_shadowing() {
+        let global = shortcut(&["cmd"], "t", 1);
+        let mut term = shortcut(&["cmd"], "t", 3);
+        term.process = Some("iTerm2".to_string());
+        let mut finder = shortcut(&["cmd"], "t", 4);
+        finder.process = Some("Finder".to_string());
+        let mut wildcard = shortcut(&["cmd"], "t", 5);
+        wildcard.process = Some(WILDCARD_PROCESS.to_string());
+
+        let conflicts = find_conflicts(&[global, term, finder, wildcard]);
+        let found: Vec<(ConflictKind, usize, usize)> = conflicts
+            .iter()
+            .map(|c| (c.kind, c.winner_line, c.shadowed_line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (ConflictKind::ProcessShadow, 3, 1),
+                (ConflictKind::ProcessShadow, 4, 1),
+                (ConflictKind::ProcessShadow, 5, 1),
+            ]
+        );
+        assert!(conflicts[0]
+            .explanation
+            .starts_with("while iTerm2 is focused"));
+        assert!(conflicts[2].explanation.contains("never runs"));
+    }
+
+    #[test]
+    fn test_different_modes_do_not_conflict() {
+        let first = shortcut(&["cmd"], "b", 1);
+        let mut second = shortcut(&["cmd"], "b", 2);
+        second.mode = Some("window".to_string());
+        let mut third = shortcut(&["cmd"], "b", 3);
+        third.mode = Some("resize, window".to_string());
+
+        assert_eq!(
+            kinds(&[first, second, third]),
+            vec![(ConflictKind::Duplicate, 2, 3)]
+        );
+    }
+}
@@ -1,6 +1,8 @@
-use super::shortcut::Shortcut;
+use super::binding_conflict::{find_conflicts, BindingConflict};
+use super::shortcut::{normalize_tag, Shortcut};
 /// Configuration File model
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Represents a parse error
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -12,6 +14,43 @@ pub struct ParseError {
     pub line_content: String,
 }
 
+/// A tag with the number of shortcuts carrying it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Name of the mode skhd starts in; it exists without a declaration
+pub const DEFAULT_MODE: &str = "default";
+
+/// A mode declared with `:: name [@] [: command]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModeDeclaration {
+    pub name: String,
+
+    /// Whether the mode captures all keys, including unbound ones (`@`)
+    #[serde(default)]
+    pub capture: bool,
+
+    /// Command run when the mode is activated
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Line number in the config file
+    pub line_number: usize,
+}
+
+/// A directive comment (`# @tags ...`) not directly above a shortcut
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DanglingDirective {
+    /// Directive text (without the # prefix)
+    pub text: String,
+
+    /// Line number in the config file
+    pub line_number: usize,
+}
+
 /// Represents the complete skhd configuration file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigFile {
@@ -21,9 +60,17 @@ pub struct ConfigFile {
     /// List of keyboard shortcuts (ordered by line number)
     pub shortcuts: Vec<Shortcut>,
 
+    /// Mode declarations (ordered by line number)
+    #[serde(default)]
+    pub modes: Vec<ModeDeclaration>,
+
     /// Global comment lines not associated with shortcuts
     pub global_comments: Vec<String>,
 
+    /// Directives the parser dropped because no shortcut follows them
+    #[serde(default)]
+    pub dangling_directives: Vec<DanglingDirective>,
+
     /// Last modification timestamp (ISO 8601)
     pub last_modified: String,
 
@@ -58,7 +105,9 @@ impl ConfigFile {
         Self {
             file_path,
             shortcuts: Vec::new(),
+            modes: Vec::new(),
             global_comments: Vec::new(),
+            dangling_directives: Vec::new(),
             last_modified: chrono::Utc::now().to_rfc3339(),
             is_modified: false,
             backup_path: None,
@@ -73,6 +122,34 @@ impl ConfigFile {
         self.is_modified = true;
     }
 
+    /// Insert a shortcut directly after another one
+    ///
+    /// Line numbers of later shortcuts and mode declarations are shifted down by one
+    /// so the new shortcut is serialized right below `after_id`. Returns false if
+    /// `after_id` is not found.
+    pub fn insert_shortcut_after(&mut self, after_id: &str, mut shortcut: Shortcut) -> bool {
+        let Some(index) = self.shortcuts.iter().position(|s| s.id == after_id) else {
+            return false;
+        };
+
+        let anchor_line = self.shortcuts[index].line_number;
+        for existing in self.shortcuts.iter_mut() {
+            if existing.line_number > anchor_line {
+                existing.line_number += 1;
+            }
+        }
+        for mode in self.modes.iter_mut() {
+            if mode.line_number > anchor_line {
+                mode.line_number += 1;
+            }
+        }
+
+        shortcut.line_number = anchor_line + 1;
+        self.shortcuts.insert(index + 1, shortcut);
+        self.is_modified = true;
+        true
+    }
+
     /// Remove a shortcut by ID
     pub fn remove_shortcut(&mut self, id: &str) -> Option<Shortcut> {
         if let Some(index) = self.shortcuts.iter().position(|s| s.id == id) {
@@ -94,6 +171,16 @@ impl ConfigFile {
         }
     }
 
+    /// Find a mode declaration by name
+    pub fn find_mode(&self, name: &str) -> Option<&ModeDeclaration> {
+        self.modes.iter().find(|m| m.name == name)
+    }
+
+    /// Check whether a mode exists (declared, or the implicit default mode)
+    pub fn has_mode(&self, name: &str) -> bool {
+        name == DEFAULT_MODE || self.find_mode(name).is_some()
+    }
+
     /// Find shortcut by ID
     pub fn find_shortcut(&self, id: &str) -> Option<&Shortcut> {
         self.shortcuts.iter().find(|s| s.id == id)
@@ -107,6 +194,11 @@ impl ConfigFile {
             .collect()
     }
 
+    /// Find duplicate and shadowed bindings
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        find_conflicts(&self.shortcuts)
+    }
+
     /// Validate all shortcuts
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
@@ -117,17 +209,17 @@ impl ConfigFile {
             }
         }
 
-        // Check for duplicates
-        for (i, shortcut) in self.shortcuts.iter().enumerate() {
-            for other in self.shortcuts.iter().skip(i + 1) {
-                if shortcut.has_same_combination(other) {
-                    errors.push(format!(
-                        "Duplicate key combination '{}' at lines {} and {}",
-                        shortcut.key_combination_string(),
-                        shortcut.line_number,
-                        other.line_number
-                    ));
-                }
+        // Check for duplicates, including chords spelled differently (`hyper` vs. its
+        // expansion); shadowed bindings are intentional often enough to only warn about
+        for conflict in self.conflicts() {
+            if conflict.kind.is_duplicate() {
+                errors.push(format!(
+                    "Duplicate key combination '{}' at lines {} and {}: {}",
+                    conflict.combination,
+                    conflict.winner_line,
+                    conflict.shadowed_line,
+                    conflict.explanation
+                ));
             }
         }
 
@@ -138,6 +230,42 @@ impl ConfigFile {
         }
     }
 
+    /// List every tag used in the configuration with its usage count (sorted by tag)
+    pub fn tag_summaries(&self) -> Vec<TagSummary> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for shortcut in &self.shortcuts {
+            for tag in &shortcut.tags {
+                *counts.entry(normalize_tag(tag)).or_insert(0) += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|(tag, count)| TagSummary { tag, count })
+            .collect()
+    }
+
+    /// Find shortcuts carrying the given tags
+    ///
+    /// With `match_all` a shortcut must carry every tag, otherwise any one is enough.
+    /// An empty tag list matches nothing.
+    pub fn shortcuts_with_tags(&self, tags: &[String], match_all: bool) -> Vec<&Shortcut> {
+        if tags.is_empty() {
+            return Vec::new();
+        }
+
+        self.shortcuts
+            .iter()
+            .filter(|s| {
+                if match_all {
+                    tags.iter().all(|t| s.has_tag(t))
+                } else {
+                    tags.iter().any(|t| s.has_tag(t))
+                }
+            })
+            .collect()
+    }
+
     /// Sort shortcuts by line number
     pub fn sort_by_line_number(&mut self) {
         self.shortcuts.sort_by_key(|s| s.line_number);
@@ -189,6 +317,48 @@ mod tests {
         assert_eq!(config.shortcuts.len(), 0);
     }
 
+    #[test]
+    fn test_validate_explains_conflicts() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(Shortcut::new(
+            vec!["hyper".to_string()],
+            "a".to_string(),
+            "echo one".to_string(),
+            1,
+        ));
+        config.add_shortcut(Shortcut::new(
+            vec![
+                "cmd".to_string(),
+                "alt".to_string(),
+                "shift".to_string(),
+                "ctrl".to_string(),
+            ],
+            "a".to_string(),
+            "echo two".to_string(),
+            2,
+        ));
+        // Shadowed, but not an error
+        config.add_shortcut(Shortcut::new(
+            vec!["fn".to_string()],
+            "left".to_string(),
+            "echo three".to_string(),
+            3,
+        ));
+        config.add_shortcut(Shortcut::new(
+            vec![],
+            "home".to_string(),
+            "echo four".to_string(),
+            4,
+        ));
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Duplicate key combination"));
+        assert!(errors[0].contains("at lines 1 and 2"));
+        assert!(errors[0].contains("skhd keeps the first definition, so line 1 wins"));
+        assert_eq!(config.conflicts().len(), 2);
+    }
+
     #[test]
     fn test_find_duplicates() {
         let mut config = ConfigFile::new("/test/path".to_string());
@@ -213,4 +383,69 @@ mod tests {
         let duplicates = config.find_duplicates(&s1);
         assert_eq!(duplicates.len(), 1);
     }
+
+    #[test]
+    fn test_insert_shortcut_after() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        let s1 = Shortcut::new(vec![], "a".to_string(), "echo a".to_string(), 1);
+        let s2 = Shortcut::new(vec![], "b".to_string(), "echo b".to_string(), 2);
+        let id1 = s1.id.clone();
+        config.add_shortcut(s1);
+        config.add_shortcut(s2);
+
+        let copy = Shortcut::new(vec![], "c".to_string(), "echo c".to_string(), 0);
+        assert!(config.insert_shortcut_after(&id1, copy));
+
+        let keys: Vec<&str> = config.shortcuts.iter().map(|s| s.key.as_str()).collect();
+        let lines: Vec<usize> = config.shortcuts.iter().map(|s| s.line_number).collect();
+        assert_eq!(keys, vec!["a", "c", "b"]);
+        assert_eq!(lines, vec![1, 2, 3]);
+
+        let orphan = Shortcut::new(vec![], "d".to_string(), "echo d".to_string(), 0);
+        assert!(!config.insert_shortcut_after("missing", orphan));
+    }
+
+    #[test]
+    fn test_tag_queries() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+
+        let mut s1 = Shortcut::new(
+            vec!["alt".to_string()],
+            "h".to_string(),
+            "yabai -m window --focus west".to_string(),
+            1,
+        );
+        s1.tags = vec!["yabai".to_string(), "work".to_string()];
+
+        let mut s2 = Shortcut::new(
+            vec!["alt".to_string()],
+            "t".to_string(),
+            "open -a Terminal".to_string(),
+            2,
+        );
+        s2.tags = vec!["work".to_string()];
+
+        config.add_shortcut(s1);
+        config.add_shortcut(s2);
+
+        let summaries = config.tag_summaries();
+        assert_eq!(
+            summaries,
+            vec![
+                TagSummary {
+                    tag: "work".to_string(),
+                    count: 2
+                },
+                TagSummary {
+                    tag: "yabai".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        let tags = vec!["@work".to_string(), "yabai".to_string()];
+        assert_eq!(config.shortcuts_with_tags(&tags, false).len(), 2);
+        assert_eq!(config.shortcuts_with_tags(&tags, true).len(), 1);
+        assert!(config.shortcuts_with_tags(&[], false).is_empty());
+    }
 }
@@ -0,0 +1,324 @@
+/// Structured validation diagnostics
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Stable diagnostic codes
+///
+/// Codes are serialized as `SKHDnnn` and must never be renumbered, since users
+/// refer to them in documentation and suppression settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiagnosticCode {
+    #[serde(rename = "SKHD001")]
+    DuplicateBinding,
+    #[serde(rename = "SKHD002")]
+    SystemShortcutConflict,
+    #[serde(rename = "SKHD003")]
+    NoModifiers,
+    #[serde(rename = "SKHD004")]
+    LongCommand,
+    #[serde(rename = "SKHD005")]
+    InvalidShortcut,
+    #[serde(rename = "SKHD006")]
+    ParseError,
+    #[serde(rename = "SKHD007")]
+    InvalidLintSettings,
+    #[serde(rename = "SKHD008")]
+    MissingExecutable,
+    #[serde(rename = "SKHD009")]
+    MissingApplication,
+    #[serde(rename = "SKHD010")]
+    ShellSyntax,
+    #[serde(rename = "SKHD011")]
+    DangerousCommand,
+    #[serde(rename = "SKHD012")]
+    UndeclaredMode,
+    #[serde(rename = "SKHD013")]
+    UnreachableMode,
+    #[serde(rename = "SKHD014")]
+    ModeWithoutExit,
+    #[serde(rename = "SKHD015")]
+    ShadowedBinding,
+    #[serde(rename = "SKHD016")]
+    SkhdError,
+    #[serde(rename = "SKHD017")]
+    DanglingDirective,
+}
+
+impl DiagnosticCode {
+    /// Every diagnostic code, in code order
+    pub const ALL: [DiagnosticCode; 17] = [
+        Self::DuplicateBinding,
+        Self::SystemShortcutConflict,
+        Self::NoModifiers,
+        Self::LongCommand,
+        Self::InvalidShortcut,
+        Self::ParseError,
+        Self::InvalidLintSettings,
+        Self::MissingExecutable,
+        Self::MissingApplication,
+        Self::ShellSyntax,
+        Self::DangerousCommand,
+        Self::UndeclaredMode,
+        Self::UnreachableMode,
+        Self::ModeWithoutExit,
+        Self::ShadowedBinding,
+        Self::SkhdError,
+        Self::DanglingDirective,
+    ];
+
+    /// Look up a code by its stable code or rule name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|c| c.code().eq_ignore_ascii_case(name) || c.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Stable code, e.g. `SKHD001`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::DuplicateBinding => "SKHD001",
+            Self::SystemShortcutConflict => "SKHD002",
+            Self::NoModifiers => "SKHD003",
+            Self::LongCommand => "SKHD004",
+            Self::InvalidShortcut => "SKHD005",
+            Self::ParseError => "SKHD006",
+            Self::InvalidLintSettings => "SKHD007",
+            Self::MissingExecutable => "SKHD008",
+            Self::MissingApplication => "SKHD009",
+            Self::ShellSyntax => "SKHD010",
+            Self::DangerousCommand => "SKHD011",
+            Self::UndeclaredMode => "SKHD012",
+            Self::UnreachableMode => "SKHD013",
+            Self::ModeWithoutExit => "SKHD014",
+            Self::ShadowedBinding => "SKHD015",
+            Self::SkhdError => "SKHD016",
+            Self::DanglingDirective => "SKHD017",
+        }
+    }
+
+    /// Human-readable rule name, e.g. `duplicate-binding`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DuplicateBinding => "duplicate-binding",
+            Self::SystemShortcutConflict => "system-shortcut-conflict",
+            Self::NoModifiers => "no-modifiers",
+            Self::LongCommand => "long-command",
+            Self::InvalidShortcut => "invalid-shortcut",
+            Self::ParseError => "parse-error",
+            Self::InvalidLintSettings => "invalid-lint-settings",
+            Self::MissingExecutable => "missing-executable",
+            Self::MissingApplication => "missing-application",
+            Self::ShellSyntax => "shell-syntax",
+            Self::DangerousCommand => "dangerous-command",
+            Self::UndeclaredMode => "undeclared-mode",
+            Self::UnreachableMode => "unreachable-mode",
+            Self::ModeWithoutExit => "mode-without-exit",
+            Self::ShadowedBinding => "shadowed-binding",
+            Self::SkhdError => "skhd-error",
+            Self::DanglingDirective => "dangling-directive",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.name())
+    }
+}
+
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A range in the config file
+///
+/// Lines and columns are 1-based; the end position is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// Span covering an entire line, including its line break
+    pub fn line(line: usize) -> Self {
+        Self {
+            line,
+            column: 1,
+            end_line: line + 1,
+            end_column: 1,
+        }
+    }
+
+    /// Span within a single line
+    pub fn columns(line: usize, column: usize, end_column: usize) -> Self {
+        Self {
+            line,
+            column,
+            end_line: line,
+            end_column,
+        }
+    }
+
+    /// Empty span at a position, used for insertions
+    pub fn point(line: usize, column: usize) -> Self {
+        Self::columns(line, column, column)
+    }
+}
+
+/// Another location relevant to a diagnostic (e.g. the first of two duplicates)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelatedLocation {
+    pub span: Span,
+    pub shortcut_id: Option<String>,
+    pub message: String,
+}
+
+/// A text replacement in the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub span: Span,
+    pub new_text: String,
+}
+
+/// A machine-applicable fix: applying all edits resolves the diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    /// Short label for the UI, e.g. "Remove duplicate binding"
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A single validation finding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+
+    /// Rule name matching `code`, e.g. `duplicate-binding`
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+
+    /// Shortcut the diagnostic is about (None for file-level issues)
+    pub shortcut_id: Option<String>,
+
+    /// Location in the config file (None when the shortcut has no known line)
+    pub span: Option<Span>,
+
+    #[serde(default)]
+    pub related: Vec<RelatedLocation>,
+
+    #[serde(default)]
+    pub fixes: Vec<Fix>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without location
+    pub fn new(code: DiagnosticCode, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            rule: code.name().to_string(),
+            severity,
+            message: message.into(),
+            shortcut_id: None,
+            span: None,
+            related: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    /// Create an error diagnostic
+    pub fn error(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Error, message)
+    }
+
+    /// Create a warning diagnostic
+    pub fn warning(code: DiagnosticCode, message: impl Into<String>) -> Self {
+        Self::new(code, Severity::Warning, message)
+    }
+
+    /// Attach the shortcut the diagnostic is about
+    pub fn with_shortcut(mut self, shortcut_id: impl Into<String>) -> Self {
+        self.shortcut_id = Some(shortcut_id.into());
+        self
+    }
+
+    /// Attach a location
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Attach a related location
+    pub fn with_related(mut self, related: RelatedLocation) -> Self {
+        self.related.push(related);
+        self
+    }
+
+    /// Attach a fix
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
+    /// Check whether this is an error
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(
+                f,
+                "{}:{}: {} [{}]",
+                span.line,
+                span.column,
+                self.message,
+                self.code.code()
+            ),
+            None => write!(f, "{} [{}]", self.message, self.code.code()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_serialization() {
+        let diagnostic = Diagnostic::error(DiagnosticCode::DuplicateBinding, "Duplicate")
+            .with_span(Span::line(3));
+
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["code"], "SKHD001");
+        assert_eq!(json["rule"], "duplicate-binding");
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["span"]["end_line"], 4);
+
+        assert_eq!(diagnostic.to_string(), "3:1: Duplicate [SKHD001]");
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            DiagnosticCode::from_name("skhd003"),
+            Some(DiagnosticCode::NoModifiers)
+        );
+        assert_eq!(
+            DiagnosticCode::from_name("long-command"),
+            Some(DiagnosticCode::LongCommand)
+        );
+        assert_eq!(DiagnosticCode::from_name("no-such-rule"), None);
+    }
+}
@@ -0,0 +1,238 @@
+/// Normalized key combination model
+use super::shortcut::Shortcut;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Base modifiers in canonical display order
+pub const BASE_MODIFIERS: [&str; 5] = ["cmd", "alt", "shift", "ctrl", "fn"];
+
+/// Every modifier a combination can hold, in canonical display order
+///
+/// Left/right variants follow their base modifier.
+const MODIFIER_ORDER: [&str; 13] = [
+    "cmd", "lcmd", "rcmd", "alt", "lalt", "ralt", "shift", "lshift", "rshift", "ctrl", "lctrl",
+    "rctrl", "fn",
+];
+
+/// Expand a modifier name (or alias) into the modifiers skhd matches on
+///
+/// Handles skhd's `hyper` (cmd + alt + shift + ctrl) and `meh` (alt + shift + ctrl)
+/// and macOS names such as `option`. Left/right variants such as `lcmd` stay
+/// distinct, since skhd registers `lcmd - a` and `cmd - a` as separate hotkeys.
+/// Returns `None` for unknown modifiers.
+pub fn expand_modifier(modifier: &str) -> Option<Vec<&'static str>> {
+    let modifier = modifier.trim().to_lowercase();
+    let expanded = match modifier.as_str() {
+        "hyper" => vec!["cmd", "alt", "shift", "ctrl"],
+        "meh" => vec!["alt", "shift", "ctrl"],
+        "cmd" | "command" => vec!["cmd"],
+        "alt" | "option" | "opt" => vec!["alt"],
+        "shift" => vec!["shift"],
+        "ctrl" | "control" => vec!["ctrl"],
+        "fn" => vec!["fn"],
+        sided => match MODIFIER_ORDER.iter().find(|m| **m == sided) {
+            Some(modifier) => vec![*modifier],
+            None => return None,
+        },
+    };
+    Some(expanded)
+}
+
+/// A key combination with modifiers expanded, deduplicated and in canonical order
+///
+/// Two shortcuts are bound to the same hotkey exactly when their
+/// `KeyCombination`s are equal, regardless of modifier order or aliases.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct KeyCombination {
+    /// Modifiers in canonical order (base modifiers, then their left/right variants)
+    pub modifiers: Vec<String>,
+
+    /// Lowercased key name
+    pub key: String,
+}
+
+impl KeyCombination {
+    /// Build a normalized combination from raw modifiers and key
+    ///
+    /// Unknown modifiers are kept as-is so they still participate in comparisons.
+    pub fn new(modifiers: &[String], key: &str) -> Self {
+        let mut expanded: Vec<String> = Vec::new();
+        for modifier in modifiers {
+            match expand_modifier(modifier) {
+                Some(base) => expanded.extend(base.into_iter().map(String::from)),
+                None => expanded.push(modifier.trim().to_lowercase()),
+            }
+        }
+
+        expanded.sort_by_key(|m| modifier_rank(m));
+        expanded.dedup();
+
+        Self {
+            modifiers: expanded,
+            key: key.trim().to_lowercase(),
+        }
+    }
+
+    /// Build the normalized combination of a shortcut
+    pub fn from_shortcut(shortcut: &Shortcut) -> Self {
+        Self::new(&shortcut.modifiers, &shortcut.key)
+    }
+
+    /// Parse a chord such as `shift + cmd - f`, `hyper - space` or `cmd+f`
+    pub fn parse(chord: &str) -> Result<Self, String> {
+        let chord = chord.trim();
+        if chord.is_empty() {
+            return Err("Key combination cannot be empty".to_string());
+        }
+
+        // The key follows the last '-', unless the key itself is '-'
+        let split = match chord.strip_suffix('-') {
+            Some(rest) => match rest.rfind('-') {
+                Some(i) => Some((&chord[..i], "-")),
+                None if rest.trim().is_empty() => Some(("", "-")),
+                None => return Err(format!("Missing key in '{}'", chord)),
+            },
+            None => chord.rfind('-').map(|i| (&chord[..i], &chord[i + 1..])),
+        };
+
+        let (modifier_part, key) = match split {
+            Some((modifiers, key)) => (modifiers.to_string(), key.trim().to_string()),
+            // No separator: "cmd+shift+f" or just "f"
+            None => match chord.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers.to_string(), key.trim().to_string()),
+                None => (String::new(), chord.to_string()),
+            },
+        };
+
+        if key.is_empty() {
+            return Err(format!("Missing key in '{}'", chord));
+        }
+
+        let mut modifiers = Vec::new();
+        for modifier in modifier_part
+            .split('+')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+        {
+            if expand_modifier(modifier).is_none() {
+                return Err(format!("Invalid modifier: {}", modifier));
+            }
+            modifiers.push(modifier.to_string());
+        }
+
+        Ok(Self::new(&modifiers, &key))
+    }
+
+    /// Modifiers present in exactly one of the two combinations
+    pub fn modifier_difference(&self, other: &KeyCombination) -> Vec<String> {
+        let mut difference: Vec<String> = self
+            .modifiers
+            .iter()
+            .filter(|m| !other.modifiers.contains(m))
+            .chain(
+                other
+                    .modifiers
+                    .iter()
+                    .filter(|m| !self.modifiers.contains(m)),
+            )
+            .cloned()
+            .collect();
+        difference.sort_by_key(|m| modifier_rank(m));
+        difference
+    }
+
+    /// Combinations with the same key that differ by exactly one base modifier
+    pub fn neighbors(&self) -> Vec<KeyCombination> {
+        BASE_MODIFIERS
+            .iter()
+            .map(|modifier| {
+                let mut modifiers = self.modifiers.clone();
+                if let Some(pos) = modifiers.iter().position(|m| m == modifier) {
+                    modifiers.remove(pos);
+                } else {
+                    modifiers.push(modifier.to_string());
+                }
+                KeyCombination::new(&modifiers, &self.key)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for KeyCombination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.is_empty() {
+            write!(f, "{}", self.key)
+        } else {
+            write!(f, "{} - {}", self.modifiers.join(" + "), self.key)
+        }
+    }
+}
+
+/// Sort position of a modifier (unknown modifiers sort last)
+fn modifier_rank(modifier: &str) -> usize {
+    MODIFIER_ORDER
+        .iter()
+        .position(|m| *m == modifier)
+        .unwrap_or(MODIFIER_ORDER.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_independent() {
+        let a = KeyCombination::new(&["shift".to_string(), "cmd".to_string()], "F");
+        let b = KeyCombination::new(&["cmd".to_string(), "shift".to_string()], "f");
+        assert_eq!(a, b);
+        assert_eq!(a.to_string(), "cmd + shift - f");
+    }
+
+    #[test]
+    fn test_aliases_expand() {
+        let hyper = KeyCombination::parse("hyper - a").unwrap();
+        let explicit = KeyCombination::parse("ctrl + shift + alt + cmd - a").unwrap();
+        assert_eq!(hyper, explicit);
+
+        let meh = KeyCombination::parse("meh - a").unwrap();
+        assert_eq!(meh.modifiers, vec!["alt", "shift", "ctrl"]);
+
+        // skhd binds left/right variants separately from the generic modifier
+        let left = KeyCombination::parse("lcmd - a").unwrap();
+        assert_ne!(left, KeyCombination::parse("cmd - a").unwrap());
+        assert_ne!(left, KeyCombination::parse("rcmd - a").unwrap());
+        assert_eq!(left, KeyCombination::parse("LCMD - a").unwrap());
+        assert_eq!(
+            KeyCombination::parse("ralt + lcmd - a")
+                .unwrap()
+                .to_string(),
+            "lcmd + ralt - a"
+        );
+    }
+
+    #[test]
+    fn test_parse_forms() {
+        assert_eq!(
+            KeyCombination::parse("cmd+shift+f").unwrap(),
+            KeyCombination::parse("cmd + shift - f").unwrap()
+        );
+        assert_eq!(KeyCombination::parse("f1").unwrap().to_string(), "f1");
+        assert_eq!(KeyCombination::parse("cmd - -").unwrap().key, "-");
+        assert!(KeyCombination::parse("super - a").is_err());
+        assert!(KeyCombination::parse("cmd - ").is_err());
+        assert!(KeyCombination::parse("").is_err());
+    }
+
+    #[test]
+    fn test_neighbors_and_difference() {
+        let combo = KeyCombination::parse("cmd - f").unwrap();
+        let neighbors = combo.neighbors();
+
+        assert_eq!(neighbors.len(), 5);
+        assert!(neighbors.contains(&KeyCombination::parse("f").unwrap()));
+        assert!(neighbors.contains(&KeyCombination::parse("cmd + shift - f").unwrap()));
+
+        let other = KeyCombination::parse("cmd + alt - f").unwrap();
+        assert_eq!(combo.modifier_difference(&other), vec!["alt"]);
+    }
+}
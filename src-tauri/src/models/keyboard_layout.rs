@@ -0,0 +1,75 @@
+/// Keyboard layout model mapping physical keys to the characters they produce
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A physical key position, named after the US-ANSI key in that position
+/// (W3C `KeyboardEvent.code` names such as `BracketLeft` or `IntlBackslash`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicalKey {
+    /// Position code, e.g. `Semicolon`
+    pub code: String,
+
+    /// Keyboard row: `number`, `top`, `home` or `bottom`
+    pub row: String,
+}
+
+/// A keyboard layout: which character each physical key produces without modifiers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyboardLayout {
+    /// Stable identifier, e.g. `de`
+    pub id: String,
+
+    /// Display name, e.g. `German`
+    pub name: String,
+
+    /// Physical keyboard type: `ansi` or `iso`
+    pub keyboard: String,
+
+    /// Character produced by each physical key code
+    pub keys: BTreeMap<String, String>,
+}
+
+impl KeyboardLayout {
+    /// Physical key that produces a character on this layout
+    ///
+    /// Matching is case-insensitive, as skhd binds letters by their unshifted key.
+    pub fn physical_key_for(&self, character: &str) -> Option<&str> {
+        let character = character.to_lowercase();
+        self.keys
+            .iter()
+            .find(|(_, c)| c.to_lowercase() == character)
+            .map(|(code, _)| code.as_str())
+    }
+
+    /// Character produced by a physical key on this layout
+    pub fn character_at(&self, code: &str) -> Option<&str> {
+        self.keys.get(code).map(String::as_str)
+    }
+
+    /// Check whether a character can be typed with a single key on this layout
+    pub fn has_character(&self, character: &str) -> bool {
+        self.physical_key_for(character).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_both_directions() {
+        let mut keys = BTreeMap::new();
+        keys.insert("KeyY".to_string(), "z".to_string());
+        keys.insert("KeyZ".to_string(), "y".to_string());
+        let layout = KeyboardLayout {
+            id: "de".to_string(),
+            name: "German".to_string(),
+            keyboard: "iso".to_string(),
+            keys,
+        };
+
+        assert_eq!(layout.physical_key_for("Z"), Some("KeyY"));
+        assert_eq!(layout.character_at("KeyZ"), Some("y"));
+        assert!(!layout.has_character("["));
+    }
+}
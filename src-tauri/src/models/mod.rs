@@ -1,21 +1,33 @@
 /// Data models module
 pub mod application;
 pub mod backup;
+pub mod binding_conflict;
 pub mod command_category;
 pub mod command_template;
 pub mod config;
+pub mod diagnostic;
+pub mod key_combination;
+pub mod keyboard_layout;
 pub mod log_entry;
 pub mod service_status;
 pub mod shortcut;
+pub mod system_shortcut;
 pub mod test_result;
 
 // Re-export commonly used types
 pub use application::Application;
-pub use backup::Backup;
+pub use backup::{namespace_key, Backup, BackupTrigger};
+pub use binding_conflict::{BindingConflict, ConflictKind};
 pub use command_category::CommandCategory;
 pub use command_template::{CommandParameter, CommandTemplate};
-pub use config::{ConfigFile, ParseError};
+pub use config::{
+    ConfigFile, DanglingDirective, ModeDeclaration, ParseError, TagSummary, DEFAULT_MODE,
+};
+pub use diagnostic::{Diagnostic, DiagnosticCode, Fix, RelatedLocation, Severity, Span, TextEdit};
+pub use key_combination::KeyCombination;
+pub use keyboard_layout::{KeyboardLayout, PhysicalKey};
 pub use log_entry::{LogEntry, LogLevel};
 pub use service_status::{ServiceState, ServiceStatus};
-pub use shortcut::Shortcut;
+pub use shortcut::{normalize_tag, Shortcut, WILDCARD_PROCESS};
+pub use system_shortcut::{SystemShortcut, SystemShortcutCategory};
 pub use test_result::TestResult;
@@ -1,4 +1,7 @@
 /// Keyboard Shortcut model
+use super::config::DEFAULT_MODE;
+use super::diagnostic::DiagnosticCode;
+use super::key_combination::KeyCombination;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -17,14 +20,56 @@ pub struct Shortcut {
     /// Shell command to execute
     pub command: String,
 
-    /// Optional mode name for modal shortcuts
+    /// Optional mode name for modal shortcuts (a comma-separated list for `a, b < ...`)
     pub mode: Option<String>,
 
+    /// Mode this shortcut switches to (`; mode`) instead of running a command
+    #[serde(default)]
+    pub activates: Option<String>,
+
+    /// Application this binding is limited to (a process list entry), `*` for any other app
+    #[serde(default)]
+    pub process: Option<String>,
+
+    /// Process list entry that passes the key through to the application (`"app" ~`)
+    #[serde(default)]
+    pub passthrough: bool,
+
     /// Optional inline comment
     pub comment: Option<String>,
 
     /// Original line number from config file
     pub line_number: usize,
+
+    /// Tags for grouping and filtering (stored without the leading `@`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Optional human-readable description of what the shortcut does
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Lint rules suppressed for this shortcut (codes or rule names, from `# @allow`)
+    #[serde(default)]
+    pub allowed_rules: Vec<String>,
+}
+
+/// Process list entry matching every application without its own entry
+pub const WILDCARD_PROCESS: &str = "*";
+
+/// Normalize a tag for storage and comparison
+///
+/// Strips a leading `@` and lowercases, so `@Work` and `work` are the same tag.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('@').to_lowercase()
+}
+
+/// Check that a mode name matches skhd's syntax (letters, digits, `_` and `-`)
+pub fn is_valid_mode_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
 }
 
 impl Shortcut {
@@ -36,8 +81,14 @@ impl Shortcut {
             key,
             command,
             mode: None,
+            activates: None,
+            process: None,
+            passthrough: false,
             comment: None,
             line_number,
+            tags: Vec::new(),
+            description: None,
+            allowed_rules: Vec::new(),
         }
     }
 
@@ -55,8 +106,31 @@ impl Shortcut {
             key,
             command,
             mode: None,
+            activates: None,
+            process: None,
+            passthrough: false,
             comment: None,
             line_number,
+            tags: Vec::new(),
+            description: None,
+            allowed_rules: Vec::new(),
+        }
+    }
+
+    /// Modes the shortcut is bound in (`default` when it has no mode)
+    pub fn modes(&self) -> Vec<&str> {
+        let modes: Vec<&str> = self
+            .mode
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .collect();
+        if modes.is_empty() {
+            vec![DEFAULT_MODE]
+        } else {
+            modes
         }
     }
 
@@ -72,24 +146,79 @@ impl Shortcut {
     }
 
     /// Check if this shortcut has the same key combination as another
+    ///
+    /// Compares normalized combinations (`hyper` equals `cmd + alt + shift + ctrl`)
+    /// in at least one common mode, for the same application.
     pub fn has_same_combination(&self, other: &Shortcut) -> bool {
-        if self.key != other.key {
-            return false;
+        KeyCombination::from_shortcut(self) == KeyCombination::from_shortcut(other)
+            && self.shares_mode(other)
+            && self.same_process(other)
+    }
+
+    /// Check whether two shortcuts are active in at least one common mode
+    pub fn shares_mode(&self, other: &Shortcut) -> bool {
+        let other_modes = other.modes();
+        self.modes().iter().any(|m| other_modes.contains(m))
+    }
+
+    /// Check if two shortcuts apply to the same application (both global, or the same entry)
+    pub fn same_process(&self, other: &Shortcut) -> bool {
+        match (&self.process, &other.process) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (None, None) => true,
+            _ => false,
         }
+    }
 
-        let mut self_mods = self.modifiers.clone();
-        let mut other_mods = other.modifiers.clone();
-        self_mods.sort();
-        other_mods.sort();
+    /// Check if this shortcut carries the given tag (case-insensitive, `@` optional)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        self.tags.iter().any(|t| normalize_tag(t) == tag)
+    }
 
-        self_mods == other_mods && self.mode == other.mode
+    /// Check whether a lint rule is suppressed for this shortcut
+    pub fn allows(&self, code: DiagnosticCode) -> bool {
+        self.allowed_rules
+            .iter()
+            .any(|rule| DiagnosticCode::from_name(rule) == Some(code))
     }
 
     /// Validate the shortcut
     pub fn validate(&self) -> Result<(), String> {
-        // Check command is not empty
-        if self.command.trim().is_empty() {
-            return Err("Command cannot be empty".to_string());
+        // Check the process list entry
+        match &self.process {
+            Some(process) if process.trim().is_empty() || process.contains('"') => {
+                return Err(format!("Invalid process name: {}", process));
+            }
+            None if self.passthrough => {
+                return Err("Only process list entries can pass the key through".to_string());
+            }
+            _ => {}
+        }
+        if self.passthrough {
+            if !self.command.trim().is_empty() || self.activates.is_some() {
+                return Err("A pass-through entry cannot run a command".to_string());
+            }
+        } else if self.process.is_some() && self.activates.is_some() {
+            return Err("Process list entries cannot activate a mode".to_string());
+        }
+
+        // Check command is not empty (mode activations run the mode's command instead)
+        match &self.activates {
+            None if self.command.trim().is_empty() && !self.passthrough => {
+                return Err("Command cannot be empty".to_string());
+            }
+            Some(_) if !self.command.trim().is_empty() => {
+                return Err("A shortcut cannot both run a command and activate a mode".to_string());
+            }
+            _ => {}
+        }
+
+        // Check mode names can be written back to the config
+        for mode in self.modes().into_iter().chain(self.activates.as_deref()) {
+            if !is_valid_mode_name(mode) {
+                return Err(format!("Invalid mode name: {}", mode));
+            }
         }
 
         // Check key is not empty
@@ -98,7 +227,7 @@ impl Shortcut {
         }
 
         // Check modifiers are valid
-        let valid_modifiers = ["cmd", "alt", "shift", "ctrl", "fn"];
+        let valid_modifiers = ["cmd", "alt", "shift", "ctrl", "fn", "hyper", "meh"];
         for modifier in &self.modifiers {
             if !valid_modifiers.contains(&modifier.as_str()) {
                 return Err(format!("Invalid modifier: {}", modifier));
@@ -113,6 +242,32 @@ impl Shortcut {
             return Err("Duplicate modifiers not allowed".to_string());
         }
 
+        // Check tags can be written back as a single directive line
+        for tag in &self.tags {
+            let normalized = normalize_tag(tag);
+            if normalized.is_empty()
+                || !normalized
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(format!("Invalid tag: {}", tag));
+            }
+        }
+
+        // Check suppressed rules exist, so typos do not silently suppress nothing
+        for rule in &self.allowed_rules {
+            if DiagnosticCode::from_name(rule).is_none() {
+                return Err(format!("Unknown lint rule in @allow: {}", rule));
+            }
+        }
+
+        // Check description fits on a single comment line
+        if let Some(description) = &self.description {
+            if description.contains('\n') || description.contains('\r') {
+                return Err("Description must be a single line".to_string());
+            }
+        }
+
         Ok(())
     }
 }
@@ -165,6 +320,57 @@ mod tests {
         );
 
         assert!(s1.has_same_combination(&s2));
+
+        // Entries for different applications do not collide
+        let mut s3 = s1.clone();
+        let mut s4 = s2.clone();
+        s3.process = Some("iTerm2".to_string());
+        s4.process = Some("Finder".to_string());
+        assert!(!s3.has_same_combination(&s4));
+        s4.process = Some("iterm2".to_string());
+        assert!(s3.has_same_combination(&s4));
+        assert!(!s1.has_same_combination(&s3));
+
+        // hyper is cmd + alt + shift + ctrl
+        let hyper = Shortcut::new(
+            vec!["hyper".to_string()],
+            "f".to_string(),
+            "h".to_string(),
+            3,
+        );
+        let mut explicit = hyper.clone();
+        explicit.modifiers = ["ctrl", "shift", "alt", "cmd"].map(String::from).to_vec();
+        assert!(hyper.has_same_combination(&explicit));
+
+        // Bindings in several modes collide in any mode they share
+        let mut windowed = s1.clone();
+        windowed.mode = Some("window, resize".to_string());
+        let mut resize = s2.clone();
+        resize.mode = Some("resize".to_string());
+        assert!(windowed.has_same_combination(&resize));
+        assert!(!windowed.has_same_combination(&s2));
+        resize.mode = Some("default".to_string());
+        assert!(resize.has_same_combination(&s1));
+    }
+
+    #[test]
+    fn test_validate_process_entries() {
+        let mut shortcut =
+            Shortcut::new(vec!["cmd".to_string()], "t".to_string(), String::new(), 1);
+        shortcut.process = Some("Finder".to_string());
+        shortcut.passthrough = true;
+        assert!(shortcut.validate().is_ok());
+
+        shortcut.command = "open .".to_string();
+        assert!(shortcut.validate().is_err());
+
+        shortcut.passthrough = false;
+        assert!(shortcut.validate().is_ok());
+
+        shortcut.process = None;
+        shortcut.command = String::new();
+        shortcut.passthrough = true;
+        assert!(shortcut.validate().is_err());
     }
 
     #[test]
@@ -185,4 +391,53 @@ mod tests {
         );
         assert!(invalid_empty_cmd.validate().is_err());
     }
+
+    #[test]
+    fn test_has_tag() {
+        let mut shortcut = Shortcut::new(
+            vec!["cmd".to_string()],
+            "h".to_string(),
+            "yabai -m window --focus west".to_string(),
+            1,
+        );
+        shortcut.tags = vec!["yabai".to_string(), "work".to_string()];
+
+        assert!(shortcut.has_tag("yabai"));
+        assert!(shortcut.has_tag("@Work"));
+        assert!(!shortcut.has_tag("experimental"));
+    }
+
+    #[test]
+    fn test_validate_tags_and_description() {
+        let mut shortcut = Shortcut::new(
+            vec!["cmd".to_string()],
+            "h".to_string(),
+            "echo hi".to_string(),
+            1,
+        );
+        shortcut.tags = vec!["@work".to_string()];
+        shortcut.description = Some("Say hi".to_string());
+        assert!(shortcut.validate().is_ok());
+
+        shortcut.tags = vec!["two words".to_string()];
+        assert!(shortcut.validate().is_err());
+
+        shortcut.tags = vec![];
+        shortcut.description = Some("line one\nline two".to_string());
+        assert!(shortcut.validate().is_err());
+    }
+
+    #[test]
+    fn test_allowed_rules() {
+        let mut shortcut = Shortcut::new(vec![], "f1".to_string(), "echo".to_string(), 1);
+        shortcut.allowed_rules = vec!["no-modifiers".to_string(), "SKHD004".to_string()];
+
+        assert!(shortcut.allows(DiagnosticCode::NoModifiers));
+        assert!(shortcut.allows(DiagnosticCode::LongCommand));
+        assert!(!shortcut.allows(DiagnosticCode::DuplicateBinding));
+        assert!(shortcut.validate().is_ok());
+
+        shortcut.allowed_rules = vec!["no-modifier".to_string()];
+        assert!(shortcut.validate().is_err());
+    }
 }
@@ -0,0 +1,39 @@
+/// macOS system shortcut model used for conflict detection
+use super::diagnostic::Severity;
+use serde::{Deserialize, Serialize};
+
+/// A group of system shortcuts (e.g. Mission Control, Finder)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemShortcutCategory {
+    pub id: String,
+    pub name: String,
+}
+
+/// A shortcut reserved by macOS or commonly used by applications
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemShortcut {
+    /// Stable identifier; user entries with the same ID replace built-in ones
+    pub id: String,
+
+    /// Category ID
+    pub category: String,
+
+    /// Key chord in skhd notation, e.g. `cmd + shift - 4` (any modifier order)
+    pub chord: String,
+
+    /// What the shortcut does
+    pub description: String,
+
+    /// Severity of a conflict with this shortcut
+    pub severity: Severity,
+
+    /// Set to false in the user file to switch off a built-in entry
+    #[serde(default = "SystemShortcut::default_enabled")]
+    pub enabled: bool,
+}
+
+impl SystemShortcut {
+    fn default_enabled() -> bool {
+        true
+    }
+}
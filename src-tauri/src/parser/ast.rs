@@ -1,4 +1,5 @@
 /// Abstract Syntax Tree structures for skhd configuration parsing
+use crate::models::{ConfigFile, DanglingDirective, ModeDeclaration, Shortcut};
 use serde::{Deserialize, Serialize};
 
 /// Represents a parsed keyboard shortcut from the skhd config
@@ -18,6 +19,42 @@ pub struct ParsedShortcut {
 
     /// Optional inline comment
     pub comment: Option<String>,
+
+    /// Tags from a `# @tags` directive directly above the shortcut
+    pub tags: Vec<String>,
+
+    /// Description from a `# @description` directive directly above the shortcut
+    pub description: Option<String>,
+
+    /// Lint rules suppressed by a `# @allow` directive directly above the shortcut
+    pub allowed_rules: Vec<String>,
+
+    /// Modes the shortcut is bound in (`window, resize < ...`), comma-separated
+    pub mode: Option<String>,
+
+    /// Mode the shortcut switches to (`... ; window`) instead of running a command
+    pub activates: Option<String>,
+
+    /// Application of a process list entry (`"iterm2" : ...`), `*` for the wildcard entry
+    pub process: Option<String>,
+
+    /// Process list entry passing the key through to the application (`"finder" ~`)
+    pub passthrough: bool,
+}
+
+/// Represents a mode declaration (`:: window @ : echo window`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedModeDeclaration {
+    /// Line number in the original config file
+    pub line_number: usize,
+
+    pub name: String,
+
+    /// Whether the mode captures all keys (`@`)
+    pub capture: bool,
+
+    /// Command run when the mode is activated
+    pub command: Option<String>,
 }
 
 /// Represents a comment line in the config
@@ -39,6 +76,9 @@ pub enum ConfigLine {
     /// A comment line
     Comment(ParsedComment),
 
+    /// A mode declaration
+    Mode(ParsedModeDeclaration),
+
     /// An empty line
     Empty(usize), // line number
 }
@@ -48,12 +88,19 @@ pub enum ConfigLine {
 pub struct ParsedConfig {
     /// All lines from the config file in order
     pub lines: Vec<ConfigLine>,
+
+    /// Directive comments (`# @tags ...`) not directly followed by a shortcut
+    #[serde(default)]
+    pub dangling_directives: Vec<ParsedComment>,
 }
 
 impl ParsedConfig {
     /// Create a new empty parsed config
     pub fn new() -> Self {
-        Self { lines: Vec::new() }
+        Self {
+            lines: Vec::new(),
+            dangling_directives: Vec::new(),
+        }
     }
 
     /// Get all shortcuts from the config
@@ -67,6 +114,17 @@ impl ParsedConfig {
             .collect()
     }
 
+    /// Get all mode declarations from the config
+    pub fn modes(&self) -> Vec<&ParsedModeDeclaration> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                ConfigLine::Mode(m) => Some(m),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Get all comments from the config
     pub fn comments(&self) -> Vec<&ParsedComment> {
         self.lines
@@ -77,6 +135,53 @@ impl ParsedConfig {
             })
             .collect()
     }
+
+    /// Convert to a ConfigFile for `file_path` (unmodified, with fresh shortcut IDs)
+    pub fn to_config_file(&self, file_path: String) -> ConfigFile {
+        let mut config = ConfigFile::new(file_path);
+
+        for parsed_shortcut in self.shortcuts() {
+            let mut shortcut = Shortcut::new(
+                parsed_shortcut.modifiers.clone(),
+                parsed_shortcut.key.clone(),
+                parsed_shortcut.command.clone(),
+                parsed_shortcut.line_number,
+            );
+            shortcut.tags = parsed_shortcut.tags.clone();
+            shortcut.description = parsed_shortcut.description.clone();
+            shortcut.allowed_rules = parsed_shortcut.allowed_rules.clone();
+            shortcut.mode = parsed_shortcut.mode.clone();
+            shortcut.activates = parsed_shortcut.activates.clone();
+            shortcut.process = parsed_shortcut.process.clone();
+            shortcut.passthrough = parsed_shortcut.passthrough;
+            config.add_shortcut(shortcut);
+        }
+
+        for parsed_mode in self.modes() {
+            config.modes.push(ModeDeclaration {
+                name: parsed_mode.name.clone(),
+                capture: parsed_mode.capture,
+                command: parsed_mode.command.clone(),
+                line_number: parsed_mode.line_number,
+            });
+        }
+
+        for parsed_comment in self.comments() {
+            config.global_comments.push(parsed_comment.text.clone());
+        }
+
+        config.dangling_directives = self
+            .dangling_directives
+            .iter()
+            .map(|directive| DanglingDirective {
+                text: directive.text.clone(),
+                line_number: directive.line_number,
+            })
+            .collect();
+
+        config.is_modified = false;
+        config
+    }
 }
 
 impl Default for ParsedConfig {
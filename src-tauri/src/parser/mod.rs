@@ -6,7 +6,8 @@ use pest_derive::Parser;
 use std::error::Error;
 use std::fmt;
 
-use ast::{ConfigLine, ParsedComment, ParsedConfig, ParsedShortcut};
+use crate::models::WILDCARD_PROCESS;
+use ast::{ConfigLine, ParsedComment, ParsedConfig, ParsedModeDeclaration, ParsedShortcut};
 
 #[derive(Parser)]
 #[grammar = "parser/grammar.pest"]
@@ -40,6 +41,70 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
+/// Comment directive prefix for shortcut tags (`# @tags work yabai`)
+pub const TAGS_DIRECTIVE: &str = "@tags";
+
+/// Comment directive prefix for shortcut descriptions (`# @description Focus west`)
+pub const DESCRIPTION_DIRECTIVE: &str = "@description";
+
+/// Comment directive suppressing lint rules for the next shortcut (`# @allow no-modifiers`)
+pub const ALLOW_DIRECTIVE: &str = "@allow";
+
+/// Metadata collected from directive comments, waiting for the next shortcut
+#[derive(Default)]
+struct PendingMetadata {
+    tags: Vec<String>,
+    description: Option<String>,
+    allowed_rules: Vec<String>,
+
+    /// The directive comments themselves, reported if no shortcut follows
+    directives: Vec<ParsedComment>,
+}
+
+impl PendingMetadata {
+    /// Drop directives that did not reach a shortcut, recording them as dangling
+    fn discard(&mut self, parsed_config: &mut ParsedConfig) {
+        let pending = std::mem::take(self);
+        parsed_config.dangling_directives.extend(pending.directives);
+    }
+}
+
+/// Try to read a comment as a metadata directive
+///
+/// Returns `true` if the comment was a directive and has been recorded.
+fn parse_directive(text: &str, pending: &mut PendingMetadata) -> bool {
+    if let Some(rest) = strip_directive(text, TAGS_DIRECTIVE) {
+        pending.tags.extend(
+            rest.split(|c: char| c == ',' || c.is_whitespace())
+                .map(crate::models::normalize_tag)
+                .filter(|t| !t.is_empty()),
+        );
+        true
+    } else if let Some(rest) = strip_directive(text, ALLOW_DIRECTIVE) {
+        pending.allowed_rules.extend(
+            rest.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|r| !r.is_empty())
+                .map(String::from),
+        );
+        true
+    } else if let Some(rest) = strip_directive(text, DESCRIPTION_DIRECTIVE) {
+        pending.description = Some(rest.to_string()).filter(|d| !d.is_empty());
+        true
+    } else {
+        false
+    }
+}
+
+/// Strip a directive name, requiring it to be followed by whitespace, `:` or nothing
+fn strip_directive<'a>(text: &'a str, directive: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(directive)?;
+    if rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || c == ':') {
+        Some(rest.trim_start_matches(':').trim())
+    } else {
+        None
+    }
+}
+
 /// Parse an skhd configuration file
 pub fn parse_config(content: &str) -> Result<ParsedConfig, Vec<ParseError>> {
     let mut parsed_config = ParsedConfig::new();
@@ -64,6 +129,7 @@ pub fn parse_config(content: &str) -> Result<ParsedConfig, Vec<ParseError>> {
 
     // Process each line
     let mut line_num = 1;
+    let mut pending = PendingMetadata::default();
     for pair in pairs {
         if pair.as_rule() == Rule::config_file {
             for line_pair in pair.into_inner() {
@@ -75,24 +141,54 @@ pub fn parse_config(content: &str) -> Result<ParsedConfig, Vec<ParseError>> {
                             .trim_end_matches('\r')
                             .trim_start_matches('#')
                             .trim();
-                        parsed_config.lines.push(ConfigLine::Comment(ParsedComment {
-                            line_number: line_num,
-                            text: text.to_string(),
-                        }));
+                        // Metadata directives belong to the next shortcut, not the comments
+                        if parse_directive(text, &mut pending) {
+                            pending.directives.push(ParsedComment {
+                                line_number: line_num,
+                                text: text.to_string(),
+                            });
+                        } else {
+                            // Directives only apply to a shortcut directly below them
+                            pending.discard(&mut parsed_config);
+                            parsed_config.lines.push(ConfigLine::Comment(ParsedComment {
+                                line_number: line_num,
+                                text: text.to_string(),
+                            }));
+                        }
                         line_num += 1;
                     }
                     Rule::shortcut => {
                         match parse_shortcut(&line_pair, line_num) {
-                            Ok(shortcut) => {
-                                parsed_config.lines.push(ConfigLine::Shortcut(shortcut));
+                            Ok(shortcuts) => {
+                                // Directives above a process list apply to every entry
+                                let metadata = std::mem::take(&mut pending);
+                                for mut shortcut in shortcuts {
+                                    shortcut.tags = metadata.tags.clone();
+                                    shortcut.description = metadata.description.clone();
+                                    shortcut.allowed_rules = metadata.allowed_rules.clone();
+                                    parsed_config.lines.push(ConfigLine::Shortcut(shortcut));
+                                }
                             }
                             Err(e) => {
                                 errors.push(e);
                             }
                         }
+                        // Process lists span several lines
+                        line_num += line_pair.as_str().lines().count();
+                    }
+                    Rule::mode_declaration => {
+                        // Directives only apply to a shortcut directly below them
+                        pending.discard(&mut parsed_config);
+                        parsed_config
+                            .lines
+                            .push(ConfigLine::Mode(parse_mode_declaration(
+                                &line_pair, line_num,
+                            )));
                         line_num += 1;
                     }
                     Rule::empty_line => {
+                        // Directives only apply to a shortcut directly below them
+                        pending.discard(&mut parsed_config);
                         parsed_config.lines.push(ConfigLine::Empty(line_num));
                         line_num += 1;
                     }
@@ -102,6 +198,7 @@ pub fn parse_config(content: &str) -> Result<ParsedConfig, Vec<ParseError>> {
             }
         }
     }
+    pending.discard(&mut parsed_config);
 
     if errors.is_empty() {
         Ok(parsed_config)
@@ -110,17 +207,30 @@ pub fn parse_config(content: &str) -> Result<ParsedConfig, Vec<ParseError>> {
     }
 }
 
-/// Parse a single shortcut line
+/// Parse a shortcut
+///
+/// A shortcut with a process list yields one `ParsedShortcut` per entry, each at
+/// the entry's own line.
 fn parse_shortcut(
     pair: &pest::iterators::Pair<Rule>,
     line_num: usize,
-) -> Result<ParsedShortcut, ParseError> {
+) -> Result<Vec<ParsedShortcut>, ParseError> {
     let mut modifiers = Vec::new();
     let mut key = String::new();
     let mut command = String::new();
+    let mut mode = None;
+    let mut activates = None;
+    let mut entries = Vec::new();
 
     for inner_pair in pair.clone().into_inner() {
         match inner_pair.as_rule() {
+            Rule::mode_list => {
+                let modes: Vec<&str> = inner_pair.into_inner().map(|m| m.as_str()).collect();
+                mode = Some(modes.join(", "));
+            }
+            Rule::activation => {
+                activates = Some(inner_pair.as_str().to_string());
+            }
             Rule::modifiers => {
                 for modifier_pair in inner_pair.into_inner() {
                     if modifier_pair.as_rule() == Rule::modifier {
@@ -134,6 +244,18 @@ fn parse_shortcut(
             Rule::command => {
                 command = inner_pair.as_str().trim().to_string();
             }
+            Rule::process_list => {
+                let first_line = pair.line_col().0;
+                for entry in inner_pair
+                    .into_inner()
+                    .filter(|p| p.as_rule() == Rule::process_entry)
+                {
+                    entries.push(parse_process_entry(
+                        &entry,
+                        line_num + entry.line_col().0 - first_line,
+                    ));
+                }
+            }
             _ => {}
         }
     }
@@ -146,7 +268,35 @@ fn parse_shortcut(
         });
     }
 
-    if command.is_empty() {
+    let shortcut = ParsedShortcut {
+        line_number: line_num,
+        modifiers,
+        key,
+        command,
+        comment: None,
+        tags: Vec::new(),
+        description: None,
+        allowed_rules: Vec::new(),
+        mode,
+        activates,
+        process: None,
+        passthrough: false,
+    };
+
+    if !entries.is_empty() {
+        return Ok(entries
+            .into_iter()
+            .map(|(line_number, process, command)| ParsedShortcut {
+                line_number,
+                passthrough: command.is_none(),
+                command: command.unwrap_or_default(),
+                process: Some(process),
+                ..shortcut.clone()
+            })
+            .collect());
+    }
+
+    if shortcut.command.is_empty() && shortcut.activates.is_none() {
         return Err(ParseError {
             line_number: line_num,
             column: None,
@@ -154,13 +304,62 @@ fn parse_shortcut(
         });
     }
 
-    Ok(ParsedShortcut {
+    Ok(vec![shortcut])
+}
+
+/// Parse a process list entry into (line, process, command)
+///
+/// The command is `None` for `~` entries, which pass the key through.
+fn parse_process_entry(
+    pair: &pest::iterators::Pair<Rule>,
+    line_num: usize,
+) -> (usize, String, Option<String>) {
+    let mut process = String::new();
+    let mut command = None;
+
+    for inner_pair in pair.clone().into_inner() {
+        match inner_pair.as_rule() {
+            Rule::process_name => {
+                process = inner_pair.as_str().trim_matches('"').to_string();
+            }
+            Rule::wildcard => {
+                process = WILDCARD_PROCESS.to_string();
+            }
+            Rule::command => {
+                command = Some(inner_pair.as_str().trim().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    (line_num, process, command)
+}
+
+/// Parse a mode declaration line
+fn parse_mode_declaration(
+    pair: &pest::iterators::Pair<Rule>,
+    line_num: usize,
+) -> ParsedModeDeclaration {
+    let mut declaration = ParsedModeDeclaration {
         line_number: line_num,
-        modifiers,
-        key,
-        command,
-        comment: None,
-    })
+        name: String::new(),
+        capture: false,
+        command: None,
+    };
+
+    for inner_pair in pair.clone().into_inner() {
+        match inner_pair.as_rule() {
+            Rule::mode_name => declaration.name = inner_pair.as_str().to_string(),
+            Rule::capture => declaration.capture = true,
+            Rule::command => {
+                declaration.command =
+                    Some(inner_pair.as_str().trim().to_string()).filter(|c| !c.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    declaration
 }
 
 #[cfg(test)]
@@ -209,4 +408,112 @@ mod tests {
         assert_eq!(parsed.comments().len(), 1);
         assert_eq!(parsed.shortcuts().len(), 1);
     }
+
+    #[test]
+    fn test_parse_metadata_directives() {
+        let config = "# Window focus\n# @description Focus the west window\n# @tags @work, yabai\nalt - h : yabai -m window --focus west\nalt - l : yabai -m window --focus east\n";
+        let parsed = parse_config(config).unwrap();
+
+        let shortcuts = parsed.shortcuts();
+        assert_eq!(shortcuts[0].tags, vec!["work", "yabai"]);
+        assert_eq!(
+            shortcuts[0].description,
+            Some("Focus the west window".to_string())
+        );
+        // Directives are consumed by the first shortcut only
+        assert!(shortcuts[1].tags.is_empty());
+        assert!(shortcuts[1].description.is_none());
+
+        // Directives are not reported as regular comments
+        assert_eq!(parsed.comments().len(), 1);
+    }
+
+    #[test]
+    fn test_directive_discarded_by_empty_line() {
+        let config = "# @tags work\n\ncmd - return : open -a Terminal\n";
+        let parsed = parse_config(config).unwrap();
+
+        assert!(parsed.shortcuts()[0].tags.is_empty());
+        assert_eq!(parsed.dangling_directives.len(), 1);
+        assert_eq!(parsed.dangling_directives[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_directive_discarded_by_comment_and_eof() {
+        let config = "# @tags work
+# Terminal
+cmd - return : open -a Terminal
+# @description Nothing below
+";
+        let parsed = parse_config(config).unwrap();
+
+        assert!(parsed.shortcuts()[0].tags.is_empty());
+        let dangling: Vec<usize> = parsed
+            .dangling_directives
+            .iter()
+            .map(|d| d.line_number)
+            .collect();
+        assert_eq!(dangling, vec![1, 4]);
+        assert_eq!(
+            parsed.dangling_directives[1].text,
+            "@description Nothing below"
+        );
+    }
+
+    #[test]
+    fn test_parse_modes() {
+        let config = ":: default : echo default\n:: window @ : echo window\n:: resize\nalt - w ; window\nwindow, resize < - escape ; default\nwindow < - h : yabai -m window --focus west\n";
+        let parsed = parse_config(config).unwrap();
+
+        let modes = parsed.modes();
+        assert_eq!(modes.len(), 3);
+        assert_eq!(modes[1].name, "window");
+        assert!(modes[1].capture);
+        assert_eq!(modes[1].command, Some("echo window".to_string()));
+        assert_eq!(modes[2].command, None);
+
+        let shortcuts = parsed.shortcuts();
+        assert_eq!(shortcuts[0].activates, Some("window".to_string()));
+        assert_eq!(shortcuts[0].mode, None);
+        assert_eq!(shortcuts[1].mode, Some("window, resize".to_string()));
+        assert_eq!(shortcuts[1].key, "escape");
+        assert_eq!(shortcuts[2].mode, Some("window".to_string()));
+        assert_eq!(shortcuts[2].command, "yabai -m window --focus west");
+        assert_eq!(shortcuts[2].line_number, 6);
+    }
+
+    #[test]
+    fn test_parse_process_list() {
+        let config = "# @tags term\nhyper - t [\n    \"iTerm2\" : echo term\n    # Finder keeps its own shortcut\n    \"Finder\" ~\n\n    * : open -a iTerm\n]\ncmd - t : echo after\n";
+        let parsed = parse_config(config).unwrap();
+
+        let shortcuts = parsed.shortcuts();
+        assert_eq!(shortcuts.len(), 4);
+        assert_eq!(shortcuts[0].modifiers, vec!["hyper"]);
+        assert_eq!(shortcuts[0].process, Some("iTerm2".to_string()));
+        assert_eq!(shortcuts[0].command, "echo term");
+        assert_eq!(shortcuts[0].line_number, 3);
+        assert_eq!(shortcuts[1].process, Some("Finder".to_string()));
+        assert!(shortcuts[1].passthrough);
+        assert_eq!(shortcuts[1].line_number, 5);
+        assert_eq!(shortcuts[2].process, Some("*".to_string()));
+        assert_eq!(shortcuts[2].line_number, 7);
+        assert!(shortcuts[..3].iter().all(|s| s.tags == vec!["term"]));
+
+        assert_eq!(shortcuts[3].process, None);
+        assert_eq!(shortcuts[3].line_number, 9);
+        assert!(shortcuts[3].tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_allow_directive() {
+        let config = "# @allow no-modifiers, SKHD004\n- f1 : open -a Terminal\n";
+        let parsed = parse_config(config).unwrap();
+
+        assert_eq!(
+            parsed.shortcuts()[0].allowed_rules,
+            vec!["no-modifiers", "SKHD004"]
+        );
+        assert!(parsed.comments().is_empty());
+    }
 }
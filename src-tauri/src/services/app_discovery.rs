@@ -18,6 +18,11 @@ pub fn discover_applications() -> Result<Vec<Application>, String> {
         PathBuf::from(format!("{}/Applications/Setapp", home_dir)),
     ];
 
+    Ok(discover_applications_in(search_paths))
+}
+
+/// Discovers applications in the given directories (and their subdirectories)
+pub fn discover_applications_in(search_paths: Vec<PathBuf>) -> Vec<Application> {
     let mut apps = Vec::new();
 
     // Use depth-first traversal to find all .app bundles, including in subdirectories
@@ -65,7 +70,7 @@ pub fn discover_applications() -> Result<Vec<Application>, String> {
     // Keep only the first (highest priority) app for each display name
     apps.dedup_by(|a, b| a.display_name == b.display_name);
 
-    Ok(apps)
+    apps
 }
 
 /// Determines the priority of an application based on its installation location
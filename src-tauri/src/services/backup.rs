@@ -1,15 +1,274 @@
 /// Backup service with SHA-256 checksums for configuration files
-use crate::models::Backup;
+///
+/// The backup directory is a content-addressed store: each distinct content is
+/// stored once as a blob named by its SHA-256 (`blobs/ab/abcd….gz`), and a snapshot
+/// log in the JSON manifest records each backup's original path, creation time,
+/// description and trigger. Backing up unchanged content only appends to the log.
+/// Snapshots are grouped into namespaces, one per config file, keyed by its path.
+/// `.backup` files of older versions are moved into the store by
+/// `migrate_legacy_backups`.
+use crate::models::{namespace_key, Backup, BackupTrigger};
 use crate::services::file_io::write_config_atomic;
-use crate::utils::path::{expand_path, get_backup_dir};
+use crate::services::settings::AppPaths;
+use crate::utils::path::{expand_path, get_default_config_path};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
-use std::io;
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::NamedTempFile;
+use uuid::Uuid;
+
+/// Manifest file in the backup directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Retention policy file in the skhd config directory
+pub const RETENTION_FILE: &str = "retention.json";
+
+/// Directory of content blobs in the backup directory
+pub const BLOBS_DIR: &str = "blobs";
+
+/// Current manifest format (1: one `.backup` file per backup, 2: no namespaces)
+const MANIFEST_VERSION: u32 = 3;
+
+/// Timestamp format of `.backup` file names
+const FILENAME_TIMESTAMP: &str = "%Y%m%d_%H%M%S";
+
+/// Serializes manifest read-modify-write cycles within the process
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Which backups pruning keeps
+///
+/// ```json
+/// { "keep_last": 10, "keep_daily_days": 14, "max_total_bytes": 52428800 }
+/// ```
+///
+/// Only automatic backups (see `BackupTrigger::is_automatic`) are pruned; backups the
+/// user asked for are always kept. An automatic backup is kept when it is one of the
+/// `keep_last` newest automatic ones, or the newest of its (UTC) day within the last
+/// `keep_daily_days` days. The oldest kept automatic backups are then dropped until
+/// all kept backups fit in `max_total_bytes`; the newest backup is always kept, so
+/// manual backups and the newest one alone may exceed it. Sizes are uncompressed,
+/// and content shared by several backups counts for each. Unset limits do not apply.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub keep_last: Option<usize>,
+
+    #[serde(default)]
+    pub keep_daily_days: Option<u32>,
+
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: Some(10),
+            keep_daily_days: Some(14),
+            max_total_bytes: Some(50 * 1024 * 1024),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Load the policy of a config directory (defaults if there is none)
+    pub fn load(config_dir: &Path) -> io::Result<Self> {
+        let path = config_dir.join(RETENTION_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        serde_json::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid retention policy {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Store the policy of a config directory
+    pub fn save(&self, config_dir: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_config_atomic(config_dir.join(RETENTION_FILE), &text)
+    }
+
+    /// Backups (newest first) this policy removes at `now`, as indices
+    pub fn select_pruned(
+        &self,
+        backups: &[Backup],
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<usize> {
+        let created: Vec<Option<chrono::DateTime<chrono::Utc>>> = backups
+            .iter()
+            .map(|b| {
+                chrono::DateTime::parse_from_rfc3339(&b.created_at)
+                    .ok()
+                    .map(|t| t.with_timezone(&chrono::Utc))
+            })
+            .collect();
+
+        let automatic: Vec<bool> = backups.iter().map(|b| b.trigger.is_automatic()).collect();
+
+        // Manual backups are kept; `keep_last` counts automatic backups only
+        let mut kept_automatic = 0;
+        let mut keep: Vec<bool> = automatic
+            .iter()
+            .map(|&automatic| {
+                if !automatic {
+                    return true;
+                }
+                kept_automatic += 1;
+                self.keep_last.is_none_or(|count| kept_automatic <= count)
+            })
+            .collect();
+
+        if let Some(days) = self.keep_daily_days {
+            let since = now - chrono::Duration::days(i64::from(days));
+            let mut seen_days = std::collections::BTreeSet::new();
+            for (i, time) in created.iter().enumerate() {
+                if !automatic[i] {
+                    continue;
+                }
+                if let Some(time) = time.filter(|t| *t >= since) {
+                    // Newest first, so the first backup of a day is its newest
+                    if seen_days.insert(time.date_naive()) {
+                        keep[i] = true;
+                    }
+                }
+            }
+        }
+
+        if let Some(max_bytes) = self.max_total_bytes {
+            // Backups that are never dropped count first, wherever they are in the list
+            let fixed = |i: usize| i == 0 || !automatic[i];
+            let mut total: u64 = (0..backups.len())
+                .filter(|&i| keep[i] && fixed(i))
+                .map(|i| backups[i].size_bytes)
+                .sum();
+
+            // Then the newest automatic backups, until one no longer fits
+            let mut full = false;
+            for (i, backup) in backups.iter().enumerate() {
+                if !keep[i] || fixed(i) {
+                    continue;
+                }
+                full = full || total + backup.size_bytes > max_bytes;
+                if full {
+                    keep[i] = false;
+                } else {
+                    total += backup.size_bytes;
+                }
+            }
+        }
+
+        (0..backups.len()).filter(|i| !keep[*i]).collect()
+    }
+}
+
+/// Backup metadata stored in the backup directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+
+    /// Whether new blobs are gzip-compressed
+    #[serde(default = "default_compress")]
+    compress: bool,
+
+    /// Snapshot log, oldest first
+    #[serde(default)]
+    snapshots: Vec<Snapshot>,
+
+    /// Version 1 records of `.backup` files not yet moved into the store
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    backups: Vec<LegacyEntry>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            compress: default_compress(),
+            snapshots: Vec::new(),
+            backups: Vec::new(),
+        }
+    }
+}
+
+fn default_compress() -> bool {
+    true
+}
+
+/// A backup: metadata pointing at the blob holding the content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    id: String,
+    checksum: String,
+    original_path: PathBuf,
+    #[serde(default)]
+    namespace: String,
+    created_at: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    trigger: BackupTrigger,
+    size_bytes: u64,
+}
+
+impl Snapshot {
+    fn from_backup(backup: &Backup) -> Self {
+        Self {
+            id: backup.id.clone(),
+            checksum: backup.checksum.clone(),
+            original_path: backup.original_path.clone(),
+            namespace: backup.namespace.clone(),
+            created_at: backup.created_at.clone(),
+            description: backup.description.clone(),
+            trigger: backup.trigger,
+            size_bytes: backup.size_bytes,
+        }
+    }
+
+    /// Backup record of the snapshot (None when its blob is missing)
+    fn to_backup(&self, backup_dir: &Path) -> Option<Backup> {
+        Some(Backup {
+            id: self.id.clone(),
+            file_path: find_blob(backup_dir, &self.checksum)?,
+            original_path: self.original_path.clone(),
+            namespace: self.namespace.clone(),
+            created_at: self.created_at.clone(),
+            checksum: self.checksum.clone(),
+            description: self.description.clone(),
+            size_bytes: self.size_bytes,
+            trigger: self.trigger,
+        })
+    }
+}
+
+/// Version 1 manifest record of a `<name>_<timestamp>.backup` file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LegacyEntry {
+    file_name: String,
+    original_path: PathBuf,
+    created_at: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    trigger: BackupTrigger,
+}
 
 /// Create a backup of a configuration file with SHA-256 checksum
 ///
 /// # Arguments
+/// * `paths` - Application paths (the backup goes to `paths.backup_dir`)
 /// * `source_path` - Path to file to backup
 /// * `description` - Optional description of why backup was created
 ///
@@ -17,87 +276,603 @@ use std::path::Path;
 /// * `Ok(Backup)` containing backup metadata
 /// * `Err(io::Error)` if backup fails
 pub fn create_backup<P: AsRef<Path>>(
+    paths: &AppPaths,
     source_path: P,
     description: Option<String>,
 ) -> io::Result<Backup> {
-    let source_path = expand_path(source_path);
+    create_backup_in(
+        &paths.backup_dir,
+        source_path,
+        description,
+        BackupTrigger::Manual,
+    )
+}
+
+/// Create a backup in a given backup directory and record it in the snapshot log
+///
+/// Content already in the store is not written again.
+pub fn create_backup_in<P: AsRef<Path>>(
+    backup_dir: &Path,
+    source_path: P,
+    description: Option<String>,
+    trigger: BackupTrigger,
+) -> io::Result<Backup> {
+    let source_path = normalize_source_path(source_path);
 
     // Read source file
     let content = fs::read(&source_path)?;
+    let checksum = sha256_hex(&content);
 
-    // Calculate checksum
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    let checksum = format!("{:x}", hasher.finalize());
-
-    // Create backup directory if it doesn't exist
-    let backup_dir = get_backup_dir();
-    fs::create_dir_all(&backup_dir)?;
-
-    // Generate backup filename with timestamp
-    let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-    let source_filename = source_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("config");
-    let backup_filename = format!("{}_{}.backup", source_filename, timestamp);
-    let backup_path = backup_dir.join(backup_filename);
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_store(backup_dir)?;
 
-    // Write backup file
-    fs::write(&backup_path, &content)?;
+    // Store the content under its checksum
+    let blob_path = store_blob(backup_dir, &checksum, &content, manifest.compress)?;
 
     // Create backup record
-    let backup = if let Some(desc) = description {
-        Backup::with_description(
-            backup_path,
-            source_path,
-            checksum,
-            content.len() as u64,
-            desc,
-        )
+    let mut backup = if let Some(desc) = description {
+        Backup::with_description(blob_path, source_path, checksum, content.len() as u64, desc)
     } else {
-        Backup::new(backup_path, source_path, checksum, content.len() as u64)
+        Backup::new(blob_path, source_path, checksum, content.len() as u64)
     };
+    backup.trigger = trigger;
+
+    manifest.snapshots.push(Snapshot::from_backup(&backup));
+    save_manifest(backup_dir, &manifest)?;
 
     Ok(backup)
 }
 
 /// List all backups in the backup directory
-pub fn list_backups() -> io::Result<Vec<Backup>> {
-    let backup_dir = get_backup_dir();
+pub fn list_backups(paths: &AppPaths) -> io::Result<Vec<Backup>> {
+    list_backups_in(&paths.backup_dir)
+}
+
+/// List the backups of one config file, newest first
+pub fn list_backups_for<P: AsRef<Path>>(
+    paths: &AppPaths,
+    source_path: P,
+) -> io::Result<Vec<Backup>> {
+    list_backups_for_in(&paths.backup_dir, source_path)
+}
+
+/// List the backups of one config file in a given backup directory
+pub fn list_backups_for_in<P: AsRef<Path>>(
+    backup_dir: &Path,
+    source_path: P,
+) -> io::Result<Vec<Backup>> {
+    let namespace = source_namespace(source_path);
+    let mut backups = list_backups_in(backup_dir)?;
+    backups.retain(|b| b.namespace == namespace);
+    Ok(backups)
+}
+
+/// Namespace of the backups of a config file
+///
+/// Different spellings of a path (`~`, symlinks) map to the same namespace.
+pub fn source_namespace<P: AsRef<Path>>(source_path: P) -> String {
+    namespace_key(&normalize_source_path(source_path))
+}
 
+/// Absolute path of a config file with `~` and symlinks resolved
+///
+/// A file that does not exist yet is resolved through its directory.
+pub(crate) fn normalize_source_path<P: AsRef<Path>>(source_path: P) -> PathBuf {
+    let path = expand_path(source_path);
+    if let Ok(canonical) = fs::canonicalize(&path) {
+        return canonical;
+    }
+
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .map(|parent| parent.join(name))
+            .unwrap_or(path),
+        _ => path,
+    }
+}
+
+/// List the backups recorded in a backup directory, newest first
+///
+/// Snapshots whose blob was deleted are skipped. Backup files from older versions
+/// are only listed once `migrate_legacy_backups` moved them into the store.
+pub fn list_backups_in(backup_dir: &Path) -> io::Result<Vec<Backup>> {
     if !backup_dir.exists() {
         return Ok(Vec::new());
     }
 
-    let mut backups = Vec::new();
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let manifest = load_store(backup_dir)?;
 
-    for entry in fs::read_dir(&backup_dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    // The log is chronological, so later entries win ties
+    let mut backups: Vec<(usize, Backup)> = manifest
+        .snapshots
+        .iter()
+        .filter_map(|snapshot| snapshot.to_backup(backup_dir))
+        .enumerate()
+        .collect();
+    backups.sort_by(|(a_index, a), (b_index, b)| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b_index.cmp(a_index))
+    });
 
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("backup") {
-            // Read file to calculate checksum
-            let content = fs::read(&path)?;
-            let mut hasher = Sha256::new();
-            hasher.update(&content);
-            let checksum = format!("{:x}", hasher.finalize());
+    Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+}
 
-            // Try to infer original path from filename
-            let filename = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-            let original_name = filename.split('_').next().unwrap_or("skhdrc");
-            let original_path = expand_path(format!("~/.config/skhd/{}", original_name));
+/// Add backups taken elsewhere, with their content, to a backup directory
+///
+/// Every content is checked against its backup's checksum before anything is
+/// stored. Backups whose ID is already in the log are skipped; the others keep
+/// their metadata, except the namespace, which is derived from the original path
+/// on this machine. Returns the added backups.
+pub fn add_backups_in(backup_dir: &Path, backups: &[(Backup, Vec<u8>)]) -> io::Result<Vec<Backup>> {
+    for (backup, content) in backups {
+        if sha256_hex(content) != backup.checksum {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Backup {} checksum mismatch - file may be corrupted",
+                    backup.id
+                ),
+            ));
+        }
+    }
 
-            let backup = Backup::new(path, original_path, checksum, content.len() as u64);
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_store(backup_dir)?;
 
-            backups.push(backup);
+    let mut added = Vec::new();
+    for (backup, content) in backups {
+        if manifest.snapshots.iter().any(|s| s.id == backup.id) {
+            continue;
         }
+
+        let file_path = store_blob(backup_dir, &backup.checksum, content, manifest.compress)?;
+        let backup = Backup {
+            file_path,
+            namespace: source_namespace(&backup.original_path),
+            ..backup.clone()
+        };
+        manifest.snapshots.push(Snapshot::from_backup(&backup));
+        added.push(backup);
     }
 
-    // Sort by creation time (newest first)
-    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    if !added.is_empty() {
+        manifest
+            .snapshots
+            .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        save_manifest(backup_dir, &manifest)?;
+    }
+    Ok(added)
+}
 
-    Ok(backups)
+/// Back up a file about to be overwritten
+///
+/// Nothing is backed up when the file does not exist yet, or still matches the
+/// newest backup taken of it.
+pub fn backup_before_overwrite<P: AsRef<Path>>(
+    paths: &AppPaths,
+    path: P,
+    trigger: BackupTrigger,
+) -> io::Result<Option<Backup>> {
+    backup_before_overwrite_in(&paths.backup_dir, path, trigger)
+}
+
+/// Back up a file about to be overwritten into a given backup directory
+pub fn backup_before_overwrite_in<P: AsRef<Path>>(
+    backup_dir: &Path,
+    path: P,
+    trigger: BackupTrigger,
+) -> io::Result<Option<Backup>> {
+    let path = normalize_source_path(path);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let checksum = sha256_hex(&fs::read(&path)?);
+    let unchanged = list_backups_for_in(backup_dir, &path)?
+        .first()
+        .is_some_and(|newest| newest.checksum == checksum);
+    if unchanged {
+        return Ok(None);
+    }
+
+    let description = match trigger {
+        BackupTrigger::BeforeRestore => "Before restoring a backup",
+        _ => "Before saving",
+    };
+    create_backup_in(backup_dir, &path, Some(description.to_string()), trigger).map(Some)
+}
+
+/// Apply the retention policy of the config directory to the backup directory
+///
+/// # Returns
+/// * `Ok(Vec<Backup>)` - Removed backups
+/// * `Err(io::Error)` if the policy or manifest cannot be read, or a file cannot be removed
+pub fn prune_backups(paths: &AppPaths) -> io::Result<Vec<Backup>> {
+    let policy = RetentionPolicy::load(&paths.config_dir)?;
+    prune_backups_in(&paths.backup_dir, &policy)
+}
+
+/// Apply a retention policy to a backup directory
+///
+/// The policy applies to each config file's backups separately. Blobs no
+/// remaining snapshot points at are deleted.
+pub fn prune_backups_in(backup_dir: &Path, policy: &RetentionPolicy) -> io::Result<Vec<Backup>> {
+    let mut namespaces: BTreeMap<String, Vec<Backup>> = BTreeMap::new();
+    for backup in list_backups_in(backup_dir)? {
+        namespaces
+            .entry(backup.namespace.clone())
+            .or_default()
+            .push(backup);
+    }
+
+    let now = chrono::Utc::now();
+    let pruned: Vec<Backup> = namespaces
+        .values()
+        .flat_map(|backups| {
+            policy
+                .select_pruned(backups, now)
+                .into_iter()
+                .map(|i| backups[i].clone())
+        })
+        .collect();
+    if pruned.is_empty() {
+        return Ok(pruned);
+    }
+
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_store(backup_dir)?;
+    manifest
+        .snapshots
+        .retain(|snapshot| !pruned.iter().any(|b| b.id == snapshot.id));
+    save_manifest(backup_dir, &manifest)?;
+    remove_unreferenced_blobs(backup_dir, &manifest)?;
+
+    Ok(pruned)
+}
+
+/// Path of the blob holding some content, if it is stored
+fn find_blob(backup_dir: &Path, checksum: &str) -> Option<PathBuf> {
+    // Checksums come from the manifest; never let one escape the blob directory
+    if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let dir = backup_dir.join(BLOBS_DIR).join(&checksum[..2]);
+    [format!("{}.gz", checksum), checksum.to_string()]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Store content under its checksum, unless it already is
+fn store_blob(
+    backup_dir: &Path,
+    checksum: &str,
+    content: &[u8],
+    compress: bool,
+) -> io::Result<PathBuf> {
+    if let Some(existing) = find_blob(backup_dir, checksum) {
+        return Ok(existing);
+    }
+
+    let dir = backup_dir.join(BLOBS_DIR).join(&checksum[..2]);
+    fs::create_dir_all(&dir)?;
+
+    let mut temp_file = NamedTempFile::new_in(&dir)?;
+    let path = if compress {
+        let mut encoder = GzEncoder::new(temp_file.as_file_mut(), Compression::default());
+        encoder.write_all(content)?;
+        encoder.finish()?;
+        dir.join(format!("{}.gz", checksum))
+    } else {
+        temp_file.write_all(content)?;
+        dir.join(checksum)
+    };
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(&path)?;
+
+    Ok(path)
+}
+
+/// Delete blobs no snapshot points at
+fn remove_unreferenced_blobs(backup_dir: &Path, manifest: &Manifest) -> io::Result<()> {
+    for blob in blob_files_in(backup_dir)? {
+        let name = blob.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let checksum = name.trim_end_matches(".gz");
+        if !manifest.snapshots.iter().any(|s| s.checksum == checksum) {
+            fs::remove_file(&blob)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every file in the blob directory of a backup directory
+pub(crate) fn blob_files_in(backup_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let blobs_dir = backup_dir.join(BLOBS_DIR);
+    if !blobs_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut blobs = Vec::new();
+    for prefix in fs::read_dir(&blobs_dir)? {
+        let prefix = prefix?.path();
+        if !prefix.is_dir() {
+            continue;
+        }
+        for blob in fs::read_dir(&prefix)? {
+            blobs.push(blob?.path());
+        }
+    }
+    blobs.sort();
+    Ok(blobs)
+}
+
+/// Every snapshot in the log of a backup directory, oldest first
+///
+/// Unlike `list_backups_in`, snapshots whose blob is missing are included, with
+/// an empty `file_path`.
+pub(crate) fn snapshots_in(backup_dir: &Path) -> io::Result<Vec<Backup>> {
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let manifest = load_store(backup_dir)?;
+    Ok(manifest
+        .snapshots
+        .iter()
+        .map(|snapshot| {
+            snapshot.to_backup(backup_dir).unwrap_or_else(|| Backup {
+                id: snapshot.id.clone(),
+                file_path: PathBuf::new(),
+                original_path: snapshot.original_path.clone(),
+                namespace: snapshot.namespace.clone(),
+                created_at: snapshot.created_at.clone(),
+                checksum: snapshot.checksum.clone(),
+                description: snapshot.description.clone(),
+                size_bytes: snapshot.size_bytes,
+                trigger: snapshot.trigger,
+            })
+        })
+        .collect())
+}
+
+/// Remove snapshots from the log of a backup directory, keeping their blobs
+pub(crate) fn remove_snapshots_in(backup_dir: &Path, ids: &[String]) -> io::Result<()> {
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_store(backup_dir)?;
+    manifest
+        .snapshots
+        .retain(|snapshot| !ids.contains(&snapshot.id));
+    save_manifest(backup_dir, &manifest)
+}
+
+/// Read the manifest of a backup directory, upgrading older formats
+///
+/// Must be called with `MANIFEST_LOCK` held.
+fn load_store(backup_dir: &Path) -> io::Result<Manifest> {
+    let mut manifest = load_manifest(backup_dir)?;
+
+    // Version 2 snapshots predate namespaces
+    for snapshot in &mut manifest.snapshots {
+        if snapshot.namespace.is_empty() {
+            snapshot.namespace = source_namespace(&snapshot.original_path);
+        }
+    }
+
+    if manifest.version < MANIFEST_VERSION {
+        manifest.version = MANIFEST_VERSION;
+        save_manifest(backup_dir, &manifest)?;
+    }
+    Ok(manifest)
+}
+
+/// Read the manifest of a backup directory (empty when there is none yet)
+fn load_manifest(backup_dir: &Path) -> io::Result<Manifest> {
+    let path = backup_dir.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(Manifest::default());
+    }
+
+    let text = fs::read_to_string(&path)?;
+    serde_json::from_str(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid backup manifest {}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Write the manifest of a backup directory atomically
+fn save_manifest(backup_dir: &Path, manifest: &Manifest) -> io::Result<()> {
+    let text = serde_json::to_string_pretty(manifest)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write_config_atomic(backup_dir.join(MANIFEST_FILE), &text)
+}
+
+/// Move `.backup` files of older versions into the backup store
+///
+/// Original files are looked for where skhd looks for its config: the directory
+/// of the default config file, the skhd config directory, `$XDG_CONFIG_HOME/skhd`
+/// and the home directory (for `~/.skhdrc`).
+///
+/// # Returns
+/// * `Ok(bool)` - Whether any file was moved
+/// * `Err(io::Error)` if the manifest cannot be read or a file cannot be moved
+pub fn migrate_legacy_backups(paths: &AppPaths) -> io::Result<bool> {
+    let mut search_dirs = Vec::new();
+    if let Some(dir) = get_default_config_path().parent() {
+        search_dirs.push(dir.to_path_buf());
+    }
+    search_dirs.push(paths.config_dir.clone());
+    if let Ok(xdg) = std::env::var("XDG_CONFIG_HOME") {
+        search_dirs.push(PathBuf::from(xdg).join("skhd"));
+    }
+    search_dirs.push(expand_path("~"));
+
+    migrate_legacy_backups_in(&paths.backup_dir, &paths.config_dir, &search_dirs)
+}
+
+/// Move `.backup` files of older versions into a given backup store
+///
+/// Files recorded in a version 1 manifest keep their metadata. For other files the
+/// original file name and creation time are recovered from the
+/// `<name>_<YYYYmmdd>_<HHMMSS>.backup` file name, falling back to the file's
+/// modification time. The original file is the first `<name>` found in
+/// `search_dirs`, or `<name>` in `config_dir` when there is none.
+pub fn migrate_legacy_backups_in(
+    backup_dir: &Path,
+    config_dir: &Path,
+    search_dirs: &[PathBuf],
+) -> io::Result<bool> {
+    if !backup_dir.exists() {
+        return Ok(false);
+    }
+
+    let original_path = |name: &str| {
+        search_dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| config_dir.join(name))
+    };
+
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut manifest = load_store(backup_dir)?;
+    migrate_legacy(backup_dir, original_path, &mut manifest)
+}
+
+/// Move the `.backup` files of a backup directory into its store
+///
+/// Must be called with `MANIFEST_LOCK` held.
+fn migrate_legacy(
+    backup_dir: &Path,
+    original_path: impl Fn(&str) -> PathBuf,
+    manifest: &mut Manifest,
+) -> io::Result<bool> {
+    let mut legacy = Vec::new();
+    for entry in fs::read_dir(backup_dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("backup") {
+            legacy.push(path);
+        }
+    }
+    legacy.sort();
+
+    let entries = std::mem::take(&mut manifest.backups);
+    for path in &legacy {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let content = fs::read(path)?;
+        let checksum = sha256_hex(&content);
+        let stem = file_name.trim_end_matches(".backup");
+
+        let snapshot = match entries.iter().find(|e| e.file_name == file_name) {
+            Some(entry) => Snapshot {
+                id: Uuid::new_v4().to_string(),
+                checksum: checksum.clone(),
+                original_path: entry.original_path.clone(),
+                namespace: source_namespace(&entry.original_path),
+                created_at: entry.created_at.clone(),
+                description: entry.description.clone(),
+                trigger: entry.trigger,
+                size_bytes: content.len() as u64,
+            },
+            None => {
+                let (original_name, created_at) = match split_backup_name(stem) {
+                    Some((name, created_at)) => (name, created_at),
+                    None => (stem.to_string(), modified_at(path)),
+                };
+                let original_path = original_path(&original_name);
+                Snapshot {
+                    id: Uuid::new_v4().to_string(),
+                    checksum: checksum.clone(),
+                    namespace: source_namespace(&original_path),
+                    original_path,
+                    created_at,
+                    description: None,
+                    trigger: BackupTrigger::Migrated,
+                    size_bytes: content.len() as u64,
+                }
+            }
+        };
+
+        store_blob(backup_dir, &checksum, &content, manifest.compress)?;
+        manifest.snapshots.push(snapshot);
+    }
+
+    if legacy.is_empty() {
+        return Ok(false);
+    }
+
+    // Keep the log chronological, then drop the copies now in the store
+    manifest
+        .snapshots
+        .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    save_manifest(backup_dir, manifest)?;
+    for path in &legacy {
+        fs::remove_file(path)?;
+    }
+
+    Ok(true)
+}
+
+/// Split `<name>_<YYYYmmdd>_<HHMMSS>` into the name and an RFC 3339 timestamp
+fn split_backup_name(stem: &str) -> Option<(String, String)> {
+    let mut parts = stem.rsplitn(3, '_');
+    let time = parts.next()?;
+    let date = parts.next()?;
+    let name = parts.next().filter(|n| !n.is_empty())?;
+
+    let timestamp =
+        chrono::NaiveDateTime::parse_from_str(&format!("{}_{}", date, time), FILENAME_TIMESTAMP)
+            .ok()?;
+    Some((name.to_string(), timestamp.and_utc().to_rfc3339()))
+}
+
+/// Modification time of a file as RFC 3339 (now when unavailable)
+fn modified_at(path: &Path) -> String {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    modified
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(chrono::Utc::now)
+        .to_rfc3339()
+}
+
+/// SHA-256 of some content as lowercase hex
+pub(crate) fn sha256_hex(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Read the content of a backup, verifying its checksum
+pub fn read_backup(backup: &Backup) -> io::Result<Vec<u8>> {
+    let stored = fs::read(&backup.file_path)?;
+    let content = if backup.file_path.extension().and_then(|e| e.to_str()) == Some("gz") {
+        let mut content = Vec::new();
+        GzDecoder::new(stored.as_slice())
+            .read_to_end(&mut content)
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Backup blob cannot be decompressed: {}", e),
+                )
+            })?;
+        content
+    } else {
+        stored
+    };
+
+    if sha256_hex(&content) != backup.checksum {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Backup checksum mismatch - file may be corrupted",
+        ));
+    }
+
+    Ok(content)
 }
 
 /// Restore a configuration file from a backup
@@ -116,20 +891,8 @@ pub fn restore_backup<P: AsRef<Path>>(backup: &Backup, target_path: Option<P>) -
         backup.original_path.clone()
     };
 
-    // Read backup file
-    let content = fs::read(&backup.file_path)?;
-
-    // Verify checksum
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    let checksum = format!("{:x}", hasher.finalize());
-
-    if checksum != backup.checksum {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Backup checksum mismatch - file may be corrupted",
-        ));
-    }
+    // Read backup file, verifying its checksum
+    let content = read_backup(backup)?;
 
     // Convert content to string for atomic write
     let content_str = String::from_utf8_lossy(&content);
@@ -147,13 +910,21 @@ mod tests {
     use sha2::Digest;
     use tempfile::TempDir;
 
+    /// Paths with every directory inside a temporary directory
+    fn temp_paths(temp_dir: &TempDir) -> AppPaths {
+        AppPaths::new(
+            temp_dir.path().join("config"),
+            temp_dir.path().join("backups"),
+        )
+    }
+
     #[test]
-    #[ignore] // Ignore in parallel test runs due to shared backup directory
     fn test_create_and_restore_backup() {
         use std::thread;
         use std::time::Duration;
 
         let temp_dir = TempDir::new().unwrap();
+        let paths = temp_paths(&temp_dir);
         let source_path = temp_dir.path().join("test.conf");
         let target_path = temp_dir.path().join("restored.conf");
         let content = "# Test config\ncmd - return : open -a Terminal\n";
@@ -161,18 +932,19 @@ mod tests {
         // Create source file
         fs::write(&source_path, content).unwrap();
 
-        // Create backup (goes to ~/.config/skhd/backups)
-        let backup = create_backup(&source_path, Some("Test backup".to_string())).unwrap();
+        // Create backup
+        let backup = create_backup(&paths, &source_path, Some("Test backup".to_string())).unwrap();
 
         // Small delay to ensure file is fully written
         thread::sleep(Duration::from_millis(10));
 
         // Verify backup exists and has correct checksum
+        assert!(backup.file_path.starts_with(&paths.backup_dir));
         assert!(backup.file_path.exists());
         assert_eq!(backup.description, Some("Test backup".to_string()));
 
         // Verify checksum matches
-        let backup_content = fs::read(&backup.file_path).unwrap();
+        let backup_content = read_backup(&backup).unwrap();
         let mut hasher = sha2::Sha256::new();
         hasher.update(&backup_content);
         let actual_checksum = format!("{:x}", hasher.finalize());
@@ -184,9 +956,6 @@ mod tests {
         // Verify restored content
         let restored_content = fs::read_to_string(&target_path).unwrap();
         assert_eq!(restored_content, content);
-
-        // Cleanup backup file
-        let _ = fs::remove_file(&backup.file_path);
     }
 
     #[test]
@@ -197,7 +966,7 @@ mod tests {
 
         fs::write(&source_path, content).unwrap();
 
-        let mut backup = create_backup(&source_path, None).unwrap();
+        let mut backup = create_backup(&temp_paths(&temp_dir), &source_path, None).unwrap();
 
         // Corrupt the checksum
         backup.checksum = "invalid_checksum".to_string();
@@ -207,10 +976,349 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_manifest_keeps_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let source_path = temp_dir.path().join("skhdrc");
+        fs::write(&source_path, "cmd - a : echo a\n").unwrap();
+
+        let backup = create_backup_in(
+            &backup_dir,
+            &source_path,
+            Some("Before import".to_string()),
+            BackupTrigger::Manual,
+        )
+        .unwrap();
+        assert!(backup_dir.join(MANIFEST_FILE).exists());
+
+        let backups = list_backups_in(&backup_dir).unwrap();
+        assert_eq!(backups, vec![backup.clone()]);
+        assert_eq!(
+            backups[0].original_path,
+            fs::canonicalize(&source_path).unwrap()
+        );
+        assert_eq!(backups[0].description, Some("Before import".to_string()));
+
+        // Listing does not reset the creation time
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(
+            list_backups_in(&backup_dir).unwrap()[0].created_at,
+            backup.created_at
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path();
+        fs::write(backup_dir.join("skhdrc_20240102_030405.backup"), "old\n").unwrap();
+        fs::write(backup_dir.join("work_rc_20240101_000000.backup"), "older\n").unwrap();
+        fs::write(
+            backup_dir.join("skhdrc_20240103_000000.backup"),
+            "tracked\n",
+        )
+        .unwrap();
+        fs::write(backup_dir.join("notes.txt"), "not a backup").unwrap();
+
+        // Version 1 manifest recording one of the files
+        fs::write(
+            backup_dir.join(MANIFEST_FILE),
+            r#"{ "version": 1, "backups": [{
+                "file_name": "skhdrc_20240103_000000.backup",
+                "original_path": "/work/skhdrc",
+                "created_at": "2024-01-03T00:00:00+00:00",
+                "checksum": "ignored",
+                "description": "Before import",
+                "trigger": "manual",
+                "size_bytes": 8
+            }] }"#,
+        )
+        .unwrap();
+
+        // Nothing is listed until the files are migrated
+        assert!(list_backups_in(backup_dir).unwrap().is_empty());
+        assert!(migrate_legacy_backups_in(backup_dir, Path::new("/config"), &[]).unwrap());
+
+        let backups = list_backups_in(backup_dir).unwrap();
+        assert_eq!(backups.len(), 3);
+        assert_eq!(backups[0].original_path, PathBuf::from("/work/skhdrc"));
+        assert_eq!(backups[0].description, Some("Before import".to_string()));
+        assert_eq!(backups[1].created_at, "2024-01-02T03:04:05+00:00");
+        assert_eq!(backups[1].trigger, BackupTrigger::Migrated);
+        assert_eq!(backups[1].checksum, sha256_hex(b"old\n"));
+        assert_eq!(read_backup(&backups[1]).unwrap(), b"old\n");
+        assert_eq!(backups[2].original_path, PathBuf::from("/config/work_rc"));
+
+        // The files moved into the store, and are migrated once
+        assert!(!backup_dir.join("skhdrc_20240102_030405.backup").exists());
+        assert!(backup_dir.join("notes.txt").exists());
+        assert_eq!(load_manifest(backup_dir).unwrap().version, MANIFEST_VERSION);
+        assert!(!migrate_legacy_backups_in(backup_dir, Path::new("/config"), &[]).unwrap());
+        assert_eq!(list_backups_in(backup_dir).unwrap(), backups);
+    }
+
+    #[test]
+    fn test_migrated_backups_find_their_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let config_dir = temp_dir.path().join("config");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&backup_dir).unwrap();
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::create_dir_all(&home).unwrap();
+
+        // A `~/.skhdrc` setup, with no config in the config directory
+        let config_path = home.join(".skhdrc");
+        fs::write(&config_path, "cmd - a : echo a\n").unwrap();
+        fs::write(backup_dir.join(".skhdrc_20240102_030405.backup"), "old\n").unwrap();
+
+        let search_dirs = [config_dir.clone(), home.clone()];
+        assert!(migrate_legacy_backups_in(&backup_dir, &config_dir, &search_dirs).unwrap());
+
+        // The migrated backup belongs to the loaded config
+        let backups = list_backups_for_in(&backup_dir, &config_path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].original_path, config_path);
+    }
+
+    #[test]
+    fn test_snapshots_share_blobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let source_path = temp_dir.path().join("skhdrc");
+        let content = "cmd - a : echo a\n";
+        fs::write(&source_path, content).unwrap();
+
+        // Two backups within the same second no longer collide
+        let first =
+            create_backup_in(&backup_dir, &source_path, None, BackupTrigger::BeforeSave).unwrap();
+        let second =
+            create_backup_in(&backup_dir, &source_path, None, BackupTrigger::BeforeSave).unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.file_path, second.file_path);
+        assert_eq!(
+            list_backups_in(&backup_dir).unwrap(),
+            vec![second.clone(), first]
+        );
+
+        // One compressed blob holds the content
+        let blobs: Vec<PathBuf> = fs::read_dir(backup_dir.join(BLOBS_DIR))
+            .unwrap()
+            .flat_map(|prefix| fs::read_dir(prefix.unwrap().path()).unwrap())
+            .map(|blob| blob.unwrap().path())
+            .collect();
+        assert_eq!(blobs, vec![second.file_path.clone()]);
+        assert_ne!(fs::read(&second.file_path).unwrap(), content.as_bytes());
+        assert_eq!(read_backup(&second).unwrap(), content.as_bytes());
+
+        // Pruning a snapshot keeps the blob the other one still uses
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily_days: None,
+            max_total_bytes: None,
+        };
+        assert_eq!(prune_backups_in(&backup_dir, &policy).unwrap().len(), 1);
+        assert!(second.file_path.exists());
+        assert_eq!(list_backups_in(&backup_dir).unwrap(), vec![second]);
+    }
+
+    fn dated_backup(days_ago: i64, hours: u32, size_bytes: u64) -> Backup {
+        let mut backup = Backup::new(
+            PathBuf::from(format!("/tmp/{}_{}.backup", days_ago, hours)),
+            PathBuf::from("/tmp/skhdrc"),
+            "abc".to_string(),
+            size_bytes,
+        );
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()
+            - chrono::Duration::days(days_ago);
+        backup.created_at = day.and_hms_opt(hours, 0, 0).unwrap().and_utc().to_rfc3339();
+        backup.trigger = BackupTrigger::BeforeSave;
+        backup
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 6, 30)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap()
+            .and_utc();
+        // Newest first: two today, two yesterday, one 10 days ago
+        let backups = vec![
+            dated_backup(0, 12, 10),
+            dated_backup(0, 9, 10),
+            dated_backup(1, 18, 10),
+            dated_backup(1, 8, 10),
+            dated_backup(10, 8, 10),
+        ];
+
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily_days: Some(7),
+            max_total_bytes: None,
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![1, 3, 4]);
+
+        let policy = RetentionPolicy {
+            keep_last: Some(4),
+            keep_daily_days: None,
+            max_total_bytes: Some(25),
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![2, 3, 4]);
+
+        // The newest backup survives any size limit
+        let policy = RetentionPolicy {
+            keep_last: None,
+            keep_daily_days: None,
+            max_total_bytes: Some(1),
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_retention_keeps_manual_backups() {
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 6, 30)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap()
+            .and_utc();
+        let mut backups = vec![
+            dated_backup(0, 12, 10),
+            dated_backup(0, 9, 10),
+            dated_backup(1, 18, 10),
+            dated_backup(40, 8, 10),
+            dated_backup(50, 8, 10),
+        ];
+        backups[1].trigger = BackupTrigger::Manual;
+        backups[4].trigger = BackupTrigger::Manual;
+
+        // The manual backup at index 1 does not use up `keep_last`
+        let policy = RetentionPolicy {
+            keep_last: Some(2),
+            keep_daily_days: None,
+            max_total_bytes: None,
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![3]);
+
+        // Manual backups count toward the size limit but are never dropped for it
+        let policy = RetentionPolicy {
+            keep_last: None,
+            keep_daily_days: None,
+            max_total_bytes: Some(15),
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_retention_size_limit_counts_older_manual_backups() {
+        let now = chrono::NaiveDate::from_ymd_opt(2024, 6, 30)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap()
+            .and_utc();
+        let mut backups = vec![
+            dated_backup(0, 12, 10),
+            dated_backup(0, 9, 10),
+            dated_backup(1, 18, 10),
+            dated_backup(2, 8, 10),
+        ];
+        backups[3].trigger = BackupTrigger::Manual;
+
+        let policy = RetentionPolicy {
+            keep_last: None,
+            keep_daily_days: None,
+            max_total_bytes: Some(30),
+        };
+        let pruned = policy.select_pruned(&backups, now);
+        assert_eq!(pruned, vec![2]);
+
+        // What is kept fits in the limit
+        let kept: u64 = (0..backups.len())
+            .filter(|i| !pruned.contains(i))
+            .map(|i| backups[i].size_bytes)
+            .sum();
+        assert!(kept <= 30);
+
+        // Dropped backups do not count, but older ones are dropped before newer ones
+        let backups = vec![
+            dated_backup(0, 12, 10),
+            dated_backup(0, 9, 5),
+            dated_backup(1, 18, 50),
+            dated_backup(2, 8, 5),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: None,
+            keep_daily_days: None,
+            max_total_bytes: Some(20),
+        };
+        assert_eq!(policy.select_pruned(&backups, now), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_backup_before_overwrite_and_prune() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let source_path = temp_dir.path().join("skhdrc");
+
+        // Nothing to back up before the first save
+        let first =
+            backup_before_overwrite_in(&backup_dir, &source_path, BackupTrigger::BeforeSave);
+        assert!(first.unwrap().is_none());
+
+        fs::write(&source_path, "cmd - a : echo a\n").unwrap();
+        let backup =
+            backup_before_overwrite_in(&backup_dir, &source_path, BackupTrigger::BeforeSave)
+                .unwrap()
+                .unwrap();
+        assert_eq!(backup.trigger, BackupTrigger::BeforeSave);
+        assert!(backup.trigger.is_automatic());
+
+        // Unchanged content is not backed up twice
+        let again =
+            backup_before_overwrite_in(&backup_dir, &source_path, BackupTrigger::BeforeRestore);
+        assert!(again.unwrap().is_none());
+
+        fs::write(&source_path, "cmd - b : echo b\n").unwrap();
+        let newer =
+            backup_before_overwrite_in(&backup_dir, &source_path, BackupTrigger::BeforeSave)
+                .unwrap()
+                .unwrap();
+
+        // Backups of another config are kept separately
+        let other_path = temp_dir.path().join("other_rc");
+        fs::write(&other_path, "cmd - c : echo c\n").unwrap();
+        let other =
+            create_backup_in(&backup_dir, &other_path, None, BackupTrigger::Manual).unwrap();
+        assert_ne!(other.namespace, newer.namespace);
+        assert_eq!(
+            list_backups_for_in(&backup_dir, &source_path).unwrap(),
+            vec![newer.clone(), backup.clone()]
+        );
+
+        let policy = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily_days: None,
+            max_total_bytes: None,
+        };
+        let config_dir = temp_dir.path().join("config");
+        policy.save(&config_dir).unwrap();
+        let pruned =
+            prune_backups_in(&backup_dir, &RetentionPolicy::load(&config_dir).unwrap()).unwrap();
+        assert_eq!(pruned, vec![backup.clone()]);
+        assert!(!backup.file_path.exists());
+        assert_eq!(list_backups_in(&backup_dir).unwrap(), vec![other, newer]);
+    }
+
     #[test]
     fn test_list_backups() {
-        // This test depends on backup directory state, so we'll just check it runs
-        let result = list_backups();
-        assert!(result.is_ok());
+        let temp_dir = TempDir::new().unwrap();
+        let paths = temp_paths(&temp_dir);
+        assert!(list_backups(&paths).unwrap().is_empty());
+
+        let source_path = temp_dir.path().join("skhdrc");
+        fs::write(&source_path, "cmd - a : echo a\n").unwrap();
+        let backup = create_backup(&paths, &source_path, None).unwrap();
+        assert_eq!(list_backups(&paths).unwrap(), vec![backup]);
     }
 }
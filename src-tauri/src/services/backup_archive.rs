@@ -0,0 +1,480 @@
+/// Export and import of all backups as a single archive
+///
+/// An archive is a gzip-compressed tar file holding `backups.json`, the metadata
+/// of every backup, and each distinct content once as `blobs/<sha256>`. Importing
+/// checks every backup's content against its SHA-256 before anything is added,
+/// so a damaged archive changes nothing. Backups already in the store are skipped,
+/// which makes importing the same archive twice harmless.
+///
+/// Original paths in the home directory are archived as `~/...` and expanded with
+/// the importing user's home, so backups follow their config file to another Mac.
+///
+/// Only the backup store is archived; git history lives in its repository.
+use crate::models::{Backup, BackupTrigger};
+use crate::services::backup::{add_backups_in, list_backups_in, read_backup};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// Metadata file in an archive
+pub const ARCHIVE_METADATA: &str = "backups.json";
+
+/// Directory of contents in an archive
+const ARCHIVE_BLOBS: &str = "blobs";
+
+/// Current archive format
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Largest file an imported archive may hold (uncompressed)
+const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Largest total size of an imported archive's files (uncompressed)
+const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Contents of `backups.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchiveMetadata {
+    version: u32,
+    exported_at: String,
+    backups: Vec<ArchivedBackup>,
+}
+
+/// A backup in an archive; its content is `blobs/<checksum>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ArchivedBackup {
+    id: String,
+
+    /// Original path, relative to the home directory (`~/...`) when it is in it
+    original_path: PathBuf,
+    created_at: String,
+    checksum: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    trigger: BackupTrigger,
+    size_bytes: u64,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportResult {
+    /// Backups added to the store
+    pub imported: Vec<Backup>,
+
+    /// Backups of the archive the store already had
+    pub skipped: usize,
+}
+
+/// Write every backup of a backup directory into an archive
+///
+/// Each backup's content is verified while it is read, so a corrupted store
+/// fails the export instead of producing a corrupted archive. The archive is
+/// written atomically. Paths in `home` are stored relative to it. Returns the
+/// number of backups exported.
+pub fn export_backups_in(backup_dir: &Path, archive_path: &Path, home: &Path) -> io::Result<usize> {
+    let backups = list_backups_in(backup_dir)?;
+
+    let mut contents = BTreeMap::new();
+    for backup in &backups {
+        if !contents.contains_key(&backup.checksum) {
+            let content = read_backup(backup)
+                .map_err(|e| io::Error::new(e.kind(), format!("Backup {}: {}", backup.id, e)))?;
+            contents.insert(backup.checksum.clone(), content);
+        }
+    }
+
+    let metadata = ArchiveMetadata {
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        backups: backups
+            .iter()
+            .map(|b| ArchivedBackup {
+                id: b.id.clone(),
+                original_path: portable_path(&b.original_path, home),
+                created_at: b.created_at.clone(),
+                checksum: b.checksum.clone(),
+                description: b.description.clone(),
+                trigger: b.trigger,
+                size_bytes: b.size_bytes,
+            })
+            .collect(),
+    };
+    let metadata = serde_json::to_vec_pretty(&metadata)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let dir = archive_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut temp_file = NamedTempFile::new_in(dir)?;
+    {
+        let encoder = GzEncoder::new(temp_file.as_file_mut(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        append_file(&mut builder, ARCHIVE_METADATA, &metadata)?;
+        for (checksum, content) in &contents {
+            append_file(
+                &mut builder,
+                &format!("{}/{}", ARCHIVE_BLOBS, checksum),
+                content,
+            )?;
+        }
+        builder.into_inner()?.finish()?;
+    }
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(archive_path)?;
+
+    Ok(backups.len())
+}
+
+/// Add the backups of an archive to a backup directory
+///
+/// Fails without changing anything when the archive is unreadable, holds files
+/// other than `backups.json` and `blobs/<sha256>`, exceeds the size limits, or any
+/// backup's content is missing or does not match its checksum. `~/...` paths are
+/// expanded with `home`.
+pub fn import_backups_in(
+    backup_dir: &Path,
+    archive_path: &Path,
+    home: &Path,
+) -> io::Result<ImportResult> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(archive_path)?));
+
+    let mut metadata = None;
+    let mut contents = BTreeMap::new();
+    let mut total = 0;
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        let checksum = path
+            .strip_prefix(&format!("{}/", ARCHIVE_BLOBS))
+            .filter(|c| is_sha256_hex(c))
+            .map(String::from);
+        if !entry.header().entry_type().is_file()
+            || (path != ARCHIVE_METADATA && checksum.is_none())
+        {
+            return Err(invalid_archive(&format!("unexpected entry {}", path)));
+        }
+
+        let content = read_entry(entry, &path, &mut total)?;
+        match checksum {
+            Some(checksum) => {
+                contents.insert(checksum, content);
+            }
+            None => metadata = Some(content),
+        }
+    }
+
+    let metadata = metadata.ok_or_else(|| invalid_archive("no backups.json"))?;
+    let metadata: ArchiveMetadata =
+        serde_json::from_slice(&metadata).map_err(|e| invalid_archive(&e.to_string()))?;
+    if metadata.version > ARCHIVE_VERSION {
+        return Err(invalid_archive(&format!(
+            "version {} is newer than this app supports",
+            metadata.version
+        )));
+    }
+
+    let mut backups = Vec::new();
+    for archived in metadata.backups {
+        let content = contents.get(&archived.checksum).ok_or_else(|| {
+            invalid_archive(&format!("content of backup {} is missing", archived.id))
+        })?;
+        let backup = Backup {
+            id: archived.id,
+            file_path: PathBuf::new(),
+            namespace: String::new(),
+            original_path: local_path(&archived.original_path, home),
+            created_at: archived.created_at,
+            checksum: archived.checksum,
+            description: archived.description,
+            size_bytes: archived.size_bytes,
+            trigger: archived.trigger,
+        };
+        backups.push((backup, content.clone()));
+    }
+
+    let imported = add_backups_in(backup_dir, &backups)?;
+    Ok(ImportResult {
+        skipped: backups.len() - imported.len(),
+        imported,
+    })
+}
+
+/// A path in `home` as `~/...`; other paths are kept
+fn portable_path(path: &Path, home: &Path) -> PathBuf {
+    if home.parent().is_none() {
+        return path.to_path_buf();
+    }
+
+    let canonical_home = std::fs::canonicalize(home).ok();
+    let portable = [Some(home), canonical_home.as_deref()]
+        .into_iter()
+        .flatten()
+        .find_map(|home| path.strip_prefix(home).ok())
+        .map(|rest| Path::new("~").join(rest));
+    portable.unwrap_or_else(|| path.to_path_buf())
+}
+
+/// A `~/...` path expanded with `home`; other paths are kept
+fn local_path(path: &Path, home: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Read an archive entry, enforcing the per-entry and total size limits
+///
+/// The declared size is not trusted: at most one byte past the limit is read.
+fn read_entry(entry: impl Read, path: &str, total: &mut u64) -> io::Result<Vec<u8>> {
+    let limit = MAX_ENTRY_BYTES.min(MAX_ARCHIVE_BYTES - *total);
+    let mut content = Vec::new();
+    entry.take(limit + 1).read_to_end(&mut content)?;
+
+    let size = content.len() as u64;
+    if size > limit {
+        let reason = if limit < MAX_ENTRY_BYTES {
+            format!("contents exceed {} bytes", MAX_ARCHIVE_BYTES)
+        } else {
+            format!("{} exceeds {} bytes", path, MAX_ENTRY_BYTES)
+        };
+        return Err(invalid_archive(&reason));
+    }
+    *total += size;
+    Ok(content)
+}
+
+/// Check for a lowercase hex SHA-256 digest
+fn is_sha256_hex(text: &str) -> bool {
+    text.len() == 64
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// Add a file to an archive
+fn append_file<W: io::Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    content: &[u8],
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, path, content)
+}
+
+fn invalid_archive(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid backup archive: {}", reason),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::backup::create_backup_in;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_and_import() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let source_path = temp_dir.path().join("skhdrc");
+        let archive_path = temp_dir.path().join("backups.tar.gz");
+
+        fs::write(&source_path, "cmd - a : echo a\n").unwrap();
+        let first = create_backup_in(
+            &source_dir,
+            &source_path,
+            Some("First".to_string()),
+            BackupTrigger::Manual,
+        )
+        .unwrap();
+        let same =
+            create_backup_in(&source_dir, &source_path, None, BackupTrigger::BeforeSave).unwrap();
+        fs::write(&source_path, "cmd - b : echo b\n").unwrap();
+        let second =
+            create_backup_in(&source_dir, &source_path, None, BackupTrigger::Manual).unwrap();
+
+        assert_eq!(
+            export_backups_in(&source_dir, &archive_path, temp_dir.path()).unwrap(),
+            3
+        );
+
+        // Importing on another machine keeps the metadata
+        let target_dir = temp_dir.path().join("target");
+        let result = import_backups_in(&target_dir, &archive_path, temp_dir.path()).unwrap();
+        assert_eq!(result.imported.len(), 3);
+        assert_eq!(result.skipped, 0);
+
+        let imported = list_backups_in(&target_dir).unwrap();
+        let ids: Vec<&str> = imported.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec![&second.id, &same.id, &first.id]);
+        assert_eq!(imported[2].description, Some("First".to_string()));
+        assert_eq!(imported[1].trigger, BackupTrigger::BeforeSave);
+        assert_eq!(read_backup(&imported[0]).unwrap(), b"cmd - b : echo b\n");
+
+        // Importing again adds nothing
+        let again = import_backups_in(&target_dir, &archive_path, temp_dir.path()).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.skipped, 3);
+    }
+
+    #[test]
+    fn test_import_rejects_corrupted_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("backups.tar.gz");
+        let metadata = ArchiveMetadata {
+            version: ARCHIVE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            backups: vec![ArchivedBackup {
+                id: "b1".to_string(),
+                original_path: PathBuf::from("/tmp/skhdrc"),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                checksum: "a".repeat(64),
+                description: None,
+                trigger: BackupTrigger::Manual,
+                size_bytes: 8,
+            }],
+        };
+
+        let mut file = File::create(&archive_path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(&mut file, Compression::default()));
+        append_file(
+            &mut builder,
+            ARCHIVE_METADATA,
+            &serde_json::to_vec(&metadata).unwrap(),
+        )
+        .unwrap();
+        append_file(
+            &mut builder,
+            &format!("{}/{}", ARCHIVE_BLOBS, "a".repeat(64)),
+            b"tampered",
+        )
+        .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        file.flush().unwrap();
+
+        let backup_dir = temp_dir.path().join("backups");
+        let error = import_backups_in(&backup_dir, &archive_path, temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"));
+        assert!(list_backups_in(&backup_dir).unwrap().is_empty());
+
+        // Not an archive at all
+        fs::write(&archive_path, "not gzip").unwrap();
+        assert!(import_backups_in(&backup_dir, &archive_path, temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_import_expands_home_relative_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let archive_path = temp_dir.path().join("backups.tar.gz");
+        let content = b"cmd - a : echo a\n".to_vec();
+        let backup = Backup {
+            id: "b1".to_string(),
+            file_path: PathBuf::new(),
+            original_path: PathBuf::from("/Users/work/.config/skhd/skhdrc"),
+            namespace: String::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            checksum: crate::services::backup::sha256_hex(&content),
+            description: None,
+            size_bytes: content.len() as u64,
+            trigger: BackupTrigger::Manual,
+        };
+        add_backups_in(&source_dir, &[(backup, content)]).unwrap();
+        export_backups_in(&source_dir, &archive_path, Path::new("/Users/work")).unwrap();
+
+        // The archive does not record the exporting user's home
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&archive_path).unwrap()));
+        let mut metadata = String::new();
+        archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap())
+            .find(|e| e.path().unwrap().to_string_lossy() == ARCHIVE_METADATA)
+            .unwrap()
+            .read_to_string(&mut metadata)
+            .unwrap();
+        assert!(metadata.contains("~/.config/skhd/skhdrc"));
+        assert!(!metadata.contains("/Users/work"));
+
+        // On the other Mac the backup lands in the namespace of its config there
+        let target_dir = temp_dir.path().join("target");
+        let result =
+            import_backups_in(&target_dir, &archive_path, Path::new("/Users/home")).unwrap();
+        let local = PathBuf::from("/Users/home/.config/skhd/skhdrc");
+        assert_eq!(result.imported[0].original_path, local);
+        assert_eq!(
+            result.imported[0].namespace,
+            crate::services::backup::source_namespace(&local)
+        );
+        assert_eq!(
+            crate::services::backup::list_backups_for_in(&target_dir, &local)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    /// Write an archive holding the given files
+    fn write_archive(path: &Path, files: &[(&str, &[u8])]) {
+        let mut file = File::create(path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(&mut file, Compression::default()));
+        for (name, content) in files {
+            append_file(&mut builder, name, content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        file.flush().unwrap();
+    }
+
+    #[test]
+    fn test_import_rejects_unexpected_and_oversized_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("backups.tar.gz");
+        let backup_dir = temp_dir.path().join("backups");
+        let metadata = serde_json::to_vec(&ArchiveMetadata {
+            version: ARCHIVE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            backups: Vec::new(),
+        })
+        .unwrap();
+
+        write_archive(&archive_path, &[(ARCHIVE_METADATA, &metadata)]);
+        assert!(import_backups_in(&backup_dir, &archive_path, temp_dir.path()).is_ok());
+
+        let uppercase = format!("{}/{}", ARCHIVE_BLOBS, "A".repeat(64));
+        for name in ["notes.txt", "blobs/abc", &uppercase] {
+            write_archive(
+                &archive_path,
+                &[(ARCHIVE_METADATA, &metadata), (name, b"x")],
+            );
+            let error = import_backups_in(&backup_dir, &archive_path, temp_dir.path()).unwrap_err();
+            assert!(error.to_string().contains("unexpected entry"), "{}", name);
+        }
+
+        // Zeros compress to almost nothing, but are not read past the limit
+        let huge = vec![0u8; MAX_ENTRY_BYTES as usize + 1];
+        let blob = format!("{}/{}", ARCHIVE_BLOBS, "a".repeat(64));
+        write_archive(
+            &archive_path,
+            &[(ARCHIVE_METADATA, &metadata), (&blob, &huge)],
+        );
+        assert!(fs::metadata(&archive_path).unwrap().len() < 1024 * 1024);
+        let error = import_backups_in(&backup_dir, &archive_path, temp_dir.path()).unwrap_err();
+        assert!(error.to_string().contains("exceeds"));
+        assert!(list_backups_in(&backup_dir).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,324 @@
+/// Integrity check of the backup directory
+///
+/// The audit reads every backup the snapshot log records and reports backups
+/// whose blob is missing, whose content no longer matches its SHA-256 (or cannot
+/// be decompressed), or which do not parse as an skhd config, plus blobs no
+/// backup points at. Quarantining moves the affected blobs to
+/// `quarantine/<timestamp>/` in the backup directory, next to a `snapshots.json`
+/// with the metadata of the backups taken out of the log, so nothing is deleted.
+use crate::models::Backup;
+use crate::parser::parse_config;
+use crate::services::backup::{
+    blob_files_in, read_backup, remove_snapshots_in, snapshots_in, BLOBS_DIR,
+};
+use crate::services::file_io::write_config_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Quarantine directory in the backup directory
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Metadata of quarantined backups in a quarantine directory
+const QUARANTINED_SNAPSHOTS: &str = "snapshots.json";
+
+/// What is wrong with a backup or blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupIssueKind {
+    /// The blob holding the backup's content is gone
+    Missing,
+
+    /// The content does not match the checksum, or cannot be decompressed
+    Corrupt,
+
+    /// The content is intact but is not a valid skhd config
+    Unparseable,
+
+    /// A blob no backup points at
+    Orphaned,
+}
+
+/// A problem found by the audit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupIssue {
+    pub kind: BackupIssueKind,
+
+    /// Affected backup (None for orphaned blobs)
+    pub backup: Option<Backup>,
+
+    /// Affected blob file (None for missing blobs)
+    pub path: Option<PathBuf>,
+
+    pub message: String,
+}
+
+/// Result of auditing a backup directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// Number of backups checked
+    pub checked: usize,
+
+    pub issues: Vec<BackupIssue>,
+}
+
+impl AuditReport {
+    /// Whether no problem was found
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Outcome of a quarantine
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineResult {
+    /// Directory the files were moved to (None when nothing was quarantined)
+    pub directory: Option<PathBuf>,
+
+    /// Issues resolved by the quarantine
+    pub quarantined: Vec<BackupIssue>,
+}
+
+/// Check every backup and blob of a backup directory
+///
+/// Backups sharing a blob are checked once and reported separately.
+pub fn audit_backups_in(backup_dir: &Path) -> io::Result<AuditReport> {
+    let backups = snapshots_in(backup_dir)?;
+    let mut report = AuditReport {
+        checked: backups.len(),
+        issues: Vec::new(),
+    };
+
+    // Result of checking each blob, by checksum
+    let mut checked: BTreeMap<String, Option<(BackupIssueKind, String)>> = BTreeMap::new();
+    for backup in &backups {
+        if backup.file_path.as_os_str().is_empty() {
+            report.issues.push(BackupIssue {
+                kind: BackupIssueKind::Missing,
+                backup: Some(backup.clone()),
+                path: None,
+                message: "Backup content is missing".to_string(),
+            });
+            continue;
+        }
+
+        let problem = match checked.get(&backup.checksum) {
+            Some(problem) => problem.clone(),
+            None => {
+                let problem = check_content(backup)?;
+                checked.insert(backup.checksum.clone(), problem.clone());
+                problem
+            }
+        };
+        if let Some((kind, message)) = problem {
+            report.issues.push(BackupIssue {
+                kind,
+                backup: Some(backup.clone()),
+                path: Some(backup.file_path.clone()),
+                message,
+            });
+        }
+    }
+
+    for blob in blob_files_in(backup_dir)? {
+        let referenced = backups.iter().any(|b| b.file_path == blob);
+        if !referenced {
+            report.issues.push(BackupIssue {
+                kind: BackupIssueKind::Orphaned,
+                backup: None,
+                path: Some(blob),
+                message: "No backup points at this file".to_string(),
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Audit a backup directory and quarantine the problems of the given kinds
+///
+/// Affected blobs move to a new quarantine directory, and backups pointing at
+/// them (or at missing blobs) are removed from the snapshot log.
+pub fn quarantine_backups_in(
+    backup_dir: &Path,
+    kinds: &[BackupIssueKind],
+) -> io::Result<QuarantineResult> {
+    let report = audit_backups_in(backup_dir)?;
+    let quarantined: Vec<BackupIssue> = report
+        .issues
+        .into_iter()
+        .filter(|issue| kinds.contains(&issue.kind))
+        .collect();
+    if quarantined.is_empty() {
+        return Ok(QuarantineResult::default());
+    }
+
+    let directory = create_quarantine_dir(backup_dir)?;
+
+    // Record the backups first, so their metadata survives a failed move
+    let backups: Vec<&Backup> = quarantined
+        .iter()
+        .filter_map(|issue| issue.backup.as_ref())
+        .collect();
+    if !backups.is_empty() {
+        let text = serde_json::to_string_pretty(&backups)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_config_atomic(directory.join(QUARANTINED_SNAPSHOTS), &text)?;
+    }
+
+    let mut moved = Vec::new();
+    for path in quarantined.iter().filter_map(|issue| issue.path.as_ref()) {
+        if moved.contains(path) || !path.exists() {
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default();
+        let blobs_dir = directory.join(BLOBS_DIR);
+        fs::create_dir_all(&blobs_dir)?;
+        fs::rename(path, blobs_dir.join(name))?;
+        moved.push(path.clone());
+    }
+
+    let ids: Vec<String> = backups.iter().map(|b| b.id.clone()).collect();
+    remove_snapshots_in(backup_dir, &ids)?;
+
+    Ok(QuarantineResult {
+        directory: Some(directory),
+        quarantined,
+    })
+}
+
+/// Problem with a backup's content, if any
+fn check_content(backup: &Backup) -> io::Result<Option<(BackupIssueKind, String)>> {
+    let content = match read_backup(backup) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return Ok(Some((BackupIssueKind::Corrupt, e.to_string())))
+        }
+        Err(e) => return Err(e),
+    };
+
+    match parse_config(&String::from_utf8_lossy(&content)) {
+        Ok(_) => Ok(None),
+        Err(errors) => {
+            let message = match errors.first() {
+                Some(first) if errors.len() > 1 => {
+                    format!("{} (and {} more errors)", first, errors.len() - 1)
+                }
+                Some(first) => first.to_string(),
+                None => "Backup does not parse".to_string(),
+            };
+            Ok(Some((BackupIssueKind::Unparseable, message)))
+        }
+    }
+}
+
+/// Create a new, empty quarantine directory named by the current time
+fn create_quarantine_dir(backup_dir: &Path) -> io::Result<PathBuf> {
+    let parent = backup_dir.join(QUARANTINE_DIR);
+    fs::create_dir_all(&parent)?;
+
+    let stamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let mut directory = parent.join(&stamp);
+    let mut attempt = 1;
+    loop {
+        match fs::create_dir(&directory) {
+            Ok(()) => return Ok(directory),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                attempt += 1;
+                directory = parent.join(format!("{}-{}", stamp, attempt));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BackupTrigger;
+    use crate::services::backup::{create_backup_in, list_backups_in};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_and_quarantine() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_dir = temp_dir.path().join("backups");
+        let source_path = temp_dir.path().join("skhdrc");
+        let backup = |content: &str| {
+            fs::write(&source_path, content).unwrap();
+            create_backup_in(&backup_dir, &source_path, None, BackupTrigger::Manual).unwrap()
+        };
+
+        let healthy = backup("cmd - a : echo a\n");
+        let corrupt = backup("cmd - b : echo b\n");
+        let unparseable = backup("cmd - : broken\n");
+        let missing = backup("cmd - c : echo c\n");
+        let report = audit_backups_in(&backup_dir).unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0].message.contains("line 1"));
+
+        fs::write(&corrupt.file_path, b"not gzip").unwrap();
+        fs::remove_file(&missing.file_path).unwrap();
+        let orphan = backup_dir.join(BLOBS_DIR).join("ab").join("stray");
+        fs::create_dir_all(orphan.parent().unwrap()).unwrap();
+        fs::write(&orphan, "stray").unwrap();
+
+        let report = audit_backups_in(&backup_dir).unwrap();
+        assert_eq!(report.checked, 4);
+        let mut found: Vec<(BackupIssueKind, Option<String>)> = report
+            .issues
+            .iter()
+            .map(|i| (i.kind, i.backup.as_ref().map(|b| b.id.clone())))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                (BackupIssueKind::Missing, Some(missing.id.clone())),
+                (BackupIssueKind::Corrupt, Some(corrupt.id.clone())),
+                (BackupIssueKind::Unparseable, Some(unparseable.id.clone())),
+                (BackupIssueKind::Orphaned, None),
+            ]
+        );
+
+        // Unparseable backups stay unless asked for
+        let result = quarantine_backups_in(
+            &backup_dir,
+            &[
+                BackupIssueKind::Missing,
+                BackupIssueKind::Corrupt,
+                BackupIssueKind::Orphaned,
+            ],
+        )
+        .unwrap();
+        assert_eq!(result.quarantined.len(), 3);
+        let directory = result.directory.unwrap();
+        assert!(directory.starts_with(backup_dir.join(QUARANTINE_DIR)));
+        assert!(directory.join(BLOBS_DIR).join("stray").exists());
+        assert!(!orphan.exists());
+        assert!(!corrupt.file_path.exists());
+
+        let recorded: Vec<Backup> = serde_json::from_str(
+            &fs::read_to_string(directory.join(QUARANTINED_SNAPSHOTS)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(recorded.len(), 2);
+
+        let remaining: Vec<String> = list_backups_in(&backup_dir)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.id)
+            .collect();
+        assert_eq!(remaining, vec![unparseable.id.clone(), healthy.id.clone()]);
+
+        let report = audit_backups_in(&backup_dir).unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, BackupIssueKind::Unparseable);
+
+        // Nothing left of the chosen kinds: no quarantine directory is created
+        let again = quarantine_backups_in(&backup_dir, &[BackupIssueKind::Corrupt]).unwrap();
+        assert_eq!(again, QuarantineResult::default());
+    }
+}
@@ -0,0 +1,285 @@
+/// Differences between two versions of a config file
+///
+/// A diff has two views: the changed lines, and the bindings added, removed or
+/// changed. Bindings are matched by mode, process and normalized key combination,
+/// so reordering or respelling a chord (`shift + cmd` vs `cmd + shift`) is not a change.
+use crate::models::{KeyCombination, Shortcut, DEFAULT_MODE};
+use crate::parser::parse_config;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a line differs between the old and new text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// A line of a line diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+
+    /// Line number in the old text (None for added lines)
+    pub old_line: Option<usize>,
+
+    /// Line number in the new text (None for removed lines)
+    pub new_line: Option<usize>,
+
+    pub text: String,
+}
+
+/// A binding present in both versions with different settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShortcutChange {
+    /// Binding key the versions were matched by
+    pub binding: String,
+
+    pub before: Shortcut,
+    pub after: Shortcut,
+
+    /// Changed fields (`command`, `description`, ...)
+    pub fields: Vec<String>,
+}
+
+/// Bindings added, removed and changed between two versions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShortcutDiff {
+    pub added: Vec<Shortcut>,
+    pub removed: Vec<Shortcut>,
+    pub changed: Vec<ShortcutChange>,
+
+    /// Number of bindings identical in both versions
+    pub unchanged: usize,
+}
+
+/// Differences between two versions of a config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    pub lines: Vec<DiffLine>,
+
+    /// Binding-level diff (None when either version does not parse)
+    pub shortcuts: Option<ShortcutDiff>,
+}
+
+impl ConfigDiff {
+    /// Diff two config texts
+    pub fn between(old: &str, new: &str) -> Self {
+        let shortcuts = match (parse_config(old), parse_config(new)) {
+            (Ok(old), Ok(new)) => Some(diff_shortcuts(
+                &old.to_config_file(String::new()).shortcuts,
+                &new.to_config_file(String::new()).shortcuts,
+            )),
+            _ => None,
+        };
+
+        Self {
+            lines: diff_lines(old, new),
+            shortcuts,
+        }
+    }
+
+    /// Whether both versions have the same lines
+    pub fn is_empty(&self) -> bool {
+        self.lines
+            .iter()
+            .all(|line| line.kind == DiffLineKind::Unchanged)
+    }
+}
+
+/// Line diff of two texts (longest common subsequence)
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, old_line, new_line, text: &str| DiffLine {
+        kind,
+        old_line,
+        new_line,
+        text: text.to_string(),
+    };
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(line(
+                DiffLineKind::Unchanged,
+                Some(i + 1),
+                Some(j + 1),
+                old[i],
+            ));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+            lines.push(line(DiffLineKind::Added, None, Some(j + 1), new[j]));
+            j += 1;
+        } else {
+            lines.push(line(DiffLineKind::Removed, Some(i + 1), None, old[i]));
+            i += 1;
+        }
+    }
+    lines
+}
+
+/// Binding-level diff of two shortcut lists
+///
+/// Bindings sharing a key are paired in file order; extra ones are added/removed.
+pub fn diff_shortcuts(old: &[Shortcut], new: &[Shortcut]) -> ShortcutDiff {
+    let mut by_binding: BTreeMap<String, (Vec<&Shortcut>, Vec<&Shortcut>)> = BTreeMap::new();
+    for shortcut in old {
+        by_binding
+            .entry(binding_key(shortcut))
+            .or_default()
+            .0
+            .push(shortcut);
+    }
+    for shortcut in new {
+        by_binding
+            .entry(binding_key(shortcut))
+            .or_default()
+            .1
+            .push(shortcut);
+    }
+
+    let mut diff = ShortcutDiff::default();
+    for (binding, (before, after)) in by_binding {
+        for (before, after) in before.iter().zip(&after) {
+            let fields = changed_fields(before, after);
+            if fields.is_empty() {
+                diff.unchanged += 1;
+            } else {
+                diff.changed.push(ShortcutChange {
+                    binding: binding.clone(),
+                    before: (*before).clone(),
+                    after: (*after).clone(),
+                    fields,
+                });
+            }
+        }
+        diff.removed
+            .extend(before.iter().skip(after.len()).map(|s| (*s).clone()));
+        diff.added
+            .extend(after.iter().skip(before.len()).map(|s| (*s).clone()));
+    }
+
+    diff.added.sort_by_key(|s| s.line_number);
+    diff.removed.sort_by_key(|s| s.line_number);
+    diff.changed.sort_by_key(|c| c.after.line_number);
+    diff
+}
+
+/// Key a binding is matched by: modes, normalized chord and process
+///
+/// e.g. `cmd - a`, `resize, window < alt - h`, `cmd - t [iTerm2]`
+pub fn binding_key(shortcut: &Shortcut) -> String {
+    let mut modes = shortcut.modes();
+    modes.sort_unstable();
+
+    let mut key = KeyCombination::from_shortcut(shortcut).to_string();
+    if modes != [DEFAULT_MODE] {
+        key = format!("{} < {}", modes.join(", "), key);
+    }
+    if let Some(process) = &shortcut.process {
+        key = format!("{} [{}]", key, process);
+    }
+    key
+}
+
+/// Names of the settings that differ between two versions of a binding
+pub fn changed_fields(before: &Shortcut, after: &Shortcut) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut compare = |name: &str, same: bool| {
+        if !same {
+            fields.push(name.to_string());
+        }
+    };
+
+    compare("command", before.command == after.command);
+    compare("activates", before.activates == after.activates);
+    compare("passthrough", before.passthrough == after.passthrough);
+    compare("description", before.description == after.description);
+    compare("tags", before.tags == after.tags);
+    compare("allowed_rules", before.allowed_rules == after.allowed_rules);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc\n", "a\nc\nd\n");
+        let summary: Vec<(DiffLineKind, Option<usize>, Option<usize>, &str)> = lines
+            .iter()
+            .map(|l| (l.kind, l.old_line, l.new_line, l.text.as_str()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (DiffLineKind::Unchanged, Some(1), Some(1), "a"),
+                (DiffLineKind::Removed, Some(2), None, "b"),
+                (DiffLineKind::Unchanged, Some(3), Some(2), "c"),
+                (DiffLineKind::Added, None, Some(3), "d"),
+            ]
+        );
+        assert!(ConfigDiff::between("a\n", "a\n").is_empty());
+    }
+
+    #[test]
+    fn test_diff_shortcuts() {
+        let old = "\
+cmd - a : echo a
+shift + cmd - b : echo b
+cmd - c : echo c
+alt - t [
+    \"iTerm2\" : echo term
+]
+";
+        let new = "\
+cmd + shift - b : echo b
+# @description Say A
+cmd - a : echo A
+alt - t [
+    \"iTerm2\" : echo term
+    \"Finder\" : echo finder
+]
+cmd - d : echo d
+";
+        let diff = ConfigDiff::between(old, new);
+        let shortcuts = diff.shortcuts.unwrap();
+
+        // Respelled chord: unchanged
+        assert_eq!(shortcuts.unchanged, 2);
+        assert_eq!(shortcuts.changed.len(), 1);
+        assert_eq!(shortcuts.changed[0].binding, "cmd - a");
+        assert_eq!(shortcuts.changed[0].fields, vec!["command", "description"]);
+
+        let added: Vec<String> = shortcuts.added.iter().map(binding_key).collect();
+        assert_eq!(added, vec!["alt - t [Finder]", "cmd - d"]);
+        assert_eq!(shortcuts.removed.len(), 1);
+        assert_eq!(shortcuts.removed[0].key, "c");
+    }
+
+    #[test]
+    fn test_unparseable_version() {
+        let diff = ConfigDiff::between("cmd - a : echo a\n", "cmd - : broken\n");
+        assert!(diff.shortcuts.is_none());
+        assert!(!diff.is_empty());
+    }
+}
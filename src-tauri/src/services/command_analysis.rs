@@ -0,0 +1,472 @@
+/// Static analysis of shortcut commands: missing executables and applications
+///
+/// Commands are tokenized (not run); every simple command's program is resolved
+/// against a PATH, and `open -a`/`open -b` targets are checked against the
+/// installed applications.
+use crate::models::Application;
+use crate::services::app_discovery::discover_applications;
+use crate::services::path_validator::validate_file_executable;
+use crate::services::shell_lexer::{simple_commands, tokenize, Token};
+use crate::utils::path::expand_path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shell builtins and keywords that never need to be on PATH
+const SHELL_BUILTINS: [&str; 40] = [
+    ":", ".", "[", "alias", "bg", "break", "cd", "command", "continue", "echo", "eval", "exec",
+    "exit", "export", "false", "fg", "getopts", "hash", "jobs", "kill", "let", "local", "printf",
+    "pwd", "read", "readonly", "return", "set", "shift", "source", "test", "times", "trap", "true",
+    "type", "ulimit", "umask", "unalias", "unset", "wait",
+];
+
+/// Programs that run the command given in their arguments
+const WRAPPERS: [&str; 9] = [
+    "sudo", "doas", "env", "nohup", "exec", "time", "nice", "command", "xargs",
+];
+
+/// Options of wrappers that take a separate value (e.g. `sudo -u root`, `xargs -I {}`)
+const WRAPPER_VALUE_OPTIONS: [&str; 9] = ["-u", "-g", "-n", "-C", "-h", "-p", "-I", "-L", "-P"];
+
+/// Homebrew's program directories (Apple Silicon, then Intel)
+///
+/// Apps started from the Finder get launchd's PATH, which lacks them, while skhd
+/// itself is usually installed and started by Homebrew.
+const HOMEBREW_PATH_DIRS: [&str; 4] = [
+    "/opt/homebrew/bin",
+    "/opt/homebrew/sbin",
+    "/usr/local/bin",
+    "/usr/local/sbin",
+];
+
+/// How long a detected environment is reused before applications are scanned again
+const ENVIRONMENT_TTL: Duration = Duration::from_secs(60);
+
+/// Commands whose analysis an environment remembers before starting over
+const MAX_ANALYZED_COMMANDS: usize = 4096;
+
+/// Environments detected recently, by PATH override
+static DETECTED: Mutex<Vec<(Option<String>, Instant, CommandEnvironment)>> = Mutex::new(Vec::new());
+
+/// Where commands are resolved: the PATH directories and the installed applications
+#[derive(Debug, Clone, Default)]
+pub struct CommandEnvironment {
+    /// Directories searched for programs (empty skips the executable check)
+    pub path_dirs: Vec<PathBuf>,
+
+    /// Installed applications (None skips the `open -a` check)
+    pub applications: Option<Vec<Application>>,
+
+    /// Problems found per command, shared by clones of this environment
+    analyzed: Arc<Mutex<HashMap<String, Vec<CommandProblem>>>>,
+}
+
+/// Kind of problem found in a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandProblemKind {
+    MissingExecutable,
+    MissingApplication,
+}
+
+/// A problem found in a command
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CommandProblem {
+    pub kind: CommandProblemKind,
+
+    /// Program, application name, bundle ID or path that could not be found
+    pub name: String,
+
+    /// Byte range of the offending word in the command
+    pub start: usize,
+    pub end: usize,
+
+    pub message: String,
+}
+
+impl CommandEnvironment {
+    /// Environment with explicit PATH directories and applications
+    pub fn new(path_dirs: Vec<PathBuf>, applications: Option<Vec<Application>>) -> Self {
+        Self {
+            path_dirs,
+            applications,
+            analyzed: Arc::default(),
+        }
+    }
+
+    /// Environment of this machine
+    ///
+    /// `path` overrides the default of `$PATH` plus Homebrew's directories (skhd
+    /// usually runs with a different PATH than the app). Applications come from
+    /// `discover_applications`; an empty list is treated as unknown so other
+    /// platforms do not report every app as missing.
+    pub fn detect(path: Option<&str>) -> Self {
+        let path_dirs = match path {
+            Some(path) => split_path(path),
+            None => default_path_dirs(&std::env::var("PATH").unwrap_or_default()),
+        };
+
+        Self::new(
+            path_dirs,
+            discover_applications().ok().filter(|apps| !apps.is_empty()),
+        )
+    }
+
+    /// Environment of this machine, reusing one detected in the last minute
+    ///
+    /// Scanning the installed applications is too slow to repeat on every edit.
+    pub fn cached(path: Option<&str>) -> Self {
+        let mut detected = DETECTED.lock().unwrap();
+        detected.retain(|(_, at, _)| at.elapsed() < ENVIRONMENT_TTL);
+        if let Some((_, _, environment)) = detected.iter().find(|(p, _, _)| p.as_deref() == path) {
+            return environment.clone();
+        }
+
+        let environment = Self::detect(path);
+        detected.push((path.map(String::from), Instant::now(), environment.clone()));
+        environment
+    }
+
+    /// Problems in a command, analyzed once per environment
+    pub fn analyze(&self, command: &str) -> Vec<CommandProblem> {
+        if let Some(problems) = self.analyzed.lock().unwrap().get(command) {
+            return problems.clone();
+        }
+
+        let problems = analyze_command(command, self);
+        let mut analyzed = self.analyzed.lock().unwrap();
+        if analyzed.len() >= MAX_ANALYZED_COMMANDS {
+            analyzed.clear();
+        }
+        analyzed.insert(command.to_string(), problems.clone());
+        problems
+    }
+
+    /// Find a program on PATH
+    pub fn resolve_executable(&self, name: &str) -> Option<PathBuf> {
+        self.path_dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|candidate| is_executable_file(candidate))
+    }
+
+    /// Find an application by the name `open -a` accepts (bundle name or file name)
+    pub fn find_application(&self, name: &str) -> Option<&Application> {
+        let name = name.trim_end_matches('/').trim_end_matches(".app");
+        self.applications.as_ref()?.iter().find(|app| {
+            app.display_name.eq_ignore_ascii_case(name)
+                || PathBuf::from(&app.app_path)
+                    .file_stem()
+                    .is_some_and(|stem| stem.to_string_lossy().eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Find an application by bundle identifier
+    pub fn find_bundle(&self, bundle_id: &str) -> Option<&Application> {
+        self.applications
+            .as_ref()?
+            .iter()
+            .find(|app| app.bundle_id.eq_ignore_ascii_case(bundle_id))
+    }
+}
+
+/// Split a PATH value into directories
+pub fn split_path(path: &str) -> Vec<PathBuf> {
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(expand_path)
+        .collect()
+}
+
+/// Directories of a PATH value followed by any of Homebrew's it lacks
+fn default_path_dirs(path: &str) -> Vec<PathBuf> {
+    let mut dirs = split_path(path);
+    for dir in HOMEBREW_PATH_DIRS.map(PathBuf::from) {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// Find missing programs and applications in a command
+///
+/// Commands that fail to tokenize are skipped; syntax is checked separately.
+pub fn analyze_command(command: &str, environment: &CommandEnvironment) -> Vec<CommandProblem> {
+    let Ok(tokens) = tokenize(command) else {
+        return Vec::new();
+    };
+
+    let mut problems = Vec::new();
+    for simple in simple_commands(&tokens) {
+        let words = strip_wrappers(&simple.words);
+        let Some(program) = words.first() else {
+            continue;
+        };
+
+        // Programs computed at runtime cannot be checked
+        if program.expands {
+            continue;
+        }
+
+        if let Some(problem) = check_program(program, environment) {
+            problems.push(problem);
+            continue;
+        }
+
+        let base_name = program.text.rsplit('/').next().unwrap_or(&program.text);
+        if base_name == "open" {
+            problems.extend(check_open_targets(&words[1..], environment));
+        }
+    }
+    problems
+}
+
+/// Skip wrapper programs (`sudo`, `env`, ...) and their options
+pub(crate) fn strip_wrappers(words: &[Token]) -> &[Token] {
+    let mut rest = words;
+    while let Some(first) = rest.first() {
+        if first.quoted || !WRAPPERS.contains(&first.text.as_str()) {
+            break;
+        }
+        rest = &rest[1..];
+
+        while let Some(word) = rest.first() {
+            if WRAPPER_VALUE_OPTIONS.contains(&word.text.as_str()) {
+                rest = rest.get(2..).unwrap_or(&[]);
+            } else if word.text.starts_with('-') || word.text.contains('=') {
+                rest = &rest[1..];
+            } else {
+                break;
+            }
+        }
+    }
+    rest
+}
+
+/// Check that a program exists
+fn check_program(program: &Token, environment: &CommandEnvironment) -> Option<CommandProblem> {
+    let name = &program.text;
+
+    if name.contains('/') {
+        if is_executable_file(&expand_path(name)) {
+            return None;
+        }
+        return Some(problem(
+            CommandProblemKind::MissingExecutable,
+            program,
+            format!("'{}' does not exist or is not executable", name),
+        ));
+    }
+
+    if SHELL_BUILTINS.contains(&name.as_str())
+        || environment.path_dirs.is_empty()
+        || environment.resolve_executable(name).is_some()
+    {
+        return None;
+    }
+
+    Some(problem(
+        CommandProblemKind::MissingExecutable,
+        program,
+        format!("'{}' was not found on PATH", name),
+    ))
+}
+
+/// Check `open -a <app>` and `open -b <bundle id>` targets
+fn check_open_targets(args: &[Token], environment: &CommandEnvironment) -> Vec<CommandProblem> {
+    if environment.applications.is_none() {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.text.as_str() {
+            // Everything after --args is passed to the application
+            "--args" => break,
+            "-a" => {
+                let Some(target) = iter.next().filter(|t| !t.expands) else {
+                    continue;
+                };
+                let found = if target.text.contains('/') {
+                    expand_path(&target.text).exists()
+                } else {
+                    environment.find_application(&target.text).is_some()
+                };
+                if !found {
+                    problems.push(problem(
+                        CommandProblemKind::MissingApplication,
+                        target,
+                        format!("Application '{}' is not installed", target.text),
+                    ));
+                }
+            }
+            "-b" => {
+                let Some(target) = iter.next().filter(|t| !t.expands) else {
+                    continue;
+                };
+                if environment.find_bundle(&target.text).is_none() {
+                    problems.push(problem(
+                        CommandProblemKind::MissingApplication,
+                        target,
+                        format!("No installed application has bundle ID '{}'", target.text),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    problems
+}
+
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file() && validate_file_executable(path).unwrap_or(false)
+}
+
+fn problem(kind: CommandProblemKind, token: &Token, message: String) -> CommandProblem {
+    CommandProblem {
+        kind,
+        name: token.text.clone(),
+        start: token.start,
+        end: token.end,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::app_discovery::discover_applications_in;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn fake_executable(dir: &Path, name: &str) {
+        let path = dir.join(name);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn fake_app(dir: &Path, file_name: &str, bundle_name: &str, bundle_id: &str) {
+        let contents = dir.join(format!("{}.app", file_name)).join("Contents");
+        fs::create_dir_all(&contents).unwrap();
+        fs::write(
+            contents.join("Info.plist"),
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>CFBundleName</key><string>{}</string>
+    <key>CFBundleIdentifier</key><string>{}</string>
+    <key>CFBundleExecutable</key><string>{}</string>
+</dict>
+</plist>"#,
+                bundle_name, bundle_id, bundle_name
+            ),
+        )
+        .unwrap();
+    }
+
+    /// PATH with `yabai` and `open`, and two installed apps
+    fn environment(dir: &TempDir) -> CommandEnvironment {
+        let bin = dir.path().join("bin");
+        let apps = dir.path().join("Applications");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(&apps).unwrap();
+        fake_executable(&bin, "yabai");
+        fake_executable(&bin, "open");
+        fake_app(&apps, "Visual Studio Code", "Code", "com.microsoft.VSCode");
+        fake_app(&apps, "Safari", "Safari", "com.apple.Safari");
+
+        CommandEnvironment::new(
+            split_path(&bin.to_string_lossy()),
+            Some(discover_applications_in(vec![apps])),
+        )
+    }
+
+    fn names(problems: &[CommandProblem]) -> Vec<&str> {
+        problems.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_missing_executables() {
+        let dir = TempDir::new().unwrap();
+        let env = environment(&dir);
+
+        assert!(analyze_command("yabai -m window --focus west", &env).is_empty());
+        assert!(analyze_command("echo hi && cd ~ && FOO=1 yabai -m space", &env).is_empty());
+
+        let problems = analyze_command("sudo -u root skhd --reload | jq .", &env);
+        assert_eq!(names(&problems), vec!["skhd", "jq"]);
+        assert_eq!(problems[0].kind, CommandProblemKind::MissingExecutable);
+
+        let problems = analyze_command("/nonexistent/bin/tool arg", &env);
+        assert_eq!(names(&problems), vec!["/nonexistent/bin/tool"]);
+
+        // Runtime-computed programs are not checked
+        assert!(analyze_command("$EDITOR ~/.skhdrc", &env).is_empty());
+    }
+
+    #[test]
+    fn test_missing_applications() {
+        let dir = TempDir::new().unwrap();
+        let env = environment(&dir);
+
+        assert!(analyze_command("open -a \"Visual Studio Code\" ~/src", &env).is_empty());
+        assert!(analyze_command("open -a code", &env).is_empty());
+        assert!(analyze_command("open -a Safari.app", &env).is_empty());
+        assert!(analyze_command("open -b com.apple.Safari", &env).is_empty());
+
+        let problems = analyze_command(
+            "open -a 'Google Chrome' && open -b com.example.Missing --args -a Nope",
+            &env,
+        );
+        assert_eq!(
+            names(&problems),
+            vec!["Google Chrome", "com.example.Missing"]
+        );
+        assert!(problems
+            .iter()
+            .all(|p| p.kind == CommandProblemKind::MissingApplication));
+    }
+
+    #[test]
+    fn test_default_path_includes_homebrew() {
+        let dirs = default_path_dirs("/usr/bin:/bin:/usr/local/bin");
+        assert_eq!(
+            dirs,
+            [
+                "/usr/bin",
+                "/bin",
+                "/usr/local/bin",
+                "/opt/homebrew/bin",
+                "/opt/homebrew/sbin",
+                "/usr/local/sbin"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn test_analysis_is_remembered() {
+        let dir = TempDir::new().unwrap();
+        let env = environment(&dir);
+        let command = "jq . && open -a Nothing";
+        let problems = env.analyze(command);
+        assert_eq!(names(&problems), vec!["jq", "Nothing"]);
+
+        // Clones share what was analyzed
+        let clone = env.clone();
+        assert_eq!(clone.analyzed.lock().unwrap().len(), 1);
+        assert_eq!(clone.analyze(command), problems);
+    }
+
+    #[test]
+    fn test_unknown_environment_skips_checks() {
+        let env = CommandEnvironment::default();
+        assert!(analyze_command("not-a-program && open -a Nothing", &env).is_empty());
+    }
+}
@@ -0,0 +1,721 @@
+/// Detection of destructive or privileged shortcut commands
+///
+/// Generalizes `CommandTemplate::requires_admin` to arbitrary commands: every
+/// shortcut's command is tokenized and checked for patterns that can destroy data,
+/// need administrator rights or take the machine down. Such commands are only run
+/// from the app after explicit confirmation.
+use crate::services::command_analysis::strip_wrappers;
+use crate::services::shell_lexer::{simple_commands, tokenize, SimpleCommand, Token};
+use serde::{Deserialize, Serialize};
+
+/// Programs that run their arguments with elevated privileges
+const PRIVILEGE_PROGRAMS: [&str; 2] = ["sudo", "doas"];
+
+/// Programs that stop other processes by name
+const KILL_PROGRAMS: [&str; 2] = ["killall", "pkill"];
+
+/// Signals of `kill` that cannot be caught
+const FORCE_KILL_SIGNALS: [&str; 3] = ["9", "KILL", "SIGKILL"];
+
+/// Programs that shut down, restart or halt the machine
+const POWER_PROGRAMS: [&str; 3] = ["shutdown", "reboot", "halt"];
+
+/// Programs that download content
+const DOWNLOAD_PROGRAMS: [&str; 2] = ["curl", "wget"];
+
+/// Interpreters that run a script read from stdin or an argument
+const INTERPRETERS: [&str; 11] = [
+    "sh",
+    "bash",
+    "zsh",
+    "dash",
+    "ksh",
+    "fish",
+    "python",
+    "python3",
+    "perl",
+    "ruby",
+    "osascript",
+];
+
+/// Shells that run the script given with `-c`
+const SCRIPT_SHELLS: [&str; 6] = ["sh", "bash", "zsh", "dash", "ksh", "fish"];
+
+/// `diskutil` verbs (lowercase) that destroy data, besides the `erase*` ones
+const DISKUTIL_DESTRUCTIVE: [&str; 6] = [
+    "partitiondisk",
+    "zerodisk",
+    "randomdisk",
+    "reformat",
+    "deletevolume",
+    "deletecontainer",
+];
+
+/// Package managers and the subcommands that remove packages
+const PACKAGE_REMOVALS: [(&str, &[&str]); 4] = [
+    ("brew", &["uninstall", "remove", "rm"]),
+    ("npm", &["uninstall", "remove", "rm", "un"]),
+    ("pip", &["uninstall"]),
+    ("pip3", &["uninstall"]),
+];
+
+/// Programs whose path arguments are modified
+const FILE_WRITERS: [&str; 9] = [
+    "cp", "mv", "rm", "ln", "tee", "touch", "mkdir", "chmod", "chown",
+];
+
+/// Directories owned by the system
+const SYSTEM_PATHS: [&str; 8] = [
+    "/System",
+    "/Library",
+    "/bin",
+    "/sbin",
+    "/usr",
+    "/etc",
+    "/private/etc",
+    "/dev",
+];
+
+/// Paths under system directories that are safe to write
+const WRITABLE_EXCEPTIONS: [&str; 5] = [
+    "/usr/local",
+    "/dev/null",
+    "/dev/stdout",
+    "/dev/stderr",
+    "/dev/tty",
+];
+
+/// Kind of risk a command carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskKind {
+    /// `rm -r`, `rm -rf`
+    RecursiveDelete,
+
+    /// `rm` of home directory paths or glob patterns, `find -delete`, `find -exec rm`
+    FileDelete,
+
+    /// `dd of=/dev/...`, `diskutil erase*`, `mkfs`
+    DiskOperation,
+
+    /// `sudo`, `doas`, `osascript ... with administrator privileges`
+    Privileged,
+
+    /// Downloaded script piped into an interpreter (`curl ... | sh`)
+    RemoteScript,
+
+    /// `killall`, `pkill`, `kill -9`
+    KillProcesses,
+
+    /// `shutdown`, `reboot`, `halt`
+    PowerControl,
+
+    /// Redirection or file operation targeting a system directory
+    SystemPathWrite,
+
+    /// `brew uninstall`, `npm uninstall`, `pip uninstall`
+    PackageRemoval,
+
+    /// Command could not be tokenized, so its risks are unknown
+    Unanalyzable,
+}
+
+/// A risky construct found in a command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRisk {
+    pub kind: RiskKind,
+    pub message: String,
+
+    /// Byte range of the offending word in the command
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Safety assessment of a command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandSafety {
+    /// Command must not run without explicit confirmation
+    pub requires_confirmation: bool,
+
+    /// Command runs with administrator privileges (same meaning as for templates)
+    pub requires_admin: bool,
+    pub risks: Vec<CommandRisk>,
+}
+
+/// Find risky constructs in a command
+///
+/// Commands that fail to tokenize are reported as one `Unanalyzable` risk spanning
+/// the whole command: the shell may still run them, so they need confirmation.
+/// Scripts run with `sh -c` or `eval` are checked too, and their risks reported at
+/// the words holding them.
+pub fn find_risks(command: &str) -> Vec<CommandRisk> {
+    let tokens = match tokenize(command) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            return vec![CommandRisk {
+                kind: RiskKind::Unanalyzable,
+                message: format!("Command could not be analyzed: {}", error.message),
+                start: 0,
+                end: command.len(),
+            }]
+        }
+    };
+
+    let commands = simple_commands(&tokens);
+    let mut risks = Vec::new();
+    for (i, simple) in commands.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| &commands[p]);
+        check_simple_command(simple, previous, &mut risks);
+    }
+    risks
+}
+
+/// Assess a command
+pub fn assess_command(command: &str) -> CommandSafety {
+    let risks = find_risks(command);
+    CommandSafety {
+        requires_confirmation: !risks.is_empty(),
+        requires_admin: risks.iter().any(|r| r.kind == RiskKind::Privileged),
+        risks,
+    }
+}
+
+/// Check that a command may run, given whether the user confirmed it
+pub fn ensure_confirmed(command: &str, confirmed: bool) -> Result<(), String> {
+    let risks = find_risks(command);
+    if confirmed || risks.is_empty() {
+        return Ok(());
+    }
+
+    let reasons: Vec<&str> = risks.iter().map(|r| r.message.as_str()).collect();
+    Err(format!(
+        "Command requires confirmation before running: {}",
+        reasons.join("; ")
+    ))
+}
+
+fn check_simple_command(
+    simple: &SimpleCommand,
+    previous: Option<&SimpleCommand>,
+    risks: &mut Vec<CommandRisk>,
+) {
+    let words = strip_wrappers(&simple.words);
+    let wrappers = &simple.words[..simple.words.len() - words.len()];
+    for wrapper in wrappers
+        .iter()
+        .filter(|w| PRIVILEGE_PROGRAMS.contains(&w.text.as_str()))
+    {
+        risks.push(risk(
+            RiskKind::Privileged,
+            wrapper,
+            format!("Runs with administrator privileges ({})", wrapper.text),
+        ));
+    }
+
+    for (operator, target) in &simple.redirects {
+        if operator.text.contains('>') && is_system_path(&target.text) {
+            risks.push(risk(
+                RiskKind::SystemPathWrite,
+                target,
+                format!("Writes to system path {}", target.text),
+            ));
+        }
+    }
+
+    let Some(program) = words.first() else {
+        return;
+    };
+    let name = program.text.rsplit('/').next().unwrap_or(&program.text);
+    let args = &words[1..];
+
+    if SCRIPT_SHELLS.contains(&name) {
+        if let Some(script) = shell_script(args) {
+            check_script(&script.text, script, script, risks);
+        }
+    }
+
+    if name == "eval" {
+        if let (Some(first), Some(last)) = (args.first(), args.last()) {
+            let script: Vec<&str> = args.iter().map(|a| a.text.as_str()).collect();
+            check_script(&script.join(" "), first, last, risks);
+        }
+    }
+
+    if name == "rm" {
+        let (options, operands) = split_options(args);
+        if options.iter().any(|o| is_recursive_flag(o)) {
+            risks.push(risk(
+                RiskKind::RecursiveDelete,
+                program,
+                "Deletes files recursively".to_string(),
+            ));
+        } else {
+            for operand in operands {
+                if is_home_path(operand) {
+                    risks.push(risk(
+                        RiskKind::FileDelete,
+                        operand,
+                        format!("Deletes files in the home directory ({})", operand.text),
+                    ));
+                } else if is_glob(operand) {
+                    risks.push(risk(
+                        RiskKind::FileDelete,
+                        operand,
+                        format!("Deletes files matching a pattern ({})", operand.text),
+                    ));
+                }
+            }
+        }
+    }
+
+    if name == "find" {
+        check_find(args, risks);
+    }
+
+    if KILL_PROGRAMS.contains(&name) {
+        risks.push(risk(
+            RiskKind::KillProcesses,
+            program,
+            format!("Terminates processes by name ({})", name),
+        ));
+    }
+
+    if name == "kill" && is_force_kill(args) {
+        risks.push(risk(
+            RiskKind::KillProcesses,
+            program,
+            "Force-kills processes (kill -9)".to_string(),
+        ));
+    }
+
+    if name == "dd" {
+        for arg in args {
+            let Some(target) = arg.text.strip_prefix("of=") else {
+                continue;
+            };
+            if target.starts_with("/dev/") && is_system_path(target) {
+                risks.push(risk(
+                    RiskKind::DiskOperation,
+                    arg,
+                    format!("Writes directly to device {} (dd)", target),
+                ));
+            } else if is_system_path(target) {
+                risks.push(risk(
+                    RiskKind::SystemPathWrite,
+                    arg,
+                    format!("Modifies system path {} (dd)", target),
+                ));
+            }
+        }
+    }
+
+    if name == "diskutil" {
+        let destructive = args.iter().find(|a| {
+            let verb = a.text.to_ascii_lowercase();
+            verb.contains("erase") || DISKUTIL_DESTRUCTIVE.contains(&verb.as_str())
+        });
+        if let Some(verb) = destructive {
+            risks.push(risk(
+                RiskKind::DiskOperation,
+                verb,
+                format!("Erases or repartitions a disk (diskutil {})", verb.text),
+            ));
+        }
+    }
+
+    if name.starts_with("mkfs") || name.starts_with("newfs") {
+        risks.push(risk(
+            RiskKind::DiskOperation,
+            program,
+            format!("Formats a disk ({})", name),
+        ));
+    }
+
+    if POWER_PROGRAMS.contains(&name) {
+        risks.push(risk(
+            RiskKind::PowerControl,
+            program,
+            format!("Shuts down or restarts the computer ({})", name),
+        ));
+    }
+
+    let removes_packages = PACKAGE_REMOVALS.iter().any(|(manager, subcommands)| {
+        name == *manager
+            && args
+                .iter()
+                .find(|a| !a.text.starts_with('-'))
+                .is_some_and(|a| subcommands.contains(&a.text.as_str()))
+    });
+    if removes_packages {
+        risks.push(risk(
+            RiskKind::PackageRemoval,
+            program,
+            format!("Uninstalls packages ({})", name),
+        ));
+    }
+
+    if name == "osascript"
+        && args
+            .iter()
+            .any(|a| a.text.contains("with administrator privileges"))
+    {
+        risks.push(risk(
+            RiskKind::Privileged,
+            program,
+            "Runs an AppleScript with administrator privileges".to_string(),
+        ));
+    }
+
+    if INTERPRETERS.contains(&name) {
+        let piped_download = previous.is_some_and(|p| {
+            p.terminator.as_deref() == Some("|")
+                && strip_wrappers(&p.words)
+                    .first()
+                    .is_some_and(|w| DOWNLOAD_PROGRAMS.contains(&w.text.as_str()))
+        });
+        let substituted_download = args.iter().any(|a| {
+            DOWNLOAD_PROGRAMS.iter().any(|d| {
+                a.text.contains(&format!("$({} ", d)) || a.text.contains(&format!("`{} ", d))
+            })
+        });
+        if piped_download || substituted_download {
+            risks.push(risk(
+                RiskKind::RemoteScript,
+                program,
+                format!("Runs a downloaded script with {}", name),
+            ));
+        }
+    }
+
+    if FILE_WRITERS.contains(&name) {
+        // `cp` and `ln` only modify their destination
+        let targets = match name {
+            "cp" | "ln" => args.last().map_or(&[][..], std::slice::from_ref),
+            _ => args,
+        };
+        for arg in targets.iter().filter(|a| is_system_path(&a.text)) {
+            risks.push(risk(
+                RiskKind::SystemPathWrite,
+                arg,
+                format!("Modifies system path {} ({})", arg.text, name),
+            ));
+        }
+    }
+}
+
+/// Check the risks of a script run by a command, reporting them at `first..last`
+fn check_script(script: &str, first: &Token, last: &Token, risks: &mut Vec<CommandRisk>) {
+    risks.extend(find_risks(script).into_iter().map(|r| CommandRisk {
+        start: first.start,
+        end: last.end,
+        ..r
+    }));
+}
+
+/// Script of a shell's `-c` option (the first operand when the options include `c`)
+fn shell_script(args: &[Token]) -> Option<&Token> {
+    let mut with_command = false;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.text.as_str() {
+            "-o" | "+o" => {
+                rest.next();
+            }
+            "--" => return with_command.then(|| rest.next()).flatten(),
+            text if text.starts_with('-') && !text.starts_with("--") => {
+                with_command |= text[1..].contains('c');
+            }
+            text if text.starts_with("--") => {}
+            _ => return with_command.then_some(arg),
+        }
+    }
+    None
+}
+
+/// Check `find` for actions deleting what it finds, and for risky `-exec` commands
+fn check_find(args: &[Token], risks: &mut Vec<CommandRisk>) {
+    let mut rest = args;
+    while let Some((arg, tail)) = rest.split_first() {
+        rest = tail;
+        match arg.text.as_str() {
+            "-delete" => risks.push(risk(
+                RiskKind::FileDelete,
+                arg,
+                "Deletes the files it finds (find -delete)".to_string(),
+            )),
+            "-exec" | "-execdir" | "-ok" | "-okdir" => {
+                let end = rest
+                    .iter()
+                    .position(|w| w.text == ";" || w.text == "+")
+                    .unwrap_or(rest.len());
+                let command = SimpleCommand {
+                    assignments: Vec::new(),
+                    words: rest[..end].to_vec(),
+                    redirects: Vec::new(),
+                    terminator: None,
+                };
+                rest = rest.get(end + 1..).unwrap_or(&[]);
+
+                let found = risks.len();
+                check_simple_command(&command, None, risks);
+                let runs_rm = strip_wrappers(&command.words)
+                    .first()
+                    .is_some_and(|p| p.text.rsplit('/').next() == Some("rm"));
+                if runs_rm && risks.len() == found {
+                    risks.push(risk(
+                        RiskKind::FileDelete,
+                        arg,
+                        format!("Deletes the files it finds (find {} rm)", arg.text),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split arguments into options and operands; everything after `--` is an operand
+fn split_options(args: &[Token]) -> (Vec<&Token>, Vec<&Token>) {
+    let end = args
+        .iter()
+        .position(|a| a.text == "--")
+        .unwrap_or(args.len());
+    let (options, mut operands): (Vec<&Token>, Vec<&Token>) = args[..end]
+        .iter()
+        .partition(|a| a.text.len() > 1 && a.text.starts_with('-'));
+    operands.extend(args.get(end + 1..).unwrap_or(&[]));
+    (options, operands)
+}
+
+/// Check for `kill -9`, `kill -KILL`, `kill -s KILL` and similar
+fn is_force_kill(args: &[Token]) -> bool {
+    let is_kill_signal = |signal: &str| {
+        FORCE_KILL_SIGNALS
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(signal))
+    };
+    args.iter()
+        .enumerate()
+        .any(|(i, arg)| match arg.text.as_str() {
+            "-s" | "-n" => args.get(i + 1).is_some_and(|s| is_kill_signal(&s.text)),
+            text => text.strip_prefix('-').is_some_and(is_kill_signal),
+        })
+}
+
+/// Check for a path in the home directory (`~`, `~/...`, `$HOME/...`)
+fn is_home_path(arg: &Token) -> bool {
+    let text = arg.text.as_str();
+    let tilde = !arg.quoted && (text == "~" || text.starts_with("~/"));
+    let home = arg.expands && (text.starts_with("$HOME") || text.starts_with("${HOME}"));
+    tilde || home
+}
+
+/// Check for an unquoted word with glob characters
+fn is_glob(arg: &Token) -> bool {
+    !arg.quoted && arg.text.contains(['*', '?', '['])
+}
+
+/// Check for `-r`, `-R`, `-rf`, `--recursive` and similar
+fn is_recursive_flag(arg: &Token) -> bool {
+    match arg.text.strip_prefix("--") {
+        Some(long) => long == "recursive",
+        None => arg.text.starts_with('-') && arg.text[1..].chars().any(|c| c == 'r' || c == 'R'),
+    }
+}
+
+/// Check whether a path lies in a system directory
+pub fn is_system_path(path: &str) -> bool {
+    let under = |dir: &str| {
+        path == dir
+            || path
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+    path == "/"
+        || (SYSTEM_PATHS.iter().any(|dir| under(dir))
+            && !WRITABLE_EXCEPTIONS.iter().any(|dir| under(dir)))
+}
+
+fn risk(kind: RiskKind, token: &Token, message: String) -> CommandRisk {
+    CommandRisk {
+        kind,
+        message,
+        start: token.start,
+        end: token.end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(command: &str) -> Vec<RiskKind> {
+        find_risks(command).into_iter().map(|r| r.kind).collect()
+    }
+
+    #[test]
+    fn test_safe_commands() {
+        for command in [
+            "open -a Terminal",
+            "yabai -m window --focus west 2>/dev/null",
+            "rm build/old.zip",
+            "rm -- -rf",
+            "rm -f '*.log'",
+            "echo hi > /tmp/log && cat /usr/share/dict/words | head",
+            "cp ~/bin/tool /usr/local/bin/tool",
+            "cp /etc/hosts ~/hosts.bak",
+            "curl -s https://example.com | jq .",
+            "brew upgrade",
+            "chmod +x ~/bin/tool",
+            "echo 'sudo rm -rf /'",
+            "bash -c 'echo hi'",
+            "command -v yabai",
+            "dd if=/dev/zero of=/tmp/blank bs=1m count=1",
+            "diskutil list",
+            "find . -name '*.log' -print",
+            "kill 1234",
+        ] {
+            assert!(find_risks(command).is_empty(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_risky_commands() {
+        assert_eq!(kinds("rm -rf ~/tmp"), vec![RiskKind::RecursiveDelete]);
+        assert_eq!(
+            kinds("rm --recursive build"),
+            vec![RiskKind::RecursiveDelete]
+        );
+        assert_eq!(
+            kinds("sudo rm -Rf /Library/Caches"),
+            vec![
+                RiskKind::Privileged,
+                RiskKind::RecursiveDelete,
+                RiskKind::SystemPathWrite
+            ]
+        );
+        assert_eq!(
+            kinds("curl -fsSL https://example.com/install.sh | bash"),
+            vec![RiskKind::RemoteScript]
+        );
+        assert_eq!(
+            kinds("sh -c \"$(curl -fsSL https://example.com/install.sh)\""),
+            vec![RiskKind::RemoteScript]
+        );
+        assert_eq!(kinds("killall Dock"), vec![RiskKind::KillProcesses]);
+        assert_eq!(
+            kinds("brew uninstall --cask foo"),
+            vec![RiskKind::PackageRemoval]
+        );
+        assert_eq!(kinds("nohup shutdown -h now"), vec![RiskKind::PowerControl]);
+        assert_eq!(
+            kinds("echo 127.0.0.1 example.com >> /etc/hosts"),
+            vec![RiskKind::SystemPathWrite]
+        );
+        assert_eq!(
+            kinds("osascript -e 'do shell script \"x\" with administrator privileges'"),
+            vec![RiskKind::Privileged]
+        );
+    }
+
+    #[test]
+    fn test_nested_and_wrapped_commands() {
+        assert_eq!(kinds("sh -c 'rm -rf ~'"), vec![RiskKind::RecursiveDelete]);
+        assert_eq!(
+            kinds("bash -c \"sudo reboot\""),
+            vec![RiskKind::Privileged, RiskKind::PowerControl]
+        );
+        assert_eq!(
+            kinds("zsh -lc 'killall Dock'"),
+            vec![RiskKind::KillProcesses]
+        );
+        assert_eq!(
+            kinds("eval 'rm -rf /'"),
+            vec![RiskKind::RecursiveDelete, RiskKind::SystemPathWrite]
+        );
+        assert_eq!(
+            kinds("command rm -rf /"),
+            vec![RiskKind::RecursiveDelete, RiskKind::SystemPathWrite]
+        );
+        assert_eq!(kinds("ls | xargs rm -rf"), vec![RiskKind::RecursiveDelete]);
+        assert_eq!(
+            kinds("exec env FOO=1 nohup shutdown -r now"),
+            vec![RiskKind::PowerControl]
+        );
+        assert_eq!(
+            kinds("env doas halt"),
+            vec![RiskKind::Privileged, RiskKind::PowerControl]
+        );
+
+        // Risks of a nested script are reported at the word holding it
+        let command = "open -a Finder && sh -c 'rm -rf ~'";
+        let risks = find_risks(command);
+        assert_eq!(&command[risks[0].start..risks[0].end], "'rm -rf ~'");
+    }
+
+    #[test]
+    fn test_delete_disk_and_kill_commands() {
+        assert_eq!(kinds("find ~ -delete"), vec![RiskKind::FileDelete]);
+        assert_eq!(
+            kinds("find . -name '*.tmp' -exec rm {} \\;"),
+            vec![RiskKind::FileDelete]
+        );
+        assert_eq!(
+            kinds("find /tmp -exec rm -rf {} +"),
+            vec![RiskKind::RecursiveDelete]
+        );
+        assert_eq!(
+            kinds("dd if=/dev/zero of=/dev/disk2"),
+            vec![RiskKind::DiskOperation]
+        );
+        assert_eq!(
+            kinds("diskutil eraseDisk APFS Empty disk2"),
+            vec![RiskKind::DiskOperation]
+        );
+        assert_eq!(
+            kinds("diskutil partitionDisk disk2 GPT JHFS+ x 0b"),
+            vec![RiskKind::DiskOperation]
+        );
+        assert_eq!(kinds("mkfs.ext4 /dev/sdb1"), vec![RiskKind::DiskOperation]);
+        assert_eq!(
+            kinds("newfs_hfs /dev/disk2s1"),
+            vec![RiskKind::DiskOperation]
+        );
+        assert_eq!(kinds("kill -9 1"), vec![RiskKind::KillProcesses]);
+        assert_eq!(kinds("kill -s KILL 1"), vec![RiskKind::KillProcesses]);
+        assert_eq!(kinds("killall -9 Dock"), vec![RiskKind::KillProcesses]);
+        assert_eq!(kinds("rm -f ~/Documents/*"), vec![RiskKind::FileDelete]);
+        assert_eq!(kinds("rm ~/Downloads/old.zip"), vec![RiskKind::FileDelete]);
+        assert_eq!(kinds("rm \"$HOME/notes.txt\""), vec![RiskKind::FileDelete]);
+        assert_eq!(kinds("rm *.log"), vec![RiskKind::FileDelete]);
+    }
+
+    #[test]
+    fn test_assessment_and_confirmation() {
+        let safety = assess_command("sudo pmset sleepnow");
+        assert!(safety.requires_confirmation);
+        assert!(safety.requires_admin);
+
+        let safety = assess_command("killall Finder");
+        assert!(safety.requires_confirmation);
+        assert!(!safety.requires_admin);
+
+        assert!(ensure_confirmed("echo hi", false).is_ok());
+        assert!(ensure_confirmed("killall Finder", true).is_ok());
+        let error = ensure_confirmed("killall Finder", false).unwrap_err();
+        assert!(error.contains("Terminates processes by name"));
+    }
+
+    #[test]
+    fn test_unanalyzable_commands_need_confirmation() {
+        assert_eq!(kinds("echo 'oops"), vec![RiskKind::Unanalyzable]);
+        assert!(assess_command("echo )").requires_confirmation);
+        assert!(ensure_confirmed("echo $(date", false).is_err());
+
+        // `case` inside a compound command is understood
+        assert_eq!(
+            kinds("if true; then case $x in a) rm -rf ~ ;; esac; fi"),
+            vec![RiskKind::RecursiveDelete]
+        );
+    }
+}
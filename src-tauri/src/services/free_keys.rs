@@ -0,0 +1,337 @@
+/// Free-key finder: suggests key combinations that are not bound yet
+use crate::models::{ConfigFile, KeyCombination, Severity, Shortcut};
+use crate::services::system_shortcuts::SystemShortcutDb;
+use std::collections::HashSet;
+
+/// Home row keys, the most comfortable to reach
+const HOME_ROW: [&str; 10] = ["a", "s", "d", "f", "g", "h", "j", "k", "l", ";"];
+
+/// Top letter row keys
+const TOP_ROW: [&str; 12] = ["q", "w", "e", "r", "t", "y", "u", "i", "o", "p", "[", "]"];
+
+/// Bottom letter row keys
+const BOTTOM_ROW: [&str; 10] = ["z", "x", "c", "v", "b", "n", "m", ",", ".", "/"];
+
+/// Number row keys
+const NUMBER_ROW: [&str; 12] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "0", "-", "="];
+
+/// Remaining keys worth suggesting, least ergonomic last
+const OTHER_KEYS: [&str; 20] = [
+    "'", "\\", "`", "return", "space", "tab", "left", "right", "up", "down", "f1", "f2", "f3",
+    "f4", "f5", "f6", "f7", "f8", "f9", "f10",
+];
+
+/// Request for free key suggestions
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct FreeKeyRequest {
+    /// Acceptable modifier sets, most preferred first (e.g. `[["alt"], ["alt", "shift"]]`)
+    pub modifier_sets: Vec<Vec<String>>,
+
+    /// Mode the shortcut will live in (None for the default mode, or a comma-separated list)
+    #[serde(default)]
+    pub mode: Option<String>,
+
+    /// Command the shortcut will run, used for mnemonic ranking
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Maximum number of suggestions to return
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// A suggested unused key combination
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FreeKeySuggestion {
+    pub modifiers: Vec<String>,
+    pub key: String,
+
+    /// Display form, e.g. `alt + shift - s`
+    pub combination: String,
+
+    /// Ranking score (higher is better)
+    pub score: u32,
+
+    /// Why this key ranked where it did
+    pub reasons: Vec<String>,
+}
+
+/// Suggest unused key combinations for a configuration
+///
+/// Keys already bound in any of the requested modes (including bindings with a mode
+/// list) and reserved shortcuts of `system` are skipped.
+/// Suggestions are ranked by mnemonic match with the command's app name first,
+/// then by keyboard row (home row best), then by modifier set preference.
+pub fn suggest_free_keys(
+    config: &ConfigFile,
+    system: &SystemShortcutDb,
+    request: &FreeKeyRequest,
+) -> Result<Vec<FreeKeySuggestion>, String> {
+    if request.modifier_sets.is_empty() {
+        return Err("At least one modifier set is required".to_string());
+    }
+
+    let modifier_sets = request
+        .modifier_sets
+        .iter()
+        .map(|set| KeyCombination::parse(&format!("{} - a", set.join(" + "))).map(|c| c.modifiers))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A stand-in for the new shortcut, to compare modes like conflict detection does
+    let mut target = Shortcut::new(Vec::new(), String::new(), String::new(), 0);
+    target.mode = request.mode.clone();
+
+    let bound: HashSet<KeyCombination> = config
+        .shortcuts
+        .iter()
+        .filter(|s| s.shares_mode(&target))
+        .map(KeyCombination::from_shortcut)
+        .collect();
+
+    // Informational entries (common app shortcuts) may still be overridden
+    let reserved: HashSet<KeyCombination> = system
+        .entries()
+        .filter(|(_, entry)| entry.severity != Severity::Info)
+        .map(|(combination, _)| combination.clone())
+        .collect();
+
+    let mnemonics = request
+        .command
+        .as_deref()
+        .and_then(app_name_from_command)
+        .map(|name| mnemonic_letters(&name))
+        .unwrap_or_default();
+
+    let mut suggestions = Vec::new();
+    for (set_index, modifiers) in modifier_sets.iter().enumerate() {
+        for key in candidate_keys() {
+            let combination = KeyCombination::new(modifiers, key);
+            if bound.contains(&combination) || reserved.contains(&combination) {
+                continue;
+            }
+
+            let mut score = 0;
+            let mut reasons = Vec::new();
+
+            if let Some(pos) = mnemonics.iter().position(|m| m == key) {
+                score += if pos == 0 { 100 } else { 60 };
+                reasons.push("Matches the app name".to_string());
+            }
+
+            let (row_score, row_reason) = row_rank(key);
+            score += row_score;
+            reasons.push(row_reason.to_string());
+
+            // Earlier modifier sets are preferred
+            score += 2 * (modifier_sets.len() - set_index) as u32;
+
+            suggestions.push(FreeKeySuggestion {
+                modifiers: combination.modifiers.clone(),
+                key: combination.key.clone(),
+                combination: combination.to_string(),
+                score,
+                reasons,
+            });
+        }
+    }
+
+    // Stable sort keeps candidate order for equal scores
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.score));
+
+    if let Some(limit) = request.limit {
+        suggestions.truncate(limit);
+    }
+
+    Ok(suggestions)
+}
+
+/// All keys considered by the finder, in ergonomic order
+fn candidate_keys() -> impl Iterator<Item = &'static str> {
+    HOME_ROW
+        .iter()
+        .chain(TOP_ROW.iter())
+        .chain(BOTTOM_ROW.iter())
+        .chain(NUMBER_ROW.iter())
+        .chain(OTHER_KEYS.iter())
+        .copied()
+}
+
+/// Ergonomic score for a key based on its keyboard row
+fn row_rank(key: &str) -> (u32, &'static str) {
+    if HOME_ROW.contains(&key) {
+        (30, "Home row")
+    } else if TOP_ROW.contains(&key) {
+        (20, "Top row")
+    } else if BOTTOM_ROW.contains(&key) {
+        (15, "Bottom row")
+    } else if NUMBER_ROW.contains(&key) {
+        (10, "Number row")
+    } else {
+        (0, "Other key")
+    }
+}
+
+/// Extract the application name a command launches
+///
+/// Recognizes `open -a "App Name"`; otherwise falls back to the program name.
+pub fn app_name_from_command(command: &str) -> Option<String> {
+    let command = command.trim();
+
+    if let Some(rest) = command.strip_prefix("open -a ") {
+        let rest = rest.trim_start();
+        let name = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or(""),
+            _ => rest.split_whitespace().next().unwrap_or(""),
+        };
+        return Some(name.trim_end_matches(".app").to_string()).filter(|n| !n.is_empty());
+    }
+
+    let program = command.split_whitespace().next()?;
+    let name = program.rsplit('/').next().unwrap_or(program);
+    Some(name.to_string()).filter(|n| !n.is_empty())
+}
+
+/// Mnemonic keys for an app name: the first letter, then the initials of later words
+fn mnemonic_letters(name: &str) -> Vec<String> {
+    let mut letters: Vec<String> = Vec::new();
+    for word in name.split(|c: char| c.is_whitespace() || c == '-' || c == '_') {
+        if let Some(c) = word.chars().find(|c| c.is_ascii_alphanumeric()) {
+            let letter = c.to_ascii_lowercase().to_string();
+            if !letters.contains(&letter) {
+                letters.push(letter);
+            }
+        }
+    }
+    letters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Built-in system shortcuts, unaffected by the user's overrides
+    fn embedded_system() -> SystemShortcutDb {
+        SystemShortcutDb::embedded().unwrap()
+    }
+
+    fn request(modifier_sets: &[&[&str]], command: Option<&str>) -> FreeKeyRequest {
+        FreeKeyRequest {
+            modifier_sets: modifier_sets
+                .iter()
+                .map(|set| set.iter().map(|m| m.to_string()).collect())
+                .collect(),
+            mode: None,
+            command: command.map(String::from),
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_skips_bound_and_system_keys() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(Shortcut::new(
+            vec!["cmd".to_string()],
+            "a".to_string(),
+            "echo a".to_string(),
+            1,
+        ));
+
+        let suggestions =
+            suggest_free_keys(&config, &embedded_system(), &request(&[&["cmd"]], None)).unwrap();
+
+        assert!(!suggestions.iter().any(|s| s.key == "a"));
+        // cmd - q and cmd - space are system shortcuts
+        assert!(!suggestions.iter().any(|s| s.key == "q"));
+        assert!(!suggestions.iter().any(|s| s.key == "space"));
+        // cmd - s (Save) is reserved too, so the next home row key comes first
+        assert!(!suggestions.iter().any(|s| s.key == "s"));
+        assert_eq!(suggestions[0].key, "d");
+    }
+
+    #[test]
+    fn test_bound_keys_in_other_modes_are_free() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        let mut shortcut = Shortcut::new(
+            vec!["alt".to_string()],
+            "a".to_string(),
+            "echo a".to_string(),
+            1,
+        );
+        shortcut.mode = Some("window".to_string());
+        config.add_shortcut(shortcut);
+
+        let suggestions =
+            suggest_free_keys(&config, &embedded_system(), &request(&[&["alt"]], None)).unwrap();
+        assert_eq!(suggestions[0].key, "a");
+    }
+
+    #[test]
+    fn test_bound_keys_in_mode_lists_are_taken() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        let mut shortcut = Shortcut::new(Vec::new(), "a".to_string(), "echo a".to_string(), 1);
+        shortcut.mode = Some("resize, window".to_string());
+        config.add_shortcut(shortcut);
+        config.add_shortcut(Shortcut::new(
+            Vec::new(),
+            "s".to_string(),
+            "echo s".to_string(),
+            2,
+        ));
+
+        let mut window = request(&[&[]], None);
+        window.mode = Some("window".to_string());
+        let suggestions = suggest_free_keys(&config, &embedded_system(), &window).unwrap();
+        assert!(!suggestions.iter().any(|s| s.key == "a"));
+        assert!(suggestions.iter().any(|s| s.key == "s"));
+
+        // No mode and the `default` mode are the same mode
+        let mut default = request(&[&[]], None);
+        default.mode = Some("default".to_string());
+        let suggestions = suggest_free_keys(&config, &embedded_system(), &default).unwrap();
+        assert!(!suggestions.iter().any(|s| s.key == "s"));
+        assert!(suggestions.iter().any(|s| s.key == "a"));
+    }
+
+    #[test]
+    fn test_mnemonic_ranking() {
+        let config = ConfigFile::new("/test/path".to_string());
+        let suggestions = suggest_free_keys(
+            &config,
+            &embedded_system(),
+            &request(
+                &[&["alt"], &["alt", "shift"]],
+                Some("open -a \"Google Chrome\""),
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(suggestions[0].combination, "alt - g");
+        assert_eq!(suggestions[1].combination, "alt + shift - g");
+        assert_eq!(suggestions[2].combination, "alt - c");
+    }
+
+    #[test]
+    fn test_invalid_request() {
+        let config = ConfigFile::new("/test/path".to_string());
+        assert!(suggest_free_keys(&config, &embedded_system(), &request(&[], None)).is_err());
+        assert!(
+            suggest_free_keys(&config, &embedded_system(), &request(&[&["super"]], None)).is_err()
+        );
+    }
+
+    #[test]
+    fn test_app_name_from_command() {
+        assert_eq!(
+            app_name_from_command("open -a \"Google Chrome\""),
+            Some("Google Chrome".to_string())
+        );
+        assert_eq!(
+            app_name_from_command("open -a Safari.app"),
+            Some("Safari".to_string())
+        );
+        assert_eq!(
+            app_name_from_command("/opt/homebrew/bin/yabai -m window --focus west"),
+            Some("yabai".to_string())
+        );
+    }
+}
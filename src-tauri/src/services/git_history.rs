@@ -0,0 +1,483 @@
+/// Config history kept in a git repository
+///
+/// Saves are committed to the git repository the config file is in, or, when it
+/// is in none, to a private repository under `<config dir>/git/<namespace>`
+/// holding a copy of the file. Each commit records its trigger in a
+/// `Keybinder-Trigger` trailer; commits made outside Keybinder show up as manual
+/// backups. Uses the `git` command line.
+use crate::models::{namespace_key, Backup, BackupTrigger};
+use crate::services::backup::{normalize_source_path, sha256_hex};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Directory of private repositories in the skhd config directory
+pub const GIT_DIR: &str = "git";
+
+/// Commit trailer holding the trigger of a commit
+const TRIGGER_TRAILER: &str = "Keybinder-Trigger";
+
+/// Identity of commits when git has none configured
+const FALLBACK_IDENTITY: [&str; 4] = [
+    "-c",
+    "user.name=Keybinder",
+    "-c",
+    "user.email=keybinder@localhost",
+];
+
+/// The git history of one config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHistory {
+    /// Top-level directory of the repository
+    work_tree: PathBuf,
+
+    /// Path of the tracked file within the repository, `/`-separated
+    tracked_path: String,
+
+    /// The config file
+    config_path: PathBuf,
+
+    /// Whether the repository is a private one holding a copy of the config
+    private: bool,
+}
+
+impl GitHistory {
+    /// Open the history of a config file
+    ///
+    /// With `use_config_repo`, a config inside a git work tree (and not ignored
+    /// there) is committed to that repository. Otherwise a private repository is
+    /// created in the skhd config directory `config_dir` on first use.
+    pub fn open(config_dir: &Path, config_path: &Path, use_config_repo: bool) -> io::Result<Self> {
+        let config_path = normalize_source_path(config_path);
+
+        if use_config_repo {
+            if let Some(history) = Self::open_containing(&config_path)? {
+                return Ok(history);
+            }
+        }
+
+        let work_tree = config_dir.join(GIT_DIR).join(namespace_key(&config_path));
+        if !work_tree.join(".git").exists() {
+            fs::create_dir_all(&work_tree)?;
+            run_git(&work_tree, ["init", "--quiet"])?;
+            run_git(&work_tree, ["config", "commit.gpgsign", "false"])?;
+        }
+        let tracked_path = config_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "config".to_string());
+
+        Ok(Self {
+            work_tree,
+            tracked_path,
+            config_path,
+            private: true,
+        })
+    }
+
+    /// History in the repository containing the config file, if there is one
+    fn open_containing(config_path: &Path) -> io::Result<Option<Self>> {
+        let Some(parent) = config_path.parent().filter(|p| p.is_dir()) else {
+            return Ok(None);
+        };
+        let output = git_command(parent)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let top_level = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let work_tree = fs::canonicalize(&top_level)?;
+        let Ok(relative) = config_path.strip_prefix(&work_tree) else {
+            return Ok(None);
+        };
+        let tracked_path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let ignored = git_command(&work_tree)
+            .args(["check-ignore", "--quiet", "--", &tracked_path])
+            .status()?
+            .success();
+        if ignored {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            work_tree,
+            tracked_path,
+            config_path: config_path.to_path_buf(),
+            private: false,
+        }))
+    }
+
+    /// Top-level directory of the repository
+    pub fn repository(&self) -> &Path {
+        &self.work_tree
+    }
+
+    /// The config file
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Whether the repository is a private one in the skhd config directory
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    /// Commit the config file as it is on disk
+    ///
+    /// Only the config file is committed; other changes staged in the repository
+    /// stay staged. Returns None when the file does not exist or is unchanged
+    /// since its last commit. The user's hooks run for commits to their own
+    /// repository; private repositories have none and skip them.
+    pub fn commit(&self, description: &str, trigger: BackupTrigger) -> io::Result<Option<Backup>> {
+        if self.private {
+            match fs::read(&self.config_path) {
+                Ok(content) => fs::write(self.work_tree.join(&self.tracked_path), content)?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        } else if !self.config_path.is_file() {
+            return Ok(None);
+        }
+
+        run_git(&self.work_tree, ["add", "--", &self.tracked_path])?;
+        let unchanged = git_command(&self.work_tree)
+            .args(["diff", "--cached", "--quiet", "--", &self.tracked_path])
+            .status()?
+            .success();
+        if unchanged {
+            return Ok(None);
+        }
+
+        let trailer = format!("{}: {}", TRIGGER_TRAILER, trigger_name(trigger));
+        let mut command = git_command(&self.work_tree);
+        if !self.has_identity()? {
+            command.args(FALLBACK_IDENTITY);
+        }
+        command.args(["commit", "--quiet"]);
+        if self.private {
+            command.arg("--no-verify");
+        }
+        command.args(["-m", description, "-m"]);
+        command.args([&trailer, "--only", "--", &self.tracked_path]);
+        check_output(command.output()?)?;
+
+        Ok(self.log()?.into_iter().next())
+    }
+
+    /// Commits of the config file, newest first
+    ///
+    /// Commits that deleted the file are skipped.
+    pub fn log(&self) -> io::Result<Vec<Backup>> {
+        let has_commits = git_command(&self.work_tree)
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .output()?
+            .status
+            .success();
+        if !has_commits {
+            return Ok(Vec::new());
+        }
+
+        let format = format!(
+            "--format=%H%x1f%aI%x1f%s%x1f%(trailers:key={},valueonly,separator=%x2C)%x1e",
+            TRIGGER_TRAILER
+        );
+        let log = run_git(&self.work_tree, ["log", &format, "--", &self.tracked_path])?;
+        let log = String::from_utf8_lossy(&log);
+
+        let commits: Vec<Vec<&str>> = log
+            .split('\x1e')
+            .map(|record| record.trim_matches('\n').split('\x1f').collect::<Vec<_>>())
+            .filter(|fields| fields.len() == 4)
+            .collect();
+        let contents = self.read_all(commits.iter().map(|fields| fields[0]))?;
+
+        let backups = commits
+            .iter()
+            .zip(contents)
+            .filter_map(|(fields, content)| {
+                let content = content?;
+                let created_at = chrono::DateTime::parse_from_rfc3339(fields[1])
+                    .map(|t| t.with_timezone(&chrono::Utc).to_rfc3339())
+                    .unwrap_or_else(|_| fields[1].to_string());
+
+                Some(Backup {
+                    id: fields[0].to_string(),
+                    file_path: self.work_tree.clone(),
+                    original_path: self.config_path.clone(),
+                    namespace: namespace_key(&self.config_path),
+                    created_at,
+                    checksum: sha256_hex(&content),
+                    description: Some(fields[2].to_string()),
+                    size_bytes: content.len() as u64,
+                    trigger: parse_trigger(fields[3]),
+                })
+            })
+            .collect();
+        Ok(backups)
+    }
+
+    /// Content of the config file at a commit
+    pub fn read(&self, commit: &str) -> io::Result<Vec<u8>> {
+        // IDs come from the frontend; never let one be taken for an option
+        if commit.len() < 4 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid commit ID: {}", commit),
+            ));
+        }
+
+        let object = format!("{}:{}", commit, self.tracked_path);
+        run_git(&self.work_tree, ["cat-file", "blob", &object])
+    }
+
+    /// Content of the config file at each commit (None where it does not exist)
+    fn read_all<'a>(
+        &self,
+        commits: impl Iterator<Item = &'a str>,
+    ) -> io::Result<Vec<Option<Vec<u8>>>> {
+        let requests: String = commits
+            .map(|commit| format!("{}:{}\n", commit, self.tracked_path))
+            .collect();
+        let count = requests.lines().count();
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut child = git_command(&self.work_tree)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // Feed requests on a thread so git cannot block on a full output pipe
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = std::thread::spawn(move || stdin.write_all(requests.as_bytes()));
+        let mut output = Vec::new();
+        if let Some(mut stdout) = child.stdout.take() {
+            stdout.read_to_end(&mut output)?;
+        }
+        child.wait()?;
+        let _ = writer.join();
+
+        // Each answer is `<oid> blob <size>\n<content>\n`, or `<object> missing\n`
+        let mut contents = Vec::with_capacity(count);
+        let mut rest = output.as_slice();
+        for _ in 0..count {
+            let header_end = rest.iter().position(|&b| b == b'\n').ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Truncated git cat-file output")
+            })?;
+            let header = String::from_utf8_lossy(&rest[..header_end]).to_string();
+            rest = &rest[header_end + 1..];
+
+            let fields: Vec<&str> = header.split(' ').collect();
+            match fields.as_slice() {
+                [_, "blob", size] => {
+                    let size: usize = size.parse().map_err(|_| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Invalid git cat-file output")
+                    })?;
+                    if rest.len() < size + 1 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Truncated git cat-file output",
+                        ));
+                    }
+                    contents.push(Some(rest[..size].to_vec()));
+                    rest = &rest[size + 1..];
+                }
+                _ => contents.push(None),
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Whether git has a committer identity for the repository
+    fn has_identity(&self) -> io::Result<bool> {
+        let output = git_command(&self.work_tree)
+            .args(["config", "user.email"])
+            .output()?;
+        Ok(output.status.success() && !output.stdout.trim_ascii().is_empty())
+    }
+}
+
+/// A git command in a directory, unaffected by the environment of a calling git
+fn git_command(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(dir)
+        .env_remove("GIT_DIR")
+        .env_remove("GIT_WORK_TREE")
+        .env_remove("GIT_INDEX_FILE")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null());
+    command
+}
+
+/// Run git in a directory and return its output, failing with its error message
+fn run_git<I, S>(dir: &Path, args: I) -> io::Result<Vec<u8>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = git_command(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to run git: {}", e)))?;
+    check_output(output)
+}
+
+/// Stdout of a finished git command, or its stderr as the error
+fn check_output(output: Output) -> io::Result<Vec<u8>> {
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    Err(io::Error::other(format!(
+        "git failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+/// Value of the trigger trailer of a commit
+fn trigger_name(trigger: BackupTrigger) -> String {
+    serde_json::to_value(trigger)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Trigger of a commit from its trailer (manual for commits made outside Keybinder)
+fn parse_trigger(trailer: &str) -> BackupTrigger {
+    let name = trailer.split(',').next().unwrap_or("").trim();
+    serde_json::from_value(serde_json::Value::String(name.to_string())).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    /// Run git in a test repository with a fixed identity
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = git_command(dir)
+            .args(FALLBACK_IDENTITY)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    #[test]
+    fn test_private_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join("config");
+        fs::create_dir(&config_dir).unwrap();
+        let config_path = config_dir.join("skhdrc");
+
+        let history = GitHistory::open(&config_dir, &config_path, false).unwrap();
+        assert!(history.is_private());
+        assert!(history.repository().starts_with(config_dir.join(GIT_DIR)));
+        assert!(history.log().unwrap().is_empty());
+
+        // Nothing to commit before the file exists
+        assert!(history
+            .commit("Before saving", BackupTrigger::BeforeSave)
+            .unwrap()
+            .is_none());
+
+        fs::write(&config_path, "cmd - a : echo a\n").unwrap();
+        let first = history
+            .commit("Before saving", BackupTrigger::BeforeSave)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.trigger, BackupTrigger::BeforeSave);
+        assert_eq!(first.description, Some("Before saving".to_string()));
+
+        // Unchanged content is not committed twice
+        assert!(history
+            .commit("Saved", BackupTrigger::Saved)
+            .unwrap()
+            .is_none());
+
+        fs::write(&config_path, "cmd - b : echo b\n").unwrap();
+        let second = history
+            .commit("Saved", BackupTrigger::Saved)
+            .unwrap()
+            .unwrap();
+
+        let log = history.log().unwrap();
+        assert_eq!(log, vec![second.clone(), first.clone()]);
+        assert_eq!(log[1].checksum, sha256_hex(b"cmd - a : echo a\n"));
+        assert_eq!(history.read(&first.id).unwrap(), b"cmd - a : echo a\n");
+        assert!(history.read("--output=x").is_err());
+
+        // Reopening finds the same repository
+        let reopened = GitHistory::open(&config_dir, &config_path, false).unwrap();
+        assert_eq!(reopened.log().unwrap(), log);
+    }
+
+    #[test]
+    fn test_config_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("dotfiles");
+        fs::create_dir_all(repo.join("skhd")).unwrap();
+        let config_path = repo.join("skhd").join("skhdrc");
+
+        git(&repo, &["init", "--quiet"]);
+        fs::write(&config_path, "cmd - a : echo a\n").unwrap();
+        fs::write(repo.join("README"), "dotfiles\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "--quiet", "-m", "Add dotfiles"]);
+
+        // A change staged by the user is left alone
+        fs::write(repo.join("notes"), "todo\n").unwrap();
+        git(&repo, &["add", "notes"]);
+
+        // The user's hooks run for commits to their repository
+        let hook = repo.join(".git").join("hooks").join("pre-commit");
+        let marker = temp_dir.path().join("hook-ran");
+        fs::write(&hook, format!("#!/bin/sh\ntouch '{}'\n", marker.display())).unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let history =
+            GitHistory::open(&temp_dir.path().join("config"), &config_path, true).unwrap();
+        assert!(!history.is_private());
+        assert_eq!(history.repository(), fs::canonicalize(&repo).unwrap());
+
+        fs::write(&config_path, "cmd - b : echo b\n").unwrap();
+        let saved = history
+            .commit("Saved", BackupTrigger::Saved)
+            .unwrap()
+            .unwrap();
+
+        assert!(marker.exists());
+
+        let log = history.log().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0], saved);
+        assert_eq!(log[1].trigger, BackupTrigger::Manual);
+        assert_eq!(log[1].description, Some("Add dotfiles".to_string()));
+        assert_eq!(history.read(&log[1].id).unwrap(), b"cmd - a : echo a\n");
+
+        assert_eq!(git(&repo, &["status", "--porcelain"]), "A  notes\n");
+
+        // Ignored configs get a private repository instead
+        fs::create_dir(repo.join("local")).unwrap();
+        fs::write(repo.join(".gitignore"), "local/\n").unwrap();
+        let ignored_path = repo.join("local").join("skhdrc");
+        let ignored =
+            GitHistory::open(&temp_dir.path().join("config"), &ignored_path, true).unwrap();
+        assert!(ignored.is_private());
+    }
+}
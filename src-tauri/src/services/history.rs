@@ -0,0 +1,349 @@
+/// Backup history of a config file, kept by the backup store or in git
+///
+/// `history.json` in the skhd config directory selects the backend:
+///
+/// ```json
+/// { "backend": "git", "use_config_repo": true }
+/// ```
+///
+/// Commands go through `ConfigHistory`, so listing, diffing and restoring work
+/// the same way for both. With git, a backup's ID is its commit hash.
+use crate::models::{Backup, BackupTrigger};
+use crate::services::backup::{
+    backup_before_overwrite_in, create_backup_in, list_backups_for_in, prune_backups_in,
+    read_backup, restore_backup, sha256_hex, RetentionPolicy,
+};
+use crate::services::file_io::write_config_atomic;
+use crate::services::git_history::GitHistory;
+use crate::services::settings::AppPaths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// History settings file in the skhd config directory
+pub const HISTORY_FILE: &str = "history.json";
+
+/// Where the history of config files is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryBackend {
+    /// Snapshots in the backup directory, pruned by the retention policy
+    #[default]
+    Store,
+
+    /// Commits in a git repository
+    Git,
+}
+
+/// How the history of config files is kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistorySettings {
+    #[serde(default)]
+    pub backend: HistoryBackend,
+
+    /// With git, commit to the repository the config is in (if any)
+    #[serde(default = "default_use_config_repo")]
+    pub use_config_repo: bool,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            backend: HistoryBackend::default(),
+            use_config_repo: default_use_config_repo(),
+        }
+    }
+}
+
+fn default_use_config_repo() -> bool {
+    true
+}
+
+impl HistorySettings {
+    /// Load the settings of a config directory (defaults if there are none)
+    pub fn load(config_dir: &Path) -> io::Result<Self> {
+        let path = config_dir.join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        serde_json::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid history settings {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Store the settings of a config directory
+    pub fn save(&self, config_dir: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_config_atomic(config_dir.join(HISTORY_FILE), &text)
+    }
+}
+
+/// The history of one config file
+#[derive(Debug, Clone)]
+pub enum ConfigHistory {
+    Store {
+        backup_dir: PathBuf,
+
+        /// skhd config directory, holding the retention policy
+        config_dir: PathBuf,
+        config_path: PathBuf,
+    },
+    Git(GitHistory),
+}
+
+impl ConfigHistory {
+    /// Open the history of a config file with the history settings of the config directory
+    pub fn open<P: AsRef<Path>>(paths: &AppPaths, config_path: P) -> io::Result<Self> {
+        let settings = HistorySettings::load(&paths.config_dir)?;
+        let config_path = config_path.as_ref();
+
+        match settings.backend {
+            HistoryBackend::Store => Ok(Self::Store {
+                backup_dir: paths.backup_dir.clone(),
+                config_dir: paths.config_dir.clone(),
+                config_path: config_path.to_path_buf(),
+            }),
+            HistoryBackend::Git => {
+                GitHistory::open(&paths.config_dir, config_path, settings.use_config_repo)
+                    .map(Self::Git)
+            }
+        }
+    }
+
+    /// Backups of the config file, newest first
+    pub fn list(&self) -> io::Result<Vec<Backup>> {
+        match self {
+            Self::Store {
+                backup_dir,
+                config_path,
+                ..
+            } => list_backups_for_in(backup_dir, config_path),
+            Self::Git(git) => git.log(),
+        }
+    }
+
+    /// Find a backup of the config file by its ID
+    pub fn find(&self, backup_id: &str) -> io::Result<Option<Backup>> {
+        Ok(self.list()?.into_iter().find(|b| b.id == backup_id))
+    }
+
+    /// Content of a backup, verifying its checksum
+    pub fn read(&self, backup: &Backup) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Store { .. } => read_backup(backup),
+            Self::Git(git) => {
+                let content = git.read(&backup.id)?;
+                if sha256_hex(&content) != backup.checksum {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Backup checksum mismatch - file may be corrupted",
+                    ));
+                }
+                Ok(content)
+            }
+        }
+    }
+
+    /// Back up the config file as it is now
+    ///
+    /// With git, unchanged content is not committed again; the newest commit is
+    /// returned instead.
+    pub fn create(&self, description: Option<String>) -> io::Result<Backup> {
+        match self {
+            Self::Store {
+                backup_dir,
+                config_path,
+                ..
+            } => create_backup_in(backup_dir, config_path, description, BackupTrigger::Manual),
+            Self::Git(git) => {
+                let message = description.unwrap_or_else(|| "Manual backup".to_string());
+                match git.commit(&message, BackupTrigger::Manual)? {
+                    Some(backup) => Ok(backup),
+                    None => git.log()?.into_iter().next().ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::NotFound, "Config file does not exist")
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Back up the config file before it is overwritten (see `backup_before_overwrite`)
+    ///
+    /// In git, the file is committed as it is on disk, so edits made outside the app
+    /// and not committed yet go into this commit. Commits to the config's own
+    /// repository run the user's hooks; when a hook or the commit fails, the failure
+    /// is logged and nothing is backed up rather than blocking the write.
+    pub fn before_overwrite(&self, trigger: BackupTrigger) -> io::Result<Option<Backup>> {
+        match self {
+            Self::Store {
+                backup_dir,
+                config_path,
+                ..
+            } => backup_before_overwrite_in(backup_dir, config_path, trigger),
+            Self::Git(git) => {
+                let description = match trigger {
+                    BackupTrigger::BeforeRestore => "Before restoring a backup",
+                    _ => "Before saving",
+                };
+                match git.commit(description, trigger) {
+                    Err(e) if !git.is_private() => {
+                        eprintln!(
+                            "Failed to commit config to {}: {}",
+                            git.repository().display(),
+                            e
+                        );
+                        Ok(None)
+                    }
+                    result => result,
+                }
+            }
+        }
+    }
+
+    /// Record that the config file was written
+    ///
+    /// The store applies its retention policy; git commits the new content.
+    pub fn after_write(&self, description: &str) -> io::Result<()> {
+        match self {
+            Self::Store {
+                backup_dir,
+                config_dir,
+                ..
+            } => {
+                let policy = RetentionPolicy::load(config_dir)?;
+                prune_backups_in(backup_dir, &policy).map(|_| ())
+            }
+            Self::Git(git) => git.commit(description, BackupTrigger::Saved).map(|_| ()),
+        }
+    }
+
+    /// Restore a backup over the config file
+    pub fn restore(&self, backup: &Backup) -> io::Result<()> {
+        match self {
+            Self::Store { config_path, .. } => restore_backup(backup, Some(config_path)),
+            Self::Git(git) => {
+                let content = self.read(backup)?;
+                write_config_atomic(git.config_path(), &String::from_utf8_lossy(&content)).map_err(
+                    |e| io::Error::new(e.kind(), format!("Failed to restore backup: {}", e)),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_git_backend_records_saves() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = AppPaths::new(
+            temp_dir.path().join("config"),
+            temp_dir.path().join("backups"),
+        );
+        let config_path = temp_dir.path().join("skhdrc");
+        fs::write(&config_path, "cmd - a : echo a\n").unwrap();
+
+        // The store is the default
+        let store = ConfigHistory::open(&paths, &config_path).unwrap();
+        assert!(matches!(store, ConfigHistory::Store { .. }));
+
+        let settings = HistorySettings {
+            backend: HistoryBackend::Git,
+            use_config_repo: false,
+        };
+        settings.save(&paths.config_dir).unwrap();
+        assert_eq!(HistorySettings::load(&paths.config_dir).unwrap(), settings);
+        let history = ConfigHistory::open(&paths, &config_path).unwrap();
+        assert!(matches!(history, ConfigHistory::Git(_)));
+
+        // Settings and the private repository do not live in the backup directory
+        assert!(!paths.backup_dir.exists());
+
+        // A save commits the old and the new content
+        let before = history
+            .before_overwrite(BackupTrigger::BeforeSave)
+            .unwrap()
+            .unwrap();
+        fs::write(&config_path, "cmd - b : echo b\n").unwrap();
+        history.after_write("Saved").unwrap();
+
+        let backups = history.list().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].trigger, BackupTrigger::Saved);
+        assert_eq!(backups[1], before);
+
+        // Manual backups of unchanged content return the newest commit
+        assert_eq!(history.create(None).unwrap(), backups[0]);
+
+        // Checking out the first commit restores its content
+        history.restore(&before).unwrap();
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "cmd - a : echo a\n"
+        );
+        assert_eq!(history.find(&before.id).unwrap(), Some(before.clone()));
+
+        let mut corrupted = before;
+        corrupted.checksum = sha256_hex(b"something else");
+        assert!(history.read(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_failing_hooks_do_not_block_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = AppPaths::new(
+            temp_dir.path().join("config"),
+            temp_dir.path().join("backups"),
+        );
+        let repo = temp_dir.path().join("dotfiles");
+        fs::create_dir_all(&repo).unwrap();
+        let config_path = repo.join("skhdrc");
+        fs::write(&config_path, "cmd - a : echo a\n").unwrap();
+
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(["-c", "user.name=Test", "-c", "user.email=test@localhost"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "skhdrc"]);
+        git(&["commit", "--quiet", "-m", "Add skhdrc"]);
+
+        let hook = repo.join(".git").join("hooks").join("pre-commit");
+        fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+        HistorySettings {
+            backend: HistoryBackend::Git,
+            use_config_repo: true,
+        }
+        .save(&paths.config_dir)
+        .unwrap();
+        let history = ConfigHistory::open(&paths, &config_path).unwrap();
+
+        // The rejected commit is logged, not returned as an error
+        fs::write(&config_path, "cmd - b : echo b\n").unwrap();
+        assert_eq!(
+            history.before_overwrite(BackupTrigger::BeforeSave).unwrap(),
+            None
+        );
+        assert_eq!(history.list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+/// Key combination index for fast "what is bound to X?" lookups
+use crate::models::{ConfigFile, KeyCombination, Shortcut};
+use std::collections::HashMap;
+
+/// A binding that differs from the queried chord by one modifier
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct NearMatch {
+    pub shortcut: Shortcut,
+
+    /// Modifier the binding has but the query does not
+    pub extra_modifier: Option<String>,
+
+    /// Modifier the query has but the binding does not
+    pub missing_modifier: Option<String>,
+}
+
+/// Result of a key combination query
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KeyQueryResult {
+    /// Normalized form of the queried chord
+    pub combination: KeyCombination,
+
+    /// Bindings for exactly this chord, across all modes
+    pub exact: Vec<Shortcut>,
+
+    /// Bindings for the same key differing by a single modifier
+    pub near: Vec<NearMatch>,
+}
+
+/// Index from normalized key combination to shortcut IDs
+#[derive(Debug, Clone, Default)]
+pub struct KeyIndex {
+    entries: HashMap<KeyCombination, Vec<String>>,
+}
+
+impl KeyIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index over every shortcut in a configuration
+    pub fn build(config: &ConfigFile) -> Self {
+        let mut index = Self::new();
+        for shortcut in &config.shortcuts {
+            index.insert(shortcut);
+        }
+        index
+    }
+
+    /// Add a shortcut to the index
+    pub fn insert(&mut self, shortcut: &Shortcut) {
+        self.entries
+            .entry(KeyCombination::from_shortcut(shortcut))
+            .or_default()
+            .push(shortcut.id.clone());
+    }
+
+    /// Remove a shortcut from the index (using its current key combination)
+    pub fn remove(&mut self, shortcut: &Shortcut) {
+        let combination = KeyCombination::from_shortcut(shortcut);
+        if let Some(ids) = self.entries.get_mut(&combination) {
+            ids.retain(|id| id != &shortcut.id);
+            if ids.is_empty() {
+                self.entries.remove(&combination);
+            }
+        }
+    }
+
+    /// IDs of shortcuts bound to a combination
+    pub fn ids_for(&self, combination: &KeyCombination) -> &[String] {
+        self.entries
+            .get(combination)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Check whether any shortcut is bound to a combination
+    pub fn contains(&self, combination: &KeyCombination) -> bool {
+        self.entries.contains_key(combination)
+    }
+
+    /// Look up exact and near matches for a combination
+    ///
+    /// IDs are resolved against `config`, which must be the configuration the index
+    /// was built from.
+    pub fn query(&self, combination: &KeyCombination, config: &ConfigFile) -> KeyQueryResult {
+        let resolve = |ids: &[String]| -> Vec<Shortcut> {
+            ids.iter()
+                .filter_map(|id| config.find_shortcut(id))
+                .cloned()
+                .collect()
+        };
+
+        let mut exact = resolve(self.ids_for(combination));
+        exact.sort_by_key(|s| s.line_number);
+
+        let mut near = Vec::new();
+        for neighbor in combination.neighbors() {
+            let difference = combination.modifier_difference(&neighbor);
+            let modifier = difference.into_iter().next();
+            let (extra_modifier, missing_modifier) =
+                if neighbor.modifiers.len() > combination.modifiers.len() {
+                    (modifier, None)
+                } else {
+                    (None, modifier)
+                };
+
+            for shortcut in resolve(self.ids_for(&neighbor)) {
+                near.push(NearMatch {
+                    shortcut,
+                    extra_modifier: extra_modifier.clone(),
+                    missing_modifier: missing_modifier.clone(),
+                });
+            }
+        }
+        near.sort_by_key(|m| m.shortcut.line_number);
+
+        KeyQueryResult {
+            combination: combination.clone(),
+            exact,
+            near,
+        }
+    }
+}
+
+/// Find every binding for a chord in a configuration
+///
+/// Accepts modifiers in any order and the `hyper`/`meh` aliases. Builds a
+/// throwaway index; the loaded config's index is kept by `ValidationCache`.
+pub fn find_bindings(config: &ConfigFile, chord: &str) -> Result<KeyQueryResult, String> {
+    let combination = KeyCombination::parse(chord)?;
+    Ok(KeyIndex::build(config).query(&combination, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcut(modifiers: &[&str], key: &str, mode: Option<&str>, line: usize) -> Shortcut {
+        let mut s = Shortcut::new(
+            modifiers.iter().map(|m| m.to_string()).collect(),
+            key.to_string(),
+            format!("echo {}", line),
+            line,
+        );
+        s.mode = mode.map(String::from);
+        s
+    }
+
+    #[test]
+    fn test_find_bindings_across_modes() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(shortcut(&["cmd", "shift"], "f", None, 1));
+        config.add_shortcut(shortcut(&["shift", "cmd"], "f", Some("window"), 2));
+        config.add_shortcut(shortcut(&["cmd"], "f", None, 3));
+        config.add_shortcut(shortcut(&["cmd", "shift", "alt"], "f", None, 4));
+        config.add_shortcut(shortcut(&["ctrl"], "f", None, 5));
+
+        let result = find_bindings(&config, "shift + cmd - f").unwrap();
+
+        assert_eq!(result.exact.len(), 2);
+        assert_eq!(result.exact[1].mode, Some("window".to_string()));
+
+        assert_eq!(result.near.len(), 2);
+        assert_eq!(result.near[0].shortcut.line_number, 3);
+        assert_eq!(result.near[0].missing_modifier, Some("shift".to_string()));
+        assert_eq!(result.near[1].shortcut.line_number, 4);
+        assert_eq!(result.near[1].extra_modifier, Some("alt".to_string()));
+    }
+
+    #[test]
+    fn test_find_bindings_hyper_alias() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(shortcut(&["cmd", "alt", "shift", "ctrl"], "a", None, 1));
+
+        let result = find_bindings(&config, "hyper - a").unwrap();
+        assert_eq!(result.exact.len(), 1);
+
+        let result = find_bindings(&config, "meh - a").unwrap();
+        assert!(result.exact.is_empty());
+        assert_eq!(result.near.len(), 1);
+        assert_eq!(result.near[0].extra_modifier, Some("cmd".to_string()));
+    }
+
+    #[test]
+    fn test_find_bindings_keeps_sides_distinct() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(shortcut(&["lcmd"], "f", None, 1));
+        config.add_shortcut(shortcut(&["rcmd"], "f", None, 2));
+        config.add_shortcut(shortcut(&["cmd"], "f", None, 3));
+
+        let result = find_bindings(&config, "lcmd - f").unwrap();
+        assert_eq!(result.exact.len(), 1);
+        assert_eq!(result.exact[0].line_number, 1);
+
+        let result = find_bindings(&config, "cmd - f").unwrap();
+        assert_eq!(result.exact.len(), 1);
+        assert_eq!(result.exact[0].line_number, 3);
+    }
+
+    #[test]
+    fn test_index_insert_and_remove() {
+        let s = shortcut(&["cmd"], "t", None, 1);
+        let combination = KeyCombination::from_shortcut(&s);
+
+        let mut index = KeyIndex::new();
+        index.insert(&s);
+        assert_eq!(index.ids_for(&combination), std::slice::from_ref(&s.id));
+
+        index.remove(&s);
+        assert!(!index.contains(&combination));
+    }
+}
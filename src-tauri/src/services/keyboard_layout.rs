@@ -0,0 +1,322 @@
+/// Keyboard layout tables and layout-aware key checks
+///
+/// skhd resolves character keys through the active keyboard layout, so a binding
+/// such as `cmd - [` lands on a different physical key (or none at all) depending
+/// on the layout. Layout tables live in `data/keyboard_layouts.json`.
+use crate::models::{ConfigFile, KeyboardLayout, PhysicalKey};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Named keys that skhd maps to fixed key codes regardless of layout
+const NAMED_KEYS: [&str; 47] = [
+    "return",
+    "tab",
+    "space",
+    "backspace",
+    "delete",
+    "escape",
+    "home",
+    "end",
+    "pageup",
+    "pagedown",
+    "insert",
+    "left",
+    "right",
+    "up",
+    "down",
+    "f1",
+    "f2",
+    "f3",
+    "f4",
+    "f5",
+    "f6",
+    "f7",
+    "f8",
+    "f9",
+    "f10",
+    "f11",
+    "f12",
+    "f13",
+    "f14",
+    "f15",
+    "f16",
+    "f17",
+    "f18",
+    "f19",
+    "f20",
+    "sound_up",
+    "sound_down",
+    "mute",
+    "brightness_up",
+    "brightness_down",
+    "illumination_up",
+    "illumination_down",
+    "play",
+    "previous",
+    "next",
+    "rewind",
+    "fast",
+];
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct LayoutData {
+    pub physical_keys: Vec<PhysicalKey>,
+    pub layouts: Vec<KeyboardLayout>,
+}
+
+/// What a key means on one layout
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct KeyLayoutMeaning {
+    pub layout_id: String,
+    pub layout_name: String,
+
+    /// Physical key that types the character on this layout (None if it cannot be typed)
+    pub physical_key: Option<String>,
+
+    /// Character at the physical position the key occupies on the active layout
+    pub character_at_same_position: Option<String>,
+}
+
+/// Description of a binding's key across all known layouts
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct KeyDescription {
+    pub key: String,
+
+    /// True for named keys (`return`, `f1`, ...) and hex key codes, which do not depend on layout
+    pub layout_independent: bool,
+
+    /// Physical key on the active layout
+    pub physical_key: Option<String>,
+
+    /// Per-layout meaning, in data file order
+    pub meanings: Vec<KeyLayoutMeaning>,
+}
+
+/// A shortcut whose key cannot be typed on a layout
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LayoutWarning {
+    pub shortcut_id: String,
+    pub line_number: usize,
+    pub key: String,
+    pub message: String,
+}
+
+/// Parse and sanity-check layout data
+pub(crate) fn parse_layout_data(json_data: &str) -> Result<LayoutData, String> {
+    let data: LayoutData = serde_json::from_str(json_data)
+        .map_err(|e| format!("Failed to parse keyboard layouts: {}", e))?;
+
+    if data.layouts.is_empty() {
+        return Err("Keyboard layout data contains no layouts".to_string());
+    }
+
+    let codes: HashSet<&str> = data.physical_keys.iter().map(|k| k.code.as_str()).collect();
+    for layout in &data.layouts {
+        let mut seen = HashSet::new();
+        for (code, character) in &layout.keys {
+            if !codes.contains(code.as_str()) {
+                return Err(format!(
+                    "Layout '{}' uses unknown physical key '{}'",
+                    layout.id, code
+                ));
+            }
+            if !seen.insert(character.to_lowercase()) {
+                return Err(format!(
+                    "Layout '{}' maps '{}' to more than one key",
+                    layout.id, character
+                ));
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Load the embedded layout tables
+fn load_layout_data() -> Result<LayoutData, String> {
+    parse_layout_data(include_str!("../data/keyboard_layouts.json"))
+}
+
+/// Get all known keyboard layouts
+pub fn get_layouts() -> Result<Vec<KeyboardLayout>, String> {
+    Ok(load_layout_data()?.layouts)
+}
+
+/// Get all physical key positions, in keyboard order
+pub fn get_physical_keys() -> Result<Vec<PhysicalKey>, String> {
+    Ok(load_layout_data()?.physical_keys)
+}
+
+/// Check whether a key is bound by key code rather than by character
+pub fn is_layout_independent(key: &str) -> bool {
+    let key = key.trim().to_lowercase();
+    NAMED_KEYS.contains(&key.as_str()) || key.starts_with("0x")
+}
+
+/// Describe what a key means on every layout
+///
+/// The physical position is taken from `active_layout_id`, the layout skhd runs with.
+pub fn describe_key(
+    key: &str,
+    active_layout_id: &str,
+    layouts: &[KeyboardLayout],
+) -> Result<KeyDescription, String> {
+    let active = find_layout(active_layout_id, layouts)?;
+
+    if is_layout_independent(key) {
+        return Ok(KeyDescription {
+            key: key.to_string(),
+            layout_independent: true,
+            physical_key: None,
+            meanings: Vec::new(),
+        });
+    }
+
+    let physical_key = active.physical_key_for(key).map(String::from);
+    let meanings = layouts
+        .iter()
+        .map(|layout| KeyLayoutMeaning {
+            layout_id: layout.id.clone(),
+            layout_name: layout.name.clone(),
+            physical_key: layout.physical_key_for(key).map(String::from),
+            character_at_same_position: physical_key
+                .as_deref()
+                .and_then(|code| layout.character_at(code))
+                .map(String::from),
+        })
+        .collect();
+
+    Ok(KeyDescription {
+        key: key.to_string(),
+        layout_independent: false,
+        physical_key,
+        meanings,
+    })
+}
+
+/// Find shortcuts whose character key does not exist on a layout
+pub fn check_layout(
+    config: &ConfigFile,
+    layout_id: &str,
+    layouts: &[KeyboardLayout],
+) -> Result<Vec<LayoutWarning>, String> {
+    let layout = find_layout(layout_id, layouts)?;
+
+    Ok(config
+        .shortcuts
+        .iter()
+        .filter(|s| !is_layout_independent(&s.key) && !layout.has_character(&s.key))
+        .map(|s| LayoutWarning {
+            shortcut_id: s.id.clone(),
+            line_number: s.line_number,
+            key: s.key.clone(),
+            message: format!(
+                "Line {}: key '{}' does not exist on the {} layout",
+                s.line_number, s.key, layout.name
+            ),
+        })
+        .collect())
+}
+
+fn find_layout<'a>(id: &str, layouts: &'a [KeyboardLayout]) -> Result<&'a KeyboardLayout, String> {
+    layouts
+        .iter()
+        .find(|l| l.id == id)
+        .ok_or_else(|| format!("Unknown keyboard layout: {}", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Shortcut;
+
+    fn meaning<'a>(description: &'a KeyDescription, layout_id: &str) -> &'a KeyLayoutMeaning {
+        description
+            .meanings
+            .iter()
+            .find(|m| m.layout_id == layout_id)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_load_embedded_layouts() {
+        let layouts = get_layouts().unwrap();
+        for id in ["us", "iso", "de", "fr"] {
+            assert!(layouts.iter().any(|l| l.id == id), "Missing layout {}", id);
+        }
+        assert!(!get_physical_keys().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_describe_key_across_layouts() {
+        let layouts = get_layouts().unwrap();
+        let description = describe_key("[", "us", &layouts).unwrap();
+
+        assert_eq!(description.physical_key, Some("BracketLeft".to_string()));
+        assert_eq!(meaning(&description, "de").physical_key, None);
+        assert_eq!(
+            meaning(&description, "de").character_at_same_position,
+            Some("ü".to_string())
+        );
+
+        let description = describe_key("z", "de", &layouts).unwrap();
+        assert_eq!(description.physical_key, Some("KeyY".to_string()));
+        assert_eq!(
+            meaning(&description, "fr").physical_key,
+            Some("KeyW".to_string())
+        );
+
+        let description = describe_key("`", "us", &layouts).unwrap();
+        assert_eq!(
+            meaning(&description, "iso").physical_key,
+            Some("IntlBackslash".to_string())
+        );
+
+        assert!(
+            describe_key("return", "fr", &layouts)
+                .unwrap()
+                .layout_independent
+        );
+        assert!(describe_key("a", "dvorak", &layouts).is_err());
+    }
+
+    #[test]
+    fn test_check_layout_warnings() {
+        let layouts = get_layouts().unwrap();
+        let mut config = ConfigFile::new("/test/path".to_string());
+        for (line, key) in [";", "a", "f1", "[", "sound_up", "brightness_down", "play"]
+            .iter()
+            .enumerate()
+        {
+            config.add_shortcut(Shortcut::new(
+                vec!["alt".to_string()],
+                key.to_string(),
+                "echo".to_string(),
+                line + 1,
+            ));
+        }
+
+        assert!(check_layout(&config, "us", &layouts).unwrap().is_empty());
+
+        let warnings = check_layout(&config, "de", &layouts).unwrap();
+        let keys: Vec<&str> = warnings.iter().map(|w| w.key.as_str()).collect();
+        assert_eq!(keys, vec![";", "["]);
+        assert!(warnings[0].message.contains("German"));
+    }
+
+    #[test]
+    fn test_parse_layout_data_rejects_unknown_keys() {
+        let json = r#"{
+            "physical_keys": [{ "code": "KeyA", "row": "home" }],
+            "layouts": [{ "id": "x", "name": "X", "keyboard": "ansi", "keys": { "KeyB": "b" } }]
+        }"#;
+        assert!(parse_layout_data(json).is_err());
+
+        let json = r#"{
+            "physical_keys": [{ "code": "KeyA", "row": "home" }],
+            "layouts": [{ "id": "x", "name": "X", "keyboard": "ansi", "keys": { "KeyA": "q" } }]
+        }"#;
+        let data = parse_layout_data(json).unwrap();
+        assert_eq!(data.layouts[0].physical_key_for("q"), Some("KeyA"));
+    }
+}
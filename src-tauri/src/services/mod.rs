@@ -1,20 +1,52 @@
 /// Service modules
 pub mod app_discovery;
 pub mod backup;
+pub mod backup_archive;
+pub mod backup_audit;
+pub mod backup_diff;
+pub mod command_analysis;
+pub mod command_safety;
 pub mod file_io;
+pub mod free_keys;
+pub mod git_history;
+pub mod history;
+pub mod key_index;
+pub mod keyboard_layout;
 pub mod log_tailer;
+pub mod mode_graph;
+pub mod partial_restore;
 pub mod path_validator;
 pub mod service_manager;
+pub mod settings;
+pub mod shell_lexer;
+pub mod shell_syntax;
+pub mod skhd_check;
+pub mod system_shortcuts;
 pub mod template_loader;
 pub mod theme_monitor;
 pub mod validation;
+pub mod validation_cache;
 
-pub use app_discovery::{discover_applications, parse_app_bundle};
+pub use app_discovery::{discover_applications, discover_applications_in, parse_app_bundle};
 pub use backup::{create_backup, list_backups, restore_backup};
+pub use backup_diff::ConfigDiff;
+pub use command_analysis::{analyze_command, CommandEnvironment};
+pub use command_safety::{assess_command, ensure_confirmed, CommandSafety};
 pub use file_io::{read_config_safe, write_config_atomic};
+pub use free_keys::suggest_free_keys;
+pub use history::{ConfigHistory, HistoryBackend, HistorySettings};
+pub use key_index::{find_bindings, KeyIndex};
+pub use keyboard_layout::{check_layout, describe_key, get_layouts};
 pub use log_tailer::{parse_log_line, LogTailer};
+pub use mode_graph::ModeGraph;
+pub use partial_restore::{restore_shortcuts, ConflictResolution};
 pub use path_validator::{detect_interpreter, escape_shell_path, validate_file_executable};
 pub use service_manager::ServiceManager;
+pub use settings::{AppPaths, AppSettings};
+pub use shell_syntax::{check_command_syntax, check_config_syntax};
+pub use skhd_check::{ParserBackend, SkhdCheck};
+pub use system_shortcuts::{load_system_shortcuts, SystemShortcutDb};
 pub use template_loader::{get_categories, get_templates};
 pub use theme_monitor::ThemeMonitorState;
-pub use validation::{validate_config, validate_shortcut};
+pub use validation::{validate_config, validate_shortcut, LintRule, Linter};
+pub use validation_cache::{ValidationCache, ValidationDelta};
@@ -0,0 +1,166 @@
+/// Mode transition graph for modal configs
+///
+/// skhd only applies the bindings of the active mode, so a mode is left through a
+/// `; <mode>` binding declared in that mode. Modes nothing activates are dead code,
+/// and a mode with no path back to `default` locks the keyboard into it.
+use crate::models::{ConfigFile, DEFAULT_MODE};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// A `; <mode>` binding switching from one mode to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeTransition {
+    pub from: String,
+    pub to: String,
+    pub shortcut_id: String,
+    pub line_number: usize,
+}
+
+/// Modes and the transitions between them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModeGraph {
+    /// Declared modes plus `default`, sorted
+    pub modes: Vec<String>,
+    pub transitions: Vec<ModeTransition>,
+}
+
+impl ModeGraph {
+    /// Build the graph of a config
+    ///
+    /// A binding in several modes (`a, b < ...`) adds one transition per mode.
+    pub fn build(config: &ConfigFile) -> Self {
+        let modes: BTreeSet<String> = config
+            .modes
+            .iter()
+            .map(|m| m.name.clone())
+            .chain(std::iter::once(DEFAULT_MODE.to_string()))
+            .collect();
+
+        let transitions = config
+            .shortcuts
+            .iter()
+            .filter_map(|s| s.activates.as_ref().map(|target| (s, target)))
+            .flat_map(|(shortcut, target)| {
+                shortcut
+                    .modes()
+                    .into_iter()
+                    .map(move |from| ModeTransition {
+                        from: from.to_string(),
+                        to: target.clone(),
+                        shortcut_id: shortcut.id.clone(),
+                        line_number: shortcut.line_number,
+                    })
+            })
+            .collect();
+
+        Self {
+            modes: modes.into_iter().collect(),
+            transitions,
+        }
+    }
+
+    /// Whether the config uses modes at all
+    pub fn is_modal(&self) -> bool {
+        self.modes.len() > 1 || !self.transitions.is_empty()
+    }
+
+    /// Modes reachable from `start` (including `start`)
+    pub fn reachable_from(&self, start: &str) -> BTreeSet<String> {
+        let mut edges: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for transition in &self.transitions {
+            edges
+                .entry(transition.from.as_str())
+                .or_default()
+                .push(transition.to.as_str());
+        }
+
+        let mut reached = BTreeSet::from([start.to_string()]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(mode) = queue.pop_front() {
+            for &next in edges.get(mode).into_iter().flatten() {
+                if reached.insert(next.to_string()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Declared modes that no chain of bindings from `default` activates
+    pub fn unreachable_modes(&self) -> Vec<&str> {
+        let reachable = self.reachable_from(DEFAULT_MODE);
+        self.modes
+            .iter()
+            .filter(|m| !reachable.contains(*m))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Reachable modes with no chain of bindings back to `default`
+    pub fn modes_without_exit(&self) -> Vec<&str> {
+        self.reachable_from(DEFAULT_MODE)
+            .into_iter()
+            .filter(|m| !self.reachable_from(m).contains(DEFAULT_MODE))
+            .filter_map(|m| self.modes.iter().find(|known| **known == m))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ModeDeclaration, Shortcut};
+
+    fn config(modes: &[&str], bindings: &[(Option<&str>, &str)]) -> ConfigFile {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        for (i, name) in modes.iter().enumerate() {
+            config.modes.push(ModeDeclaration {
+                name: name.to_string(),
+                capture: false,
+                command: None,
+                line_number: i + 1,
+            });
+        }
+        for (i, (mode, target)) in bindings.iter().enumerate() {
+            let mut shortcut = Shortcut::new(
+                vec!["alt".to_string()],
+                "w".to_string(),
+                String::new(),
+                modes.len() + i + 1,
+            );
+            shortcut.mode = mode.map(String::from);
+            shortcut.activates = Some(target.to_string());
+            config.add_shortcut(shortcut);
+        }
+        config
+    }
+
+    #[test]
+    fn test_reachability() {
+        let config = config(
+            &["window", "resize", "orphan", "trap"],
+            &[
+                (None, "window"),
+                (Some("window"), "resize"),
+                (Some("resize, window"), "default"),
+                (Some("window"), "trap"),
+                (Some("orphan"), "default"),
+            ],
+        );
+        let graph = ModeGraph::build(&config);
+
+        assert!(graph.is_modal());
+        assert_eq!(graph.transitions.len(), 6);
+        assert_eq!(graph.unreachable_modes(), vec!["orphan"]);
+        assert_eq!(graph.modes_without_exit(), vec!["trap"]);
+    }
+
+    #[test]
+    fn test_non_modal_config() {
+        let graph = ModeGraph::build(&ConfigFile::new("/test/path".to_string()));
+        assert!(!graph.is_modal());
+        assert!(graph.unreachable_modes().is_empty());
+        assert!(graph.modes_without_exit().is_empty());
+    }
+}
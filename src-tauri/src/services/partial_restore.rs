@@ -0,0 +1,311 @@
+/// Restore selected bindings of a backup into the current configuration
+///
+/// Backup bindings are selected by their line number in the backup, which stays
+/// the same between browsing and restoring (shortcut IDs are regenerated on
+/// every parse). A selected binding whose key combination is already used in a
+/// mode it is bound in is skipped, replaces the current binding, or is added next
+/// to it. Combinations are compared normalized, so `hyper - h` conflicts with
+/// `cmd + alt + shift + ctrl - h`.
+use crate::models::{ConfigFile, ModeDeclaration, Shortcut};
+use crate::services::backup_diff::changed_fields;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// What to do with a restored binding whose key combination is already used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the current binding
+    #[default]
+    Skip,
+
+    /// Overwrite the current binding with the backup's
+    Replace,
+
+    /// Add the backup's binding as well (a duplicate skhd ignores)
+    KeepBoth,
+}
+
+/// A binding of a backup, compared with the current configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupShortcut {
+    /// Binding as in the backup (`line_number` selects it for restoring)
+    pub shortcut: Shortcut,
+
+    /// Current binding using the same key combination in a common mode
+    pub conflict: Option<Shortcut>,
+
+    /// Whether the conflicting binding is identical, so restoring changes nothing
+    pub identical: bool,
+}
+
+/// Outcome of a partial restore
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShortcutRestoreResult {
+    /// Bindings added to the configuration
+    pub added: Vec<Shortcut>,
+
+    /// Current bindings overwritten, as they are after the restore
+    pub replaced: Vec<Shortcut>,
+
+    /// Selected backup bindings left out (conflicts resolved with `skip`, identical ones)
+    pub skipped: Vec<Shortcut>,
+
+    /// Mode declarations copied from the backup for restored bindings
+    pub modes_added: Vec<String>,
+}
+
+/// Compare every binding of a backup with the current configuration
+pub fn compare_shortcuts(backup: &ConfigFile, current: &ConfigFile) -> Vec<BackupShortcut> {
+    backup
+        .shortcuts
+        .iter()
+        .map(|shortcut| {
+            let conflict = find_conflict(current, shortcut);
+            let identical = conflict
+                .as_ref()
+                .is_some_and(|c| changed_fields(c, shortcut).is_empty());
+
+            BackupShortcut {
+                shortcut: shortcut.clone(),
+                conflict,
+                identical,
+            }
+        })
+        .collect()
+}
+
+/// Restore the bindings at `line_numbers` of `backup` into `current`
+///
+/// `resolve` decides, per backup line, what happens on a conflict. Every selected
+/// binding is checked first, so nothing changes when one is missing or invalid.
+pub fn restore_shortcuts(
+    current: &mut ConfigFile,
+    backup: &ConfigFile,
+    line_numbers: &[usize],
+    resolve: impl Fn(usize) -> ConflictResolution,
+) -> Result<ShortcutRestoreResult, String> {
+    let mut selected = Vec::new();
+    for &line in line_numbers {
+        let shortcut = backup
+            .shortcuts
+            .iter()
+            .find(|s| s.line_number == line)
+            .ok_or_else(|| format!("No binding at line {} of the backup", line))?;
+        shortcut
+            .validate()
+            .map_err(|e| format!("Invalid binding at line {} of the backup: {}", line, e))?;
+        selected.push(shortcut);
+    }
+
+    let mut result = ShortcutRestoreResult::default();
+    let mut next_line = next_free_line(current);
+
+    // Declare modes the restored bindings use or activate before the bindings
+    for shortcut in &selected {
+        let used = shortcut
+            .modes()
+            .into_iter()
+            .chain(shortcut.activates.as_deref());
+        for name in used {
+            if current.has_mode(name) {
+                continue;
+            }
+            if let Some(declaration) = backup.find_mode(name) {
+                current.modes.push(ModeDeclaration {
+                    line_number: next_line,
+                    ..declaration.clone()
+                });
+                next_line += 1;
+                result.modes_added.push(name.to_string());
+            }
+        }
+    }
+
+    for shortcut in selected {
+        let conflict = find_conflict(current, shortcut);
+        let resolution = match &conflict {
+            Some(existing) if changed_fields(existing, shortcut).is_empty() => {
+                ConflictResolution::Skip
+            }
+            Some(_) => resolve(shortcut.line_number),
+            None => ConflictResolution::KeepBoth,
+        };
+
+        match (resolution, conflict) {
+            (ConflictResolution::Skip, _) => result.skipped.push(shortcut.clone()),
+            (ConflictResolution::Replace, Some(existing)) => {
+                let replacement = Shortcut {
+                    id: existing.id.clone(),
+                    line_number: existing.line_number,
+                    ..shortcut.clone()
+                };
+                current.update_shortcut(replacement.clone());
+                result.replaced.push(replacement);
+            }
+            _ => {
+                let added = Shortcut {
+                    id: Uuid::new_v4().to_string(),
+                    line_number: next_line,
+                    ..shortcut.clone()
+                };
+                next_line += 1;
+                current.add_shortcut(added.clone());
+                result.added.push(added);
+            }
+        }
+    }
+
+    if !result.added.is_empty() || !result.replaced.is_empty() || !result.modes_added.is_empty() {
+        current.is_modified = true;
+    }
+    Ok(result)
+}
+
+/// Current binding reached by the same normalized key combination in a common mode
+fn find_conflict(current: &ConfigFile, shortcut: &Shortcut) -> Option<Shortcut> {
+    current
+        .find_duplicates(shortcut)
+        .first()
+        .map(|c| (*c).clone())
+}
+
+/// First line after every shortcut and mode declaration
+fn next_free_line(config: &ConfigFile) -> usize {
+    let shortcuts = config.shortcuts.iter().map(|s| s.line_number);
+    let modes = config.modes.iter().map(|m| m.line_number);
+    shortcuts.chain(modes).max().map_or(1, |line| line + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_config;
+
+    fn config(text: &str) -> ConfigFile {
+        parse_config(text)
+            .unwrap()
+            .to_config_file("/test/skhdrc".to_string())
+    }
+
+    const BACKUP: &str = "\
+:: window
+cmd - a : echo old-a
+cmd - b : echo b
+cmd - c : echo c
+alt - w ; window
+window < - escape ; default
+";
+
+    #[test]
+    fn test_compare_shortcuts() {
+        let backup = config(BACKUP);
+        let current = config("cmd - a : echo new-a\ncmd - b : echo b\n");
+
+        let compared = compare_shortcuts(&backup, &current);
+        let summary: Vec<(usize, bool, bool)> = compared
+            .iter()
+            .map(|c| (c.shortcut.line_number, c.conflict.is_some(), c.identical))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (2, true, false),
+                (3, true, true),
+                (4, false, false),
+                (5, false, false),
+                (6, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conflicts_use_normalized_combinations_and_shared_modes() {
+        let backup =
+            config(":: window\n:: resize\nhyper - h : echo h\nwindow, resize < alt - j : echo j\n");
+        let current = config(
+            ":: resize\ncmd + alt + shift + ctrl - h : echo other\nresize < alt - j : echo k\n",
+        );
+
+        let compared = compare_shortcuts(&backup, &current);
+        assert_eq!(
+            compared[0].conflict.as_ref().map(|c| c.command.as_str()),
+            Some("echo other")
+        );
+        assert_eq!(
+            compared[1].conflict.as_ref().map(|c| c.command.as_str()),
+            Some("echo k")
+        );
+
+        let mut restored = current.clone();
+        let result = restore_shortcuts(&mut restored, &backup, &[3, 4], |_| {
+            ConflictResolution::Replace
+        })
+        .unwrap();
+        assert_eq!(result.replaced.len(), 2);
+        assert_eq!(restored.shortcuts.len(), 2);
+    }
+
+    #[test]
+    fn test_restore_with_conflict_resolution() {
+        let backup = config(BACKUP);
+        let mut current = config("cmd - a : echo new-a\ncmd - b : echo b\n");
+        let original_a = current.shortcuts[0].clone();
+
+        // Skip by default
+        let result = restore_shortcuts(&mut current, &backup, &[2, 3, 4], |_| {
+            ConflictResolution::Skip
+        })
+        .unwrap();
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].command, "echo c");
+        assert_eq!(result.added[0].line_number, 3);
+        assert_eq!(result.skipped.len(), 2);
+        assert_eq!(current.shortcuts[0], original_a);
+        assert!(current.is_modified);
+
+        // Replace keeps the current binding's identity and position
+        let result =
+            restore_shortcuts(&mut current, &backup, &[2], |_| ConflictResolution::Replace)
+                .unwrap();
+        assert_eq!(result.replaced.len(), 1);
+        assert_eq!(current.shortcuts[0].id, original_a.id);
+        assert_eq!(current.shortcuts[0].command, "echo old-a");
+
+        // Identical bindings are never added twice
+        let result = restore_shortcuts(&mut current, &backup, &[2, 3], |_| {
+            ConflictResolution::KeepBoth
+        })
+        .unwrap();
+        assert_eq!(result.skipped.len(), 2);
+        assert_eq!(current.shortcuts.len(), 3);
+
+        // Keep both adds a duplicate next to a differing binding
+        let mut other = config("cmd - a : echo new-a\n");
+        let result =
+            restore_shortcuts(&mut other, &backup, &[2], |_| ConflictResolution::KeepBoth).unwrap();
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(other.shortcuts.len(), 2);
+    }
+
+    #[test]
+    fn test_restore_copies_modes_and_rejects_unknown_lines() {
+        let backup = config(BACKUP);
+        let mut current = config("cmd - a : echo a\n");
+
+        let error = restore_shortcuts(&mut current, &backup, &[5, 42], |_| {
+            ConflictResolution::Skip
+        })
+        .unwrap_err();
+        assert!(error.contains("line 42"));
+        assert_eq!(current.shortcuts.len(), 1);
+
+        let result =
+            restore_shortcuts(&mut current, &backup, &[5, 6], |_| ConflictResolution::Skip)
+                .unwrap();
+        assert_eq!(result.modes_added, vec!["window"]);
+        assert_eq!(current.modes[0].line_number, 2);
+        let lines: Vec<usize> = result.added.iter().map(|s| s.line_number).collect();
+        assert_eq!(lines, vec![3, 4]);
+    }
+}
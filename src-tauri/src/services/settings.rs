@@ -0,0 +1,147 @@
+/// Application settings and the paths derived from them
+///
+/// Settings live in `keybinder.json` in the skhd config directory:
+///
+/// ```json
+/// { "backup_dir": "~/Documents/skhd-backups" }
+/// ```
+///
+/// Services that read or write files outside the config being edited take an
+/// `AppPaths` instead of resolving locations themselves, so the backup directory
+/// follows the settings and tests can point every path at a temporary directory.
+use crate::services::file_io::write_config_atomic;
+use crate::utils::path::expand_path;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Settings file in the skhd config directory
+pub const SETTINGS_FILE: &str = "keybinder.json";
+
+/// User-configurable application settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Directory for backups (`~` is expanded; `<config dir>/backups` if unset)
+    #[serde(default)]
+    pub backup_dir: Option<PathBuf>,
+}
+
+impl AppSettings {
+    /// Load the settings of a config directory (defaults if there are none)
+    pub fn load(config_dir: &Path) -> io::Result<Self> {
+        let path = config_dir.join(SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(&path)?;
+        serde_json::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid settings {}: {}", path.display(), e),
+            )
+        })
+    }
+
+    /// Store the settings of a config directory
+    pub fn save(&self, config_dir: &Path) -> io::Result<()> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_config_atomic(config_dir.join(SETTINGS_FILE), &text)
+    }
+
+    /// Check that the settings can be applied
+    ///
+    /// A custom backup directory must be an absolute path (after `~` expansion),
+    /// and must be a directory if it exists.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(backup_dir) = &self.backup_dir {
+            let expanded = expand_path(backup_dir);
+            if !expanded.is_absolute() {
+                return Err(format!(
+                    "Backup directory must be an absolute path: {}",
+                    backup_dir.display()
+                ));
+            }
+            if expanded.exists() && !expanded.is_dir() {
+                return Err(format!(
+                    "Backup directory is not a directory: {}",
+                    expanded.display()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Locations the application reads and writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppPaths {
+    /// skhd config directory, holding the settings, retention policy, history
+    /// settings and private git repositories
+    pub config_dir: PathBuf,
+
+    /// Backup store
+    pub backup_dir: PathBuf,
+}
+
+impl AppPaths {
+    /// Paths with explicit directories
+    pub fn new(config_dir: PathBuf, backup_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            backup_dir,
+        }
+    }
+
+    /// Paths of a config directory with its settings applied
+    pub fn with_settings(config_dir: PathBuf, settings: &AppSettings) -> Self {
+        let backup_dir = match &settings.backup_dir {
+            Some(dir) => expand_path(dir),
+            None => config_dir.join("backups"),
+        };
+        Self::new(config_dir, backup_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_decide_backup_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().to_path_buf();
+
+        // No settings: backups next to the config
+        let settings = AppSettings::load(&config_dir).unwrap();
+        assert_eq!(settings, AppSettings::default());
+        let paths = AppPaths::with_settings(config_dir.clone(), &settings);
+        assert_eq!(paths.backup_dir, config_dir.join("backups"));
+
+        let settings = AppSettings {
+            backup_dir: Some(PathBuf::from("~/skhd-backups")),
+        };
+        assert!(settings.validate().is_ok());
+        settings.save(&config_dir).unwrap();
+        let loaded = AppSettings::load(&config_dir).unwrap();
+        assert_eq!(loaded, settings);
+        assert_eq!(
+            AppPaths::with_settings(config_dir.clone(), &loaded).backup_dir,
+            expand_path("~/skhd-backups")
+        );
+
+        // Relative directories and files are rejected
+        let relative = AppSettings {
+            backup_dir: Some(PathBuf::from("backups")),
+        };
+        assert!(relative.validate().is_err());
+        let file = config_dir.join(SETTINGS_FILE);
+        let not_a_dir = AppSettings {
+            backup_dir: Some(file),
+        };
+        assert!(not_a_dir.validate().is_err());
+    }
+}
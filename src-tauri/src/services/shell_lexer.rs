@@ -0,0 +1,551 @@
+/// Minimal POSIX shell tokenizer for analyzing shortcut commands
+///
+/// This is not a full shell parser: it understands quoting, escapes, comments,
+/// command/parameter substitutions, control operators and redirections, which is
+/// enough to find the programs a command runs and to spot malformed quoting.
+use serde::{Deserialize, Serialize};
+
+/// Kind of a shell token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    /// A word, with quotes and escapes removed
+    Word,
+    /// A control operator: `&&`, `||`, `;`, `;;`, `&`, `|`, `(`, `)`
+    Operator,
+    /// A redirection operator such as `>`, `2>>` or `<`
+    Redirect,
+}
+
+/// A shell token with its byte range in the command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub kind: TokenKind,
+
+    /// Word value with quoting removed, or the operator text
+    pub text: String,
+
+    /// Whether any part of the word was quoted or escaped
+    pub quoted: bool,
+
+    /// Whether the word contains a `$` expansion or command substitution
+    pub expands: bool,
+
+    /// Byte offset where the token starts
+    pub start: usize,
+
+    /// Byte offset just past the token
+    pub end: usize,
+}
+
+/// A tokenizer error (unterminated quote or substitution, stray `)`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LexError {
+    pub message: String,
+
+    /// Byte offset of the construct that caused the error
+    pub offset: usize,
+}
+
+/// A simple command: the words run as one program invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// Leading `NAME=value` assignments
+    pub assignments: Vec<Token>,
+
+    /// Program and arguments (redirections and their targets excluded)
+    pub words: Vec<Token>,
+
+    /// Redirection targets, e.g. the file in `> /etc/hosts`
+    pub redirects: Vec<(Token, Token)>,
+
+    /// Operator that ended this command (`|`, `&&`, ...), if any
+    pub terminator: Option<String>,
+}
+
+impl SimpleCommand {
+    /// Program name (first word), if the command runs one
+    pub fn program(&self) -> Option<&Token> {
+        self.words.first()
+    }
+
+    /// Arguments after the program name
+    pub fn args(&self) -> &[Token] {
+        self.words.get(1..).unwrap_or(&[])
+    }
+}
+
+/// Reserved words that introduce the command that follows them
+const LEADING_KEYWORDS: [&str; 8] = ["if", "then", "elif", "else", "while", "until", "do", "!"];
+
+/// Reserved words that close a compound command
+const CLOSING_KEYWORDS: [&str; 5] = ["fi", "done", "esac", "}", "{"];
+
+/// Tokenize a shell command
+pub fn tokenize(command: &str) -> Result<Vec<Token>, LexError> {
+    let bytes = command.as_bytes();
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    // Open `case` statements, whose patterns end with an unmatched `)`
+    let mut cases = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+
+        if c == b' ' || c == b'\t' || c == b'\n' || c == b'\r' {
+            i += 1;
+            continue;
+        }
+
+        // Comment: runs to the end of the line
+        if c == b'#' {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        if let Some(len) = redirect_len(&bytes[i..]) {
+            tokens.push(operator(TokenKind::Redirect, command, i, i + len));
+            i += len;
+            continue;
+        }
+
+        if let Some(len) = operator_len(&bytes[i..]) {
+            match c {
+                b'(' => depth += 1,
+                b')' if depth == 0 && cases > 0 => {}
+                b')' if depth == 0 => {
+                    return Err(LexError {
+                        message: "Unexpected ')'".to_string(),
+                        offset: i,
+                    })
+                }
+                b')' => depth -= 1,
+                _ => {}
+            }
+            tokens.push(operator(TokenKind::Operator, command, i, i + len));
+            i += len;
+            continue;
+        }
+
+        let (token, next) = read_word(command, i)?;
+        if !token.quoted {
+            // `case` may also follow a reserved word, as in `if true; then case ...`
+            let command_start = tokens.last().is_none_or(|t: &Token| {
+                t.kind == TokenKind::Operator
+                    || (t.kind == TokenKind::Word
+                        && !t.quoted
+                        && (LEADING_KEYWORDS.contains(&t.text.as_str()) || t.text == "{"))
+            });
+            match token.text.as_str() {
+                "case" if command_start => cases += 1,
+                "esac" => cases = cases.saturating_sub(1),
+                _ => {}
+            }
+        }
+        tokens.push(token);
+        i = next;
+    }
+
+    if depth > 0 {
+        return Err(LexError {
+            message: "Unclosed '('".to_string(),
+            offset: command.len(),
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Split a command into simple commands
+pub fn simple_commands(tokens: &[Token]) -> Vec<SimpleCommand> {
+    let mut commands = Vec::new();
+    let mut current = SimpleCommand {
+        assignments: Vec::new(),
+        words: Vec::new(),
+        redirects: Vec::new(),
+        terminator: None,
+    };
+    let mut iter = tokens.iter().peekable();
+
+    while let Some(token) = iter.next() {
+        match token.kind {
+            TokenKind::Operator => {
+                current.terminator = Some(token.text.clone());
+                let finished = std::mem::replace(
+                    &mut current,
+                    SimpleCommand {
+                        assignments: Vec::new(),
+                        words: Vec::new(),
+                        redirects: Vec::new(),
+                        terminator: None,
+                    },
+                );
+                if !finished.words.is_empty() || !finished.redirects.is_empty() {
+                    commands.push(finished);
+                }
+            }
+            TokenKind::Redirect => {
+                // Descriptor duplication such as `2>&1` has a target but no file
+                if let Some(target) = iter.next_if(|t| t.kind == TokenKind::Word) {
+                    current.redirects.push((token.clone(), target.clone()));
+                }
+            }
+            TokenKind::Word => {
+                if current.words.is_empty() {
+                    if !token.quoted && is_assignment(&token.text) {
+                        current.assignments.push(token.clone());
+                        continue;
+                    }
+                    if !token.quoted
+                        && (LEADING_KEYWORDS.contains(&token.text.as_str())
+                            || CLOSING_KEYWORDS.contains(&token.text.as_str()))
+                    {
+                        continue;
+                    }
+                }
+                current.words.push(token.clone());
+            }
+        }
+    }
+
+    if !current.words.is_empty() || !current.redirects.is_empty() {
+        commands.push(current);
+    }
+
+    // `for`/`case` headers are not program invocations
+    commands.retain(|c| {
+        !matches!(
+            c.program().map(|t| t.text.as_str()),
+            Some("for") | Some("case") | Some("in")
+        )
+    });
+    commands
+}
+
+/// Check for a `NAME=value` assignment word
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+fn operator(kind: TokenKind, command: &str, start: usize, end: usize) -> Token {
+    Token {
+        kind,
+        text: command[start..end].to_string(),
+        quoted: false,
+        expands: false,
+        start,
+        end,
+    }
+}
+
+/// Length of a control operator at the start of `bytes`
+fn operator_len(bytes: &[u8]) -> Option<usize> {
+    match bytes {
+        [b'&', b'&', ..] | [b'|', b'|', ..] | [b';', b';', ..] => Some(2),
+        [b'&', ..] | [b'|', ..] | [b';', ..] | [b'(', ..] | [b')', ..] => Some(1),
+        _ => None,
+    }
+}
+
+/// Length of a redirection operator (with optional descriptor) at the start of `bytes`
+fn redirect_len(bytes: &[u8]) -> Option<usize> {
+    let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    let rest = &bytes[digits..];
+
+    let len = match rest {
+        [b'&', b'>', b'>', ..] => 3,
+        [b'&', b'>', ..] if digits == 0 => 2,
+        [b'>', b'>', ..] | [b'>', b'&', ..] | [b'>', b'|', ..] => 2,
+        [b'<', b'<', b'<', ..] => 3,
+        [b'<', b'<', ..] | [b'<', b'&', ..] | [b'<', b'>', ..] => 2,
+        [b'>', ..] | [b'<', ..] => 1,
+        _ => return None,
+    };
+    Some(digits + len)
+}
+
+/// Read a word starting at `start`, returning it and the offset after it
+fn read_word(command: &str, start: usize) -> Result<(Token, usize), LexError> {
+    let bytes = command.as_bytes();
+    let mut text = String::new();
+    let mut quoted = false;
+    let mut expands = false;
+    let mut i = start;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            b' ' | b'\t' | b'\n' | b'\r' => break,
+            _ if operator_len(&bytes[i..]).is_some() => break,
+            b'<' | b'>' => break,
+            b'\\' => {
+                quoted = true;
+                match command[i + 1..].chars().next() {
+                    // Line continuation
+                    Some('\n') => i += 2,
+                    Some(ch) => {
+                        text.push(ch);
+                        i += 1 + ch.len_utf8();
+                    }
+                    None => i += 1,
+                }
+            }
+            b'\'' => {
+                quoted = true;
+                let end = command[i + 1..].find('\'').ok_or(LexError {
+                    message: "Unterminated single quote".to_string(),
+                    offset: i,
+                })?;
+                text.push_str(&command[i + 1..i + 1 + end]);
+                i += end + 2;
+            }
+            b'"' => {
+                quoted = true;
+                let (value, next, has_expansion) = read_double_quoted(command, i)?;
+                text.push_str(&value);
+                expands |= has_expansion;
+                i = next;
+            }
+            b'`' => {
+                expands = true;
+                let end = find_backquote_end(command, i)?;
+                text.push_str(&command[i..end]);
+                i = end;
+            }
+            b'$' => {
+                expands = true;
+                let end = expansion_end(command, i)?;
+                text.push_str(&command[i..end]);
+                i = end;
+            }
+            _ => {
+                let ch = command[i..].chars().next().unwrap_or_default();
+                text.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+    }
+
+    Ok((
+        Token {
+            kind: TokenKind::Word,
+            text,
+            quoted,
+            expands,
+            start,
+            end: i,
+        },
+        i,
+    ))
+}
+
+/// Read a double-quoted string starting at the opening quote
+fn read_double_quoted(command: &str, start: usize) -> Result<(String, usize, bool), LexError> {
+    let bytes = command.as_bytes();
+    let mut value = String::new();
+    let mut expands = false;
+    let mut i = start + 1;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => return Ok((value, i + 1, expands)),
+            b'\\' if matches!(bytes.get(i + 1), Some(b'$' | b'`' | b'"' | b'\\')) => {
+                value.push(bytes[i + 1] as char);
+                i += 2;
+            }
+            b'\\' if bytes.get(i + 1) == Some(&b'\n') => i += 2,
+            b'$' => {
+                expands = true;
+                let end = expansion_end(command, i)?;
+                value.push_str(&command[i..end]);
+                i = end;
+            }
+            b'`' => {
+                expands = true;
+                let end = find_backquote_end(command, i)?;
+                value.push_str(&command[i..end]);
+                i = end;
+            }
+            _ => {
+                let ch = command[i..].chars().next().unwrap_or_default();
+                value.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+    }
+
+    Err(LexError {
+        message: "Unterminated double quote".to_string(),
+        offset: start,
+    })
+}
+
+/// End offset of a backquoted command substitution starting at `start`
+fn find_backquote_end(command: &str, start: usize) -> Result<usize, LexError> {
+    let bytes = command.as_bytes();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => return Ok(i + 1),
+            _ => i += 1,
+        }
+    }
+    Err(LexError {
+        message: "Unterminated command substitution '`'".to_string(),
+        offset: start,
+    })
+}
+
+/// End offset of a `$` expansion starting at `start`
+fn expansion_end(command: &str, start: usize) -> Result<usize, LexError> {
+    let bytes = command.as_bytes();
+    match bytes.get(start + 1) {
+        Some(b'(') => matching_close(command, start + 1, b'(', b')', "$("),
+        Some(b'{') => matching_close(command, start + 1, b'{', b'}', "${"),
+        Some(c) if c.is_ascii_alphabetic() || *c == b'_' => Ok(start
+            + 1
+            + bytes[start + 1..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count()),
+        Some(c) if c.is_ascii_digit() || b"@*#?$!-".contains(c) => Ok(start + 2),
+        _ => Ok(start + 1),
+    }
+}
+
+/// Offset just past the bracket closing the one at `open_at`, skipping quoted text
+fn matching_close(
+    command: &str,
+    open_at: usize,
+    open: u8,
+    close: u8,
+    construct: &str,
+) -> Result<usize, LexError> {
+    let bytes = command.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open_at;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'\'' => {
+                i = command[i + 1..]
+                    .find('\'')
+                    .map(|end| i + 1 + end)
+                    .unwrap_or(bytes.len());
+            }
+            b'"' => {
+                i = read_double_quoted(command, i)?.1 - 1;
+            }
+            b if b == open => depth += 1,
+            b if b == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Err(LexError {
+        message: format!("Unterminated '{}'", construct),
+        offset: open_at - 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(command: &str) -> Vec<String> {
+        tokenize(command)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(
+            words(r#"open -a "Google Chrome" 'it''s' a\ b"#),
+            vec!["open", "-a", "Google Chrome", "its", "a b"]
+        );
+        assert_eq!(
+            words(r#"echo "$(date +%s)" ${HOME}/x"#),
+            vec!["echo", "$(date +%s)", "${HOME}/x"]
+        );
+    }
+
+    #[test]
+    fn test_operators_and_redirects() {
+        let tokens = tokenize("a && b | c 2>&1 > /tmp/out; d # note").unwrap();
+        let kinds: Vec<(TokenKind, &str)> =
+            tokens.iter().map(|t| (t.kind, t.text.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::Word, "a"),
+                (TokenKind::Operator, "&&"),
+                (TokenKind::Word, "b"),
+                (TokenKind::Operator, "|"),
+                (TokenKind::Word, "c"),
+                (TokenKind::Redirect, "2>&"),
+                (TokenKind::Word, "1"),
+                (TokenKind::Redirect, ">"),
+                (TokenKind::Word, "/tmp/out"),
+                (TokenKind::Operator, ";"),
+                (TokenKind::Word, "d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simple_commands() {
+        let tokens = tokenize(
+            "FOO=1 yabai -m space --focus 2 > /dev/null || if true; then osascript -e 'x'; fi",
+        )
+        .unwrap();
+        let commands = simple_commands(&tokens);
+        let programs: Vec<&str> = commands
+            .iter()
+            .filter_map(|c| c.program().map(|p| p.text.as_str()))
+            .collect();
+
+        assert_eq!(programs, vec!["yabai", "true", "osascript"]);
+        assert_eq!(commands[0].assignments[0].text, "FOO=1");
+        assert_eq!(commands[0].args().len(), 4);
+        assert_eq!(commands[0].redirects[0].1.text, "/dev/null");
+        assert_eq!(commands[0].terminator.as_deref(), Some("||"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            tokenize("echo 'oops").unwrap_err().message,
+            "Unterminated single quote"
+        );
+        assert!(tokenize("echo \"oops").is_err());
+        assert!(tokenize("echo $(date").is_err());
+        assert!(tokenize("echo `date").is_err());
+        assert!(tokenize("echo )").is_err());
+        assert!(tokenize("(echo hi").is_err());
+        assert!(tokenize("echo \"$(echo ')')\"").is_ok());
+        assert!(tokenize("case $1 in a) echo a ;; (b) echo b ;; esac").is_ok());
+        assert!(tokenize("if true; then case $x in a) echo a ;; esac; fi").is_ok());
+        assert!(tokenize("{ case $x in a) echo a ;; esac; }").is_ok());
+    }
+}
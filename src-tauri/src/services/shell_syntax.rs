@@ -0,0 +1,480 @@
+/// Shell syntax checking for shortcut commands
+///
+/// Commands are checked with the built-in tokenizer instead of spawning `sh -n`
+/// once per shortcut, so a whole config is checked in a single pass and gives the
+/// same result on every platform. The checker covers what skhd one-liners use:
+/// quoting, lists and pipelines, redirections, subshells, brace groups, `if`,
+/// `while`/`until`/`for` loops, `case` and function definitions, plus the process
+/// substitution (`<(...)`, `>(...)`) of bash and zsh, which skhd runs commands with.
+use crate::models::ConfigFile;
+use crate::services::shell_lexer::{tokenize, Token, TokenKind};
+use serde::{Deserialize, Serialize};
+
+/// A syntax error in a command
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyntaxError {
+    pub message: String,
+
+    /// Byte offset in the command
+    pub offset: usize,
+}
+
+impl SyntaxError {
+    /// 1-based character column of the error in the command it was found in
+    pub fn column(&self, command: &str) -> usize {
+        let mut offset = self.offset.min(command.len());
+        while !command.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        command[..offset].chars().count() + 1
+    }
+}
+
+/// A syntax error in one of a config's shortcuts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortcutSyntaxError {
+    pub shortcut_id: String,
+
+    /// Line of the shortcut in the config file
+    pub line_number: usize,
+
+    /// 1-based character column in the command
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IfPart {
+    Condition,
+    Then,
+    Else,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopPart {
+    /// `for NAME in WORDS;` before `do`
+    Header,
+    Condition,
+    Body,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CasePart {
+    /// `case WORD` before `in`
+    Header,
+    Pattern,
+    Body,
+}
+
+/// Compound command being parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    If(IfPart),
+    Loop(LoopPart),
+    Case(CasePart),
+    Brace,
+    Subshell,
+
+    /// `<(...)` or `>(...)`, a word holding a command
+    ProcessSubstitution,
+}
+
+impl Block {
+    /// Opening and closing words, for error messages
+    fn delimiters(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::If(_) => ("if", "fi"),
+            Self::Loop(_) => ("loop", "done"),
+            Self::Case(_) => ("case", "esac"),
+            Self::Brace => ("{", "}"),
+            Self::Subshell => ("(", ")"),
+            Self::ProcessSubstitution => ("<(", ")"),
+        }
+    }
+}
+
+/// Parser state while walking the tokens of one command
+struct Checker {
+    /// Open compound commands with the offset of their opening token
+    blocks: Vec<(Block, usize)>,
+
+    /// At a position where a command name (or reserved word) may appear
+    at_start: bool,
+
+    /// A compound command just closed; only operators and redirections may follow
+    closed: bool,
+
+    /// Token that must be followed by a command (`&&`, `|`, `then`, ...)
+    required: Option<(String, usize)>,
+}
+
+/// Check the syntax of a single command
+pub fn check_command_syntax(command: &str) -> Result<(), SyntaxError> {
+    let tokens = tokenize(command).map_err(|e| SyntaxError {
+        message: e.message,
+        offset: e.offset,
+    })?;
+
+    let mut checker = Checker {
+        blocks: Vec::new(),
+        at_start: true,
+        closed: false,
+        required: None,
+    };
+
+    let mut iter = tokens.iter().peekable();
+    while let Some(token) = iter.next() {
+        match token.kind {
+            TokenKind::Redirect => {
+                // `<(` directly followed by a command is process substitution
+                if matches!(token.text.as_str(), "<" | ">") && !checker.closed {
+                    if let Some(open) = iter.next_if(|t| {
+                        t.kind == TokenKind::Operator && t.text == "(" && t.start == token.end
+                    }) {
+                        checker
+                            .blocks
+                            .push((Block::ProcessSubstitution, token.start));
+                        checker.expect_command(open);
+                        continue;
+                    }
+                }
+                if iter.next_if(|t| t.kind == TokenKind::Word).is_none() {
+                    return Err(error(
+                        format!("Missing target for redirection '{}'", token.text),
+                        token,
+                    ));
+                }
+                checker.at_start = false;
+                checker.required = None;
+            }
+            TokenKind::Word => checker.word(token)?,
+            TokenKind::Operator => {
+                // `name()` starts a function definition
+                if token.text == "("
+                    && !checker.at_start
+                    && !checker.closed
+                    && iter
+                        .next_if(|t| t.kind == TokenKind::Operator && t.text == ")")
+                        .is_some()
+                {
+                    checker.at_start = true;
+                    checker.required = Some(("()".to_string(), token.start));
+                    continue;
+                }
+                checker.operator(token)?;
+            }
+        }
+    }
+
+    checker.finish(command.len())
+}
+
+impl Checker {
+    fn word(&mut self, token: &Token) -> Result<(), SyntaxError> {
+        let reserved = !token.quoted;
+
+        // Words inside `for`/`case` headers and case patterns are not commands
+        if let Some((block, _)) = self.blocks.last_mut() {
+            match block {
+                Block::Loop(LoopPart::Header) => {
+                    if reserved && token.text == "do" {
+                        *block = Block::Loop(LoopPart::Body);
+                        self.expect_command(token);
+                    }
+                    return Ok(());
+                }
+                Block::Case(CasePart::Header) => {
+                    if reserved && token.text == "in" {
+                        *block = Block::Case(CasePart::Pattern);
+                    }
+                    return Ok(());
+                }
+                Block::Case(CasePart::Pattern) => {
+                    if reserved && token.text == "esac" {
+                        self.close();
+                    }
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        if self.closed {
+            return Err(unexpected(token));
+        }
+
+        if reserved && self.at_start && self.reserved_word(token)? {
+            return Ok(());
+        }
+
+        self.at_start = false;
+        self.required = None;
+        Ok(())
+    }
+
+    /// Handle a reserved word; returns false if the word is an ordinary command name
+    fn reserved_word(&mut self, token: &Token) -> Result<bool, SyntaxError> {
+        let top = self.blocks.last().map(|(block, _)| *block);
+        // Parts of a compound command can only follow a complete command
+        let ready = self.required.is_none();
+
+        match token.text.as_str() {
+            "if" => self.open(Block::If(IfPart::Condition), token),
+            "while" | "until" => self.open(Block::Loop(LoopPart::Condition), token),
+            "{" => self.open(Block::Brace, token),
+            "for" => {
+                self.blocks
+                    .push((Block::Loop(LoopPart::Header), token.start));
+                self.required = None;
+            }
+            "case" => {
+                self.blocks
+                    .push((Block::Case(CasePart::Header), token.start));
+                self.required = None;
+            }
+            "!" => self.expect_command(token),
+            "then" if ready && top == Some(Block::If(IfPart::Condition)) => {
+                self.replace_top(Block::If(IfPart::Then), token)
+            }
+            "elif" if ready && top == Some(Block::If(IfPart::Then)) => {
+                self.replace_top(Block::If(IfPart::Condition), token)
+            }
+            "else" if ready && top == Some(Block::If(IfPart::Then)) => {
+                self.replace_top(Block::If(IfPart::Else), token)
+            }
+            "do" if ready && top == Some(Block::Loop(LoopPart::Condition)) => {
+                self.replace_top(Block::Loop(LoopPart::Body), token)
+            }
+            "fi" if ready
+                && matches!(
+                    top,
+                    Some(Block::If(IfPart::Then)) | Some(Block::If(IfPart::Else))
+                ) =>
+            {
+                self.close()
+            }
+            "done" if ready && top == Some(Block::Loop(LoopPart::Body)) => self.close(),
+            "esac" if ready && top == Some(Block::Case(CasePart::Body)) => self.close(),
+            "}" if ready && top == Some(Block::Brace) => self.close(),
+            "then" | "elif" | "else" | "do" | "fi" | "done" | "esac" | "}" => {
+                return Err(unexpected(token))
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn operator(&mut self, token: &Token) -> Result<(), SyntaxError> {
+        let text = token.text.as_str();
+
+        if let Some((block, _)) = self.blocks.last_mut() {
+            match (*block, text) {
+                // Alternatives and the optional `(` in case patterns
+                (Block::Case(CasePart::Pattern), "|" | "(") => return Ok(()),
+                (Block::Case(CasePart::Pattern), ")") => {
+                    *block = Block::Case(CasePart::Body);
+                    self.at_start = true;
+                    self.closed = false;
+                    self.required = None;
+                    return Ok(());
+                }
+                (Block::Case(CasePart::Body), ";;") if self.required.is_none() => {
+                    *block = Block::Case(CasePart::Pattern);
+                    self.closed = false;
+                    return Ok(());
+                }
+                (Block::Case(CasePart::Header | CasePart::Pattern), _) => {
+                    return Err(unexpected(token))
+                }
+                (Block::Loop(LoopPart::Header), ";") => return Ok(()),
+                (Block::Loop(LoopPart::Header), _) => return Err(unexpected(token)),
+                _ => {}
+            }
+        }
+
+        // A list or pipeline operator needs a command on its left
+        let empty = self.at_start && !self.closed;
+        match text {
+            "&&" | "||" | "|" => {
+                if empty || self.required.is_some() {
+                    return Err(unexpected(token));
+                }
+                self.closed = false;
+                self.expect_command(token);
+            }
+            ";" | "&" => {
+                if empty || self.required.is_some() {
+                    return Err(unexpected(token));
+                }
+                self.closed = false;
+                self.at_start = true;
+            }
+            "(" if self.at_start && !self.closed => self.open(Block::Subshell, token),
+            ")" if self.required.is_none()
+                && self.blocks.last().map(|(block, _)| *block) == Some(Block::Subshell) =>
+            {
+                self.close()
+            }
+            // The substitution is a word of the enclosing command, which goes on
+            ")" if self.required.is_none()
+                && self.blocks.last().map(|(block, _)| *block)
+                    == Some(Block::ProcessSubstitution) =>
+            {
+                self.blocks.pop();
+                self.at_start = false;
+                self.closed = false;
+            }
+            _ => return Err(unexpected(token)),
+        }
+        Ok(())
+    }
+
+    fn finish(self, end: usize) -> Result<(), SyntaxError> {
+        if let Some((text, _)) = self.required {
+            return Err(SyntaxError {
+                message: format!("Unexpected end of command after '{}'", text),
+                offset: end,
+            });
+        }
+
+        if let Some((block, offset)) = self.blocks.last() {
+            let (open, close) = block.delimiters();
+            return Err(SyntaxError {
+                message: format!("Missing '{}' to close '{}'", close, open),
+                offset: *offset,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn open(&mut self, block: Block, token: &Token) {
+        self.blocks.push((block, token.start));
+        self.expect_command(token);
+    }
+
+    fn replace_top(&mut self, block: Block, token: &Token) {
+        if let Some(top) = self.blocks.last_mut() {
+            top.0 = block;
+        }
+        self.expect_command(token);
+    }
+
+    fn close(&mut self) {
+        self.blocks.pop();
+        self.at_start = false;
+        self.closed = true;
+        self.required = None;
+    }
+
+    fn expect_command(&mut self, token: &Token) {
+        self.at_start = true;
+        self.required = Some((token.text.clone(), token.start));
+    }
+}
+
+fn error(message: String, token: &Token) -> SyntaxError {
+    SyntaxError {
+        message,
+        offset: token.start,
+    }
+}
+
+fn unexpected(token: &Token) -> SyntaxError {
+    error(format!("Unexpected '{}'", token.text), token)
+}
+
+/// Check the syntax of every command in a config
+///
+/// Errors are reported per shortcut, at the shortcut's line in the config file.
+pub fn check_config_syntax(config: &ConfigFile) -> Vec<ShortcutSyntaxError> {
+    config
+        .shortcuts
+        .iter()
+        .filter_map(|shortcut| {
+            let error = check_command_syntax(&shortcut.command).err()?;
+            Some(ShortcutSyntaxError {
+                shortcut_id: shortcut.id.clone(),
+                line_number: shortcut.line_number,
+                column: error.column(&shortcut.command),
+                message: error.message,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Shortcut;
+
+    fn message(command: &str) -> String {
+        check_command_syntax(command).unwrap_err().message
+    }
+
+    #[test]
+    fn test_valid_commands() {
+        for command in [
+            "open -a Terminal",
+            "yabai -m window --focus west || yabai -m display --focus west",
+            "echo \"a;b\" > /tmp/out 2>&1 &",
+            "(cd ~ && ls) | wc -l; echo done",
+            "if pgrep -q Music; then osascript -e 'tell app \"Music\" to playpause'; else open -a Music; fi",
+            "for s in 1 2 3; do yabai -m space $s --label s$s; done",
+            "while true; do sleep 1; done &",
+            "case $(yabai -m query --spaces --space | jq .type) in '\"bsp\"') yabai -m space --layout float ;; *) yabai -m space --layout bsp ;; esac",
+            "{ echo a; echo b; } > /tmp/log",
+            "toggle() { echo on; }; toggle",
+            "! pgrep skhd && skhd --start-service",
+            "diff <(ls ~/a) <(ls ~/b) > /tmp/diff",
+            "tee >(wc -l) < /tmp/in | cat",
+        ] {
+            assert_eq!(check_command_syntax(command), Ok(()), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        assert_eq!(message("echo 'oops"), "Unterminated single quote");
+        assert_eq!(message("&& echo"), "Unexpected '&&'");
+        assert_eq!(message("echo a &&"), "Unexpected end of command after '&&'");
+        assert_eq!(message("echo a | | wc"), "Unexpected '|'");
+        assert_eq!(message("echo a ;; echo b"), "Unexpected ';;'");
+        assert_eq!(message("echo >"), "Missing target for redirection '>'");
+        assert_eq!(
+            message("if true; then echo a"),
+            "Missing 'fi' to close 'if'"
+        );
+        assert_eq!(message("if true; then fi"), "Unexpected 'fi'");
+        assert_eq!(message("echo a; done"), "Unexpected 'done'");
+        assert_eq!(message("(echo a) echo b"), "Unexpected 'echo'");
+        assert_eq!(message("echo ( a )"), "Unexpected '('");
+        assert_eq!(message("diff <(ls"), "Unclosed '('");
+        assert_eq!(message("diff <(ls &&)"), "Unexpected ')'");
+        assert_eq!(message("cat < (ls)"), "Missing target for redirection '<'");
+
+        let error = check_command_syntax("echo a; then").unwrap_err();
+        assert_eq!(error.offset, 8);
+    }
+
+    #[test]
+    fn test_check_config_syntax() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        for (line, command) in ["echo ok", "echo 'é && oops", "ls |"].iter().enumerate() {
+            config.add_shortcut(Shortcut::new(
+                vec!["alt".to_string()],
+                "a".to_string(),
+                command.to_string(),
+                line + 3,
+            ));
+        }
+
+        let errors = check_config_syntax(&config);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line_number, 4);
+        assert_eq!(errors[0].column, 6);
+        assert_eq!(errors[0].shortcut_id, config.shortcuts[1].id);
+        assert_eq!(errors[1].line_number, 5);
+        assert_eq!(errors[1].column, 5);
+    }
+}
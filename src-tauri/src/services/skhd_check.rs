@@ -0,0 +1,239 @@
+/// Syntax check with the installed skhd binary
+///
+/// skhd has no check-only flag: it parses its config at startup, reports errors on
+/// stderr as `#<line>:<column> <message>` and then keeps running. The check starts
+/// `skhd -V -c <temp copy>`, stops it after a short timeout and reads the errors.
+/// skhd may refuse to start next to a running service (pid file lock); callers then
+/// fall back to the built-in parser. While skhd runs, the copy's hotkeys are live.
+use crate::services::command_analysis::{split_path, CommandEnvironment};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Default time skhd may run before it is stopped
+pub const DEFAULT_SKHD_TIMEOUT_MS: u64 = 1500;
+
+/// Interval at which the running skhd is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Parser used to check config syntax
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParserBackend {
+    /// Keybinder's own parser
+    #[default]
+    Builtin,
+
+    /// The installed skhd, falling back to the built-in parser when unavailable
+    Skhd,
+}
+
+/// An error reported by skhd
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkhdError {
+    pub line_number: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// An installed skhd binary
+#[derive(Debug, Clone)]
+pub struct SkhdCheck {
+    pub binary: PathBuf,
+    pub timeout: Duration,
+}
+
+impl SkhdCheck {
+    /// Check with an explicit binary
+    pub fn new(binary: PathBuf, timeout: Duration) -> Self {
+        Self { binary, timeout }
+    }
+
+    /// Find skhd on a PATH (`$PATH` when None)
+    pub fn find(path: Option<&str>, timeout: Duration) -> Option<Self> {
+        let path = path
+            .map(String::from)
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        let environment = CommandEnvironment::new(split_path(&path), None);
+
+        environment
+            .resolve_executable("skhd")
+            .map(|binary| Self::new(binary, timeout))
+    }
+
+    /// Run skhd on a temporary copy of a config and collect its errors
+    ///
+    /// Fails when skhd cannot be started, or exits with an error without reporting
+    /// any config error (e.g. another instance holds the pid file).
+    pub fn run(&self, text: &str) -> Result<Vec<SkhdError>, String> {
+        let mut copy = tempfile::Builder::new()
+            .prefix("keybinder-check-")
+            .suffix(".skhdrc")
+            .tempfile()
+            .map_err(|e| format!("Failed to create temporary config: {}", e))?;
+        copy.write_all(text.as_bytes())
+            .and_then(|_| copy.flush())
+            .map_err(|e| format!("Failed to write temporary config: {}", e))?;
+
+        let mut child = Command::new(&self.binary)
+            .arg("-V")
+            .arg("-c")
+            .arg(copy.path())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", self.binary.display(), e))?;
+
+        // Drain stderr on a thread so a chatty skhd cannot block on a full pipe
+        let (sender, receiver) = mpsc::channel();
+        if let Some(mut stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                let mut output = String::new();
+                let _ = stderr.read_to_string(&mut output);
+                let _ = sender.send(output);
+            });
+        }
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break None;
+                }
+                Ok(None) => std::thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(format!("Failed to wait for skhd: {}", e)),
+            }
+        };
+
+        let stderr = receiver.recv_timeout(self.timeout).unwrap_or_default();
+        let errors = parse_skhd_errors(&stderr);
+
+        match status {
+            Some(status) if !status.success() && errors.is_empty() => {
+                Err(format!("skhd exited with {}: {}", status, stderr.trim()))
+            }
+            _ => Ok(errors),
+        }
+    }
+}
+
+/// Write a fake `skhd` into `dir` and return `dir` as a PATH
+///
+/// It reports `#<line>:<column> <message>` for every config line containing `pattern`,
+/// then `exec`s into `sleep`, like the real daemon that keeps running.
+#[cfg(test)]
+pub(crate) fn fake_skhd(
+    dir: &std::path::Path,
+    pattern: &str,
+    column: usize,
+    message: &str,
+) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("skhd");
+    std::fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n\
+             grep -n -F '{}' \"$3\" | while IFS=: read -r line rest; do\n\
+             \x20 echo \"#$line:{} {}\" >&2\n\
+             done\n\
+             exec sleep 5\n",
+            pattern, column, message
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    dir.to_string_lossy().to_string()
+}
+
+/// Parse skhd's `#<line>:<column> <message>` error lines
+///
+/// Other output (verbose logging) is ignored.
+pub fn parse_skhd_errors(stderr: &str) -> Vec<SkhdError> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("skhd: ").unwrap_or(line);
+            let (position, message) = line.strip_prefix('#')?.split_once(char::is_whitespace)?;
+            let (line_number, column) = position.split_once(':')?;
+
+            Some(SkhdError {
+                line_number: line_number.parse().ok()?,
+                column: column.parse().ok()?,
+                message: message.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_skhd_errors() {
+        let stderr = "skhd: using config '/tmp/x'\n#3:12 expected modifier\nskhd: #7:1 unknown token 'foo'\n#bad line\n";
+        let errors = parse_skhd_errors(stderr);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line_number, 3);
+        assert_eq!(errors[0].column, 12);
+        assert_eq!(errors[0].message, "expected modifier");
+        assert_eq!(errors[1].line_number, 7);
+    }
+
+    #[test]
+    fn test_run_fake_skhd() {
+        let dir = TempDir::new().unwrap();
+        let path = fake_skhd(dir.path(), "bogus", 5, "expected modifier");
+        let check = SkhdCheck::find(Some(&path), Duration::from_millis(300)).unwrap();
+
+        let started = Instant::now();
+        let errors = check.run("cmd - a : echo\nbogus - b : echo\n").unwrap();
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert_eq!(
+            errors,
+            vec![SkhdError {
+                line_number: 2,
+                column: 5,
+                message: "expected modifier".to_string(),
+            }]
+        );
+
+        assert!(check.run("cmd - a : echo\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_missing_or_failing_skhd() {
+        let empty = TempDir::new().unwrap();
+        let path = empty.path().to_string_lossy().to_string();
+        assert!(SkhdCheck::find(Some(&path), Duration::from_millis(100)).is_none());
+
+        let dir = TempDir::new().unwrap();
+        let binary = dir.path().join("skhd");
+        fs::write(
+            &binary,
+            "#!/bin/sh\necho 'skhd: could not lock pid-file!' >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let error = SkhdCheck::new(binary, Duration::from_millis(500))
+            .run("cmd - a : echo\n")
+            .unwrap_err();
+        assert!(error.contains("could not lock pid-file"));
+    }
+}
@@ -0,0 +1,232 @@
+/// macOS system shortcut conflict database
+///
+/// Built-in entries live in `data/system_shortcuts.json`. Users can add entries,
+/// change their severity or switch them off in `system_shortcuts.json` in the skhd
+/// config directory (`~/.config/skhd`), which uses the same format; entries are
+/// matched by ID.
+use crate::models::{KeyCombination, SystemShortcut, SystemShortcutCategory};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File name of the user's system shortcut overrides in the skhd config directory
+pub const USER_SYSTEM_SHORTCUTS_FILE: &str = "system_shortcuts.json";
+
+/// System shortcut data file format
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SystemShortcutCatalog {
+    #[serde(default)]
+    pub categories: Vec<SystemShortcutCategory>,
+    pub shortcuts: Vec<SystemShortcut>,
+}
+
+/// System shortcuts indexed by normalized key combination
+#[derive(Debug, Clone, Default)]
+pub struct SystemShortcutDb {
+    catalog: SystemShortcutCatalog,
+    index: HashMap<KeyCombination, Vec<usize>>,
+}
+
+impl SystemShortcutDb {
+    /// Build a database from a catalog, rejecting entries with invalid chords
+    pub fn from_catalog(catalog: SystemShortcutCatalog) -> Result<Self, String> {
+        let mut index: HashMap<KeyCombination, Vec<usize>> = HashMap::new();
+        for (i, entry) in catalog.shortcuts.iter().enumerate() {
+            let combination = KeyCombination::parse(&entry.chord).map_err(|e| {
+                format!("System shortcut '{}' has an invalid chord: {}", entry.id, e)
+            })?;
+            if entry.enabled {
+                index.entry(combination).or_default().push(i);
+            }
+        }
+        Ok(Self { catalog, index })
+    }
+
+    /// Load the built-in database
+    pub fn embedded() -> Result<Self, String> {
+        let json_data = include_str!("../data/system_shortcuts.json");
+        let catalog: SystemShortcutCatalog = serde_json::from_str(json_data)
+            .map_err(|e| format!("Failed to load system shortcuts: {}", e))?;
+        Self::from_catalog(catalog)
+    }
+
+    /// Load the built-in database merged with the overrides in a config directory (if any)
+    pub fn load(config_dir: &Path) -> Result<Self, String> {
+        Self::load_with_user_file(&user_file_path(config_dir))
+    }
+
+    /// Load the built-in database merged with overrides from `path` (if it exists)
+    pub fn load_with_user_file(path: &Path) -> Result<Self, String> {
+        let db = Self::embedded()?;
+        if !path.exists() {
+            return Ok(db);
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let user: SystemShortcutCatalog = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+        db.merged(user)
+    }
+
+    /// Merge user entries: same ID replaces, new IDs are appended
+    fn merged(self, user: SystemShortcutCatalog) -> Result<Self, String> {
+        let mut catalog = self.catalog;
+
+        for category in user.categories {
+            match catalog.categories.iter_mut().find(|c| c.id == category.id) {
+                Some(existing) => *existing = category,
+                None => catalog.categories.push(category),
+            }
+        }
+
+        for entry in user.shortcuts {
+            match catalog.shortcuts.iter_mut().find(|s| s.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => catalog.shortcuts.push(entry),
+            }
+        }
+
+        Self::from_catalog(catalog)
+    }
+
+    /// Enabled entries bound to a key combination
+    pub fn conflicts(&self, combination: &KeyCombination) -> Vec<&SystemShortcut> {
+        self.index
+            .get(combination)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|&i| &self.catalog.shortcuts[i])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Enabled entries with their normalized key combinations
+    pub fn entries(&self) -> impl Iterator<Item = (&KeyCombination, &SystemShortcut)> {
+        self.index.iter().flat_map(move |(combination, indices)| {
+            indices
+                .iter()
+                .map(move |&i| (combination, &self.catalog.shortcuts[i]))
+        })
+    }
+
+    /// Display name of a category (falls back to the ID)
+    pub fn category_name<'a>(&'a self, category_id: &'a str) -> &'a str {
+        self.catalog
+            .categories
+            .iter()
+            .find(|c| c.id == category_id)
+            .map_or(category_id, |c| c.name.as_str())
+    }
+
+    /// The merged catalog, including disabled entries
+    pub fn catalog(&self) -> &SystemShortcutCatalog {
+        &self.catalog
+    }
+}
+
+/// Path of the user's system shortcut overrides in a config directory
+pub fn user_file_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(USER_SYSTEM_SHORTCUTS_FILE)
+}
+
+/// Load the system shortcut database used for validation
+///
+/// Uses the overrides in `config_dir`, falling back to the built-in entries if the
+/// user file cannot be loaded.
+pub fn load_system_shortcuts(config_dir: &Path) -> SystemShortcutDb {
+    SystemShortcutDb::load(config_dir).unwrap_or_else(|e| {
+        eprintln!("Ignoring user system shortcuts: {}", e);
+        SystemShortcutDb::embedded().unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+    use tempfile::TempDir;
+
+    fn combination(chord: &str) -> KeyCombination {
+        KeyCombination::parse(chord).unwrap()
+    }
+
+    #[test]
+    fn test_embedded_database() {
+        let db = SystemShortcutDb::embedded().unwrap();
+
+        let conflicts = db.conflicts(&combination("cmd - space"));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, "spotlight");
+        assert_eq!(db.category_name(&conflicts[0].category), "Spotlight");
+
+        // Modifier order does not matter
+        assert_eq!(db.conflicts(&combination("shift + cmd - 4")).len(), 1);
+        assert!(db
+            .conflicts(&combination("cmd + alt + shift - k"))
+            .is_empty());
+
+        // Every entry belongs to a known category
+        for entry in &db.catalog().shortcuts {
+            assert!(
+                db.catalog()
+                    .categories
+                    .iter()
+                    .any(|c| c.id == entry.category),
+                "Unknown category for {}",
+                entry.id
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_overrides() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(USER_SYSTEM_SHORTCUTS_FILE);
+        std::fs::write(
+            &path,
+            r#"{
+                "shortcuts": [
+                    { "id": "spotlight", "category": "spotlight", "chord": "cmd - space",
+                      "description": "Spotlight", "severity": "error", "enabled": false },
+                    { "id": "screenshot-selection", "category": "screenshot",
+                      "chord": "cmd + shift - 4", "description": "Screenshot", "severity": "info" },
+                    { "id": "raycast", "category": "launchers", "chord": "alt - space",
+                      "description": "Raycast", "severity": "warning" }
+                ],
+                "categories": [{ "id": "launchers", "name": "Launchers" }]
+            }"#,
+        )
+        .unwrap();
+
+        let db = SystemShortcutDb::load_with_user_file(&path).unwrap();
+
+        assert!(db.conflicts(&combination("cmd - space")).is_empty());
+        assert_eq!(
+            db.conflicts(&combination("cmd + shift - 4"))[0].severity,
+            Severity::Info
+        );
+        let raycast = db.conflicts(&combination("alt - space"));
+        assert_eq!(raycast[0].id, "raycast");
+        assert_eq!(db.category_name("launchers"), "Launchers");
+    }
+
+    #[test]
+    fn test_invalid_user_chord() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(USER_SYSTEM_SHORTCUTS_FILE);
+        std::fs::write(
+            &path,
+            r#"{ "shortcuts": [{ "id": "bad", "category": "system", "chord": "super - a",
+                 "description": "Bad", "severity": "warning" }] }"#,
+        )
+        .unwrap();
+
+        assert!(SystemShortcutDb::load_with_user_file(&path).is_err());
+        // A missing user file is not an error
+        assert!(SystemShortcutDb::load_with_user_file(&dir.path().join("missing.json")).is_ok());
+    }
+}
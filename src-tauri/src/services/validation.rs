@@ -1,31 +1,65 @@
 /// Validation service for shortcuts and configurations
-use crate::models::{ConfigFile, Shortcut};
+///
+/// Checks are lint rules implementing [`LintRule`]. Projects can switch rules off or
+/// change their severity in a `.keybinder-lint.json` file next to the config, and a
+/// `# @allow <rule>` comment above a shortcut suppresses a rule for that shortcut.
+use crate::models::{
+    BindingConflict, ConfigFile, ConflictKind, Diagnostic, DiagnosticCode, Fix, KeyCombination,
+    ModeDeclaration, RelatedLocation, Severity, Shortcut, Span, TextEdit, DEFAULT_MODE,
+};
 use crate::parser::parse_config;
+use crate::services::command_analysis::{CommandEnvironment, CommandProblemKind};
+use crate::services::command_safety::{find_risks, RiskKind};
+use crate::services::mode_graph::ModeGraph;
+use crate::services::shell_syntax::check_command_syntax;
+use crate::services::skhd_check::{ParserBackend, SkhdCheck, DEFAULT_SKHD_TIMEOUT_MS};
+use crate::services::system_shortcuts::{load_system_shortcuts, SystemShortcutDb};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-/// Validation result containing errors and warnings
+/// Validation result containing structured diagnostics
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ValidationResult {
     pub is_valid: bool,
-    pub errors: Vec<String>,
-    pub warnings: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl ValidationResult {
     pub fn new() -> Self {
         Self {
             is_valid: true,
-            errors: Vec::new(),
-            warnings: Vec::new(),
+            diagnostics: Vec::new(),
         }
     }
 
-    pub fn add_error(&mut self, error: String) {
-        self.is_valid = false;
-        self.errors.push(error);
+    /// Add a diagnostic (errors mark the result invalid)
+    pub fn add(&mut self, diagnostic: Diagnostic) {
+        if diagnostic.is_error() {
+            self.is_valid = false;
+        }
+        self.diagnostics.push(diagnostic);
     }
 
-    pub fn add_warning(&mut self, warning: String) {
-        self.warnings.push(warning);
+    /// Add every diagnostic from another result
+    pub fn extend(&mut self, other: ValidationResult) {
+        for diagnostic in other.diagnostics {
+            self.add(diagnostic);
+        }
+    }
+
+    /// Error diagnostics
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    /// Warning diagnostics
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
     }
 }
 
@@ -35,125 +69,1079 @@ impl Default for ValidationResult {
     }
 }
 
-/// Validate a single shortcut
+/// File name of per-project lint settings, looked up next to the config file
+pub const LINT_SETTINGS_FILE: &str = ".keybinder-lint.json";
+
+/// Commands longer than this trigger the `long-command` rule
+const MAX_COMMAND_LENGTH: usize = 500;
+
+/// Configured level of a lint rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Error,
+    Warning,
+    Info,
+}
+
+impl RuleLevel {
+    /// Severity diagnostics are reported with (None when the rule is off)
+    pub fn severity(&self) -> Option<Severity> {
+        match self {
+            Self::Off => None,
+            Self::Error => Some(Severity::Error),
+            Self::Warning => Some(Severity::Warning),
+            Self::Info => Some(Severity::Info),
+        }
+    }
+}
+
+/// Per-project lint settings
 ///
-/// Checks:
-/// - Key and command are not empty
-/// - Modifiers are valid (cmd, alt, shift, ctrl, fn)
-/// - No duplicate modifiers
-pub fn validate_shortcut(shortcut: &Shortcut) -> ValidationResult {
-    let mut result = ValidationResult::new();
+/// ```json
+/// { "rules": { "no-modifiers": "off", "SKHD004": "error" }, "path": "/opt/homebrew/bin:/usr/bin:/bin" }
+/// ```
+///
+/// Rules are keyed by code or name; unlisted rules keep their default severity.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LintSettings {
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleLevel>,
+
+    /// PATH commands are resolved against (defaults to `$PATH`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Parser checking the config syntax when validating text
+    #[serde(default)]
+    pub parser: ParserBackend,
+
+    /// How long the `skhd` parser may run before it is stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skhd_timeout_ms: Option<u64>,
+}
+
+impl LintSettings {
+    /// Load settings from a file (defaults if it does not exist)
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
 
-    // Use the shortcut's own validation
-    if let Err(e) = shortcut.validate() {
-        result.add_error(e);
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
     }
 
-    // Additional warnings
-    if shortcut.command.len() > 500 {
-        result.add_warning("Command is very long (>500 chars)".to_string());
+    /// Load the settings file next to a config file
+    pub fn for_config(config: &ConfigFile) -> Result<Self, String> {
+        match settings_path(&config.file_path) {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
     }
 
-    if shortcut.modifiers.is_empty() {
-        result.add_warning(
-            "Shortcut has no modifiers - may conflict with system shortcuts".to_string(),
-        );
+    /// Time the `skhd` parser may run
+    pub fn skhd_timeout(&self) -> Duration {
+        Duration::from_millis(self.skhd_timeout_ms.unwrap_or(DEFAULT_SKHD_TIMEOUT_MS))
     }
 
-    result
+    /// Configured level for a rule, if any
+    pub fn level(&self, code: DiagnosticCode) -> Option<RuleLevel> {
+        self.rules
+            .iter()
+            .find(|(name, _)| DiagnosticCode::from_name(name) == Some(code))
+            .map(|(_, level)| *level)
+    }
 }
 
-/// Validate an entire configuration file
+/// Path of the lint settings file for a config file (None for in-memory configs)
+pub fn settings_path(config_path: &str) -> Option<PathBuf> {
+    let path = Path::new(config_path);
+    if !path.is_absolute() {
+        return None;
+    }
+    path.parent().map(|dir| dir.join(LINT_SETTINGS_FILE))
+}
+
+/// Data available to lint rules
+pub struct LintContext<'a> {
+    pub config: &'a ConfigFile,
+    pub system: &'a SystemShortcutDb,
+    pub environment: &'a CommandEnvironment,
+}
+
+/// A single lint check
 ///
-/// Checks:
-/// - All shortcuts are individually valid
-/// - No duplicate key combinations
-/// - No system shortcuts conflicts (basic check)
-pub fn validate_config(config: &ConfigFile) -> ValidationResult {
-    let mut result = ValidationResult::new();
+/// The linter gives every diagnostic of a rule its configured level, or else the
+/// rule's `default_severity`, whatever severity the rule reported it with. It
+/// applies `# @allow` suppressions as well.
+pub trait LintRule: Send + Sync {
+    /// Code identifying the rule and its diagnostics
+    fn code(&self) -> DiagnosticCode;
+
+    /// One-line explanation for settings UIs
+    fn description(&self) -> &'static str;
+
+    /// Severity used when the rule is not configured
+    fn default_severity(&self) -> Severity;
+
+    /// Whether unconfigured diagnostics keep the severity the rule reported
+    /// instead of `default_severity` (e.g. per database entry)
+    fn keeps_own_severity(&self) -> bool {
+        false
+    }
+
+    /// Check one shortcut on its own
+    fn check_shortcut(&self, _shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+
+    /// Check a whole configuration (by default, every shortcut on its own)
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        ctx.config
+            .shortcuts
+            .iter()
+            .flat_map(|shortcut| self.check_shortcut(shortcut, ctx))
+            .collect()
+    }
+}
+
+/// Description of a rule and its effective level
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LintRuleInfo {
+    pub code: DiagnosticCode,
+    pub rule: String,
+    pub description: String,
+    pub default_severity: Severity,
+
+    /// Level from the settings file (None when not configured)
+    pub level: Option<RuleLevel>,
+}
+
+/// Span of a shortcut's line (None for shortcuts not yet placed in a file)
+fn shortcut_span(shortcut: &Shortcut) -> Option<Span> {
+    (shortcut.line_number > 0).then(|| Span::line(shortcut.line_number))
+}
+
+/// Start a diagnostic about a shortcut, located at its line
+fn shortcut_diagnostic(diagnostic: Diagnostic, shortcut: &Shortcut) -> Diagnostic {
+    let diagnostic = diagnostic.with_shortcut(shortcut.id.clone());
+    match shortcut_span(shortcut) {
+        Some(span) => diagnostic.with_span(span),
+        None => diagnostic,
+    }
+}
+
+/// SKHD001: two bindings for the same key combination in the same mode
+pub struct DuplicateBindingRule;
+
+impl LintRule for DuplicateBindingRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::DuplicateBinding
+    }
+
+    fn description(&self) -> &'static str {
+        "Key combination is bound more than once in the same mode"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        // Report each duplicate at the later binding, pointing back at the earlier one
+        conflict_pairs(ctx.config, |kind| kind.is_duplicate())
+            .map(|(conflict, first, duplicate)| {
+                duplicate_diagnostic(first, duplicate, &conflict.explanation)
+            })
+            .collect()
+    }
+}
+
+/// Conflicts of the matching kinds, with their winning and shadowed shortcuts
+fn conflict_pairs(
+    config: &ConfigFile,
+    kinds: impl Fn(ConflictKind) -> bool,
+) -> impl Iterator<Item = (BindingConflict, &Shortcut, &Shortcut)> {
+    config
+        .conflicts()
+        .into_iter()
+        .filter(move |c| kinds(c.kind))
+        .filter_map(|c| {
+            let winner = config.find_shortcut(&c.winner_id)?;
+            let shadowed = config.find_shortcut(&c.shadowed_id)?;
+            Some((c, winner, shadowed))
+        })
+}
+
+/// Duplicate binding diagnostic for `duplicate`, which repeats `first`
+fn duplicate_diagnostic(first: &Shortcut, duplicate: &Shortcut, explanation: &str) -> Diagnostic {
+    let mut diagnostic = shortcut_diagnostic(
+        Diagnostic::error(
+            DiagnosticCode::DuplicateBinding,
+            format!(
+                "Duplicate key combination '{}' at lines {} and {}: {}",
+                duplicate.key_combination_string(),
+                first.line_number,
+                duplicate.line_number,
+                explanation
+            ),
+        ),
+        duplicate,
+    );
+
+    if let Some(span) = shortcut_span(first) {
+        diagnostic = diagnostic.with_related(RelatedLocation {
+            span,
+            shortcut_id: Some(first.id.clone()),
+            message: "First bound here".to_string(),
+        });
+    }
+
+    if let Some(span) = shortcut_span(duplicate) {
+        diagnostic = diagnostic
+            .with_fix(Fix {
+                title: "Remove duplicate binding".to_string(),
+                edits: vec![TextEdit {
+                    span,
+                    new_text: String::new(),
+                }],
+            })
+            .with_fix(Fix {
+                title: "Comment out duplicate binding".to_string(),
+                edits: vec![TextEdit {
+                    span: Span::point(span.line, 1),
+                    new_text: "# ".to_string(),
+                }],
+            });
+    }
+
+    diagnostic
+}
+
+/// SKHD015: binding hidden by a process list entry or by its laptop `fn` translation
+pub struct ShadowedBindingRule;
+
+impl LintRule for ShadowedBindingRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::ShadowedBinding
+    }
+
+    fn description(&self) -> &'static str {
+        "Binding is hidden by another binding for the same keystroke"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        conflict_pairs(ctx.config, |kind| !kind.is_duplicate())
+            .map(|(conflict, winner, shadowed)| {
+                let mut diagnostic = shortcut_diagnostic(
+                    Diagnostic::warning(
+                        self.code(),
+                        format!(
+                            "'{}' is shadowed: {}",
+                            conflict.combination, conflict.explanation
+                        ),
+                    ),
+                    shadowed,
+                );
+                if let Some(span) = shortcut_span(winner) {
+                    diagnostic = diagnostic.with_related(RelatedLocation {
+                        span,
+                        shortcut_id: Some(winner.id.clone()),
+                        message: "Wins here".to_string(),
+                    });
+                }
+                diagnostic
+            })
+            .collect()
+    }
+}
+
+/// SKHD002: binding collides with a macOS or common app shortcut
+///
+/// Diagnostics use the severity of the matching database entry unless the rule is re-leveled.
+pub struct SystemShortcutRule;
+
+impl LintRule for SystemShortcutRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::SystemShortcutConflict
+    }
+
+    fn description(&self) -> &'static str {
+        "Key combination is used by macOS or common applications"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn keeps_own_severity(&self) -> bool {
+        true
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, ctx: &LintContext) -> Vec<Diagnostic> {
+        let combination = KeyCombination::from_shortcut(shortcut);
+        ctx.system
+            .conflicts(&combination)
+            .into_iter()
+            .map(|entry| {
+                shortcut_diagnostic(
+                    Diagnostic::new(
+                        self.code(),
+                        entry.severity,
+                        format!(
+                            "Shortcut {} conflicts with system shortcut '{}' ({})",
+                            combination,
+                            entry.description,
+                            ctx.system.category_name(&entry.category)
+                        ),
+                    ),
+                    shortcut,
+                )
+            })
+            .collect()
+    }
+}
+
+/// SKHD003: binding without modifiers
+pub struct NoModifiersRule;
+
+impl LintRule for NoModifiersRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::NoModifiers
+    }
+
+    fn description(&self) -> &'static str {
+        "Shortcut has no modifiers and captures the bare key everywhere"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        if !shortcut.modifiers.is_empty() {
+            return Vec::new();
+        }
+        vec![shortcut_diagnostic(
+            Diagnostic::warning(
+                self.code(),
+                "Shortcut has no modifiers - may conflict with system shortcuts",
+            ),
+            shortcut,
+        )]
+    }
+}
+
+/// SKHD004: command longer than `MAX_COMMAND_LENGTH`
+pub struct LongCommandRule;
+
+impl LintRule for LongCommandRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::LongCommand
+    }
+
+    fn description(&self) -> &'static str {
+        "Command is very long and would be easier to maintain as a script"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        if shortcut.command.len() <= MAX_COMMAND_LENGTH {
+            return Vec::new();
+        }
+        vec![shortcut_diagnostic(
+            Diagnostic::warning(
+                self.code(),
+                format!("Command is very long (>{} chars)", MAX_COMMAND_LENGTH),
+            ),
+            shortcut,
+        )]
+    }
+}
+
+/// SKHD005: shortcut fails its own validation (empty key or command, bad modifiers...)
+pub struct InvalidShortcutRule;
+
+impl LintRule for InvalidShortcutRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::InvalidShortcut
+    }
+
+    fn description(&self) -> &'static str {
+        "Shortcut is malformed"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        match shortcut.validate() {
+            Ok(()) => Vec::new(),
+            Err(e) => vec![shortcut_diagnostic(
+                Diagnostic::error(self.code(), e),
+                shortcut,
+            )],
+        }
+    }
+}
+
+/// SKHD008: command runs a program that is not on PATH
+pub struct MissingExecutableRule;
+
+impl LintRule for MissingExecutableRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::MissingExecutable
+    }
+
+    fn description(&self) -> &'static str {
+        "Command runs a program that was not found on PATH"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, ctx: &LintContext) -> Vec<Diagnostic> {
+        command_diagnostics(
+            self.code(),
+            CommandProblemKind::MissingExecutable,
+            shortcut,
+            ctx,
+        )
+    }
+}
+
+/// SKHD009: `open -a`/`open -b` names an application that is not installed
+pub struct MissingApplicationRule;
+
+impl LintRule for MissingApplicationRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::MissingApplication
+    }
+
+    fn description(&self) -> &'static str {
+        "Command opens an application that is not installed"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, ctx: &LintContext) -> Vec<Diagnostic> {
+        command_diagnostics(
+            self.code(),
+            CommandProblemKind::MissingApplication,
+            shortcut,
+            ctx,
+        )
+    }
+}
+
+/// Report command analysis problems of one kind as warnings
+fn command_diagnostics(
+    code: DiagnosticCode,
+    kind: CommandProblemKind,
+    shortcut: &Shortcut,
+    ctx: &LintContext,
+) -> Vec<Diagnostic> {
+    ctx.environment
+        .analyze(&shortcut.command)
+        .into_iter()
+        .filter(|problem| problem.kind == kind)
+        .map(|problem| shortcut_diagnostic(Diagnostic::warning(code, problem.message), shortcut))
+        .collect()
+}
+
+/// SKHD010: command is not valid shell syntax
+pub struct ShellSyntaxRule;
+
+impl LintRule for ShellSyntaxRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::ShellSyntax
+    }
+
+    fn description(&self) -> &'static str {
+        "Command has a shell syntax error"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        let Err(error) = check_command_syntax(&shortcut.command) else {
+            return Vec::new();
+        };
+        vec![shortcut_diagnostic(
+            Diagnostic::error(
+                self.code(),
+                format!(
+                    "Shell syntax error at column {} of the command: {}",
+                    error.column(&shortcut.command),
+                    error.message
+                ),
+            ),
+            shortcut,
+        )]
+    }
+}
+
+/// SKHD011: command looks destructive or needs administrator privileges
+pub struct DangerousCommandRule;
+
+impl LintRule for DangerousCommandRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::DangerousCommand
+    }
+
+    fn description(&self) -> &'static str {
+        "Command deletes files, needs admin rights, kills processes or writes system paths"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+        // Commands that cannot be tokenized are reported by the syntax rule
+        find_risks(&shortcut.command)
+            .into_iter()
+            .filter(|risk| risk.kind != RiskKind::Unanalyzable)
+            .map(|risk| {
+                shortcut_diagnostic(Diagnostic::warning(self.code(), risk.message), shortcut)
+            })
+            .collect()
+    }
+}
+
+/// Attach a mode declaration's location to a diagnostic
+fn mode_diagnostic(diagnostic: Diagnostic, mode: &ModeDeclaration) -> Diagnostic {
+    if mode.line_number > 0 {
+        diagnostic.with_span(Span::line(mode.line_number))
+    } else {
+        diagnostic
+    }
+}
+
+/// SKHD012: binding scoped to, or activating, a mode that is not declared
+pub struct UndeclaredModeRule;
+
+impl LintRule for UndeclaredModeRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::UndeclaredMode
+    }
+
+    fn description(&self) -> &'static str {
+        "Shortcut is bound in or activates a mode without a `::` declaration"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_shortcut(&self, shortcut: &Shortcut, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = shortcut
+            .modes()
+            .into_iter()
+            .filter(|mode| !ctx.config.has_mode(mode))
+            .map(|mode| {
+                Diagnostic::error(
+                    self.code(),
+                    format!("Shortcut is bound in undeclared mode '{}'", mode),
+                )
+            })
+            .collect();
+
+        if let Some(target) = &shortcut.activates {
+            if !ctx.config.has_mode(target) {
+                diagnostics.push(Diagnostic::error(
+                    self.code(),
+                    format!("Shortcut activates undeclared mode '{}'", target),
+                ));
+            }
+        }
+
+        diagnostics
+            .into_iter()
+            .map(|d| shortcut_diagnostic(d, shortcut))
+            .collect()
+    }
+}
+
+/// SKHD013: declared mode that no binding chain from `default` activates
+pub struct UnreachableModeRule;
+
+impl LintRule for UnreachableModeRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::UnreachableMode
+    }
+
+    fn description(&self) -> &'static str {
+        "Mode cannot be activated from the default mode"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let graph = ModeGraph::build(ctx.config);
+        graph
+            .unreachable_modes()
+            .into_iter()
+            .filter_map(|name| ctx.config.find_mode(name))
+            .map(|mode| {
+                mode_diagnostic(
+                    Diagnostic::warning(
+                        self.code(),
+                        format!(
+                            "Mode '{}' is never activated from the {} mode",
+                            mode.name, DEFAULT_MODE
+                        ),
+                    ),
+                    mode,
+                )
+            })
+            .collect()
+    }
+}
+
+/// SKHD014: reachable mode with no binding chain back to `default`
+pub struct ModeWithoutExitRule;
+
+impl LintRule for ModeWithoutExitRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::ModeWithoutExit
+    }
+
+    fn description(&self) -> &'static str {
+        "Mode has no binding leading back to the default mode"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let graph = ModeGraph::build(ctx.config);
+        graph
+            .modes_without_exit()
+            .into_iter()
+            .filter_map(|name| ctx.config.find_mode(name))
+            .map(|mode| {
+                let mut diagnostic = mode_diagnostic(
+                    Diagnostic::error(
+                        self.code(),
+                        format!(
+                            "Mode '{}' has no binding back to the {} mode; once active, its keys stay captured",
+                            mode.name, DEFAULT_MODE
+                        ),
+                    ),
+                    mode,
+                );
+                if mode.line_number > 0 {
+                    diagnostic = diagnostic.with_fix(Fix {
+                        title: format!("Bind escape to leave '{}'", mode.name),
+                        edits: vec![TextEdit {
+                            span: Span::point(mode.line_number + 1, 1),
+                            new_text: format!("{} < - escape ; {}\n", mode.name, DEFAULT_MODE),
+                        }],
+                    });
+                }
+                diagnostic
+            })
+            .collect()
+    }
+}
+
+/// SKHD017: directive comment the parser dropped because no shortcut follows it
+pub struct DanglingDirectiveRule;
+
+impl LintRule for DanglingDirectiveRule {
+    fn code(&self) -> DiagnosticCode {
+        DiagnosticCode::DanglingDirective
+    }
+
+    fn description(&self) -> &'static str {
+        "Directive comment is not directly above a shortcut and is ignored"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_config(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        ctx.config
+            .dangling_directives
+            .iter()
+            .map(|directive| {
+                Diagnostic::warning(
+                    self.code(),
+                    format!(
+                        "Line {}: '# {}' is not directly above a shortcut and is ignored",
+                        directive.line_number, directive.text
+                    ),
+                )
+                .with_span(Span::line(directive.line_number))
+            })
+            .collect()
+    }
+}
+
+/// Built-in rules, in reporting order
+pub fn default_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(InvalidShortcutRule),
+        Box::new(ShellSyntaxRule),
+        Box::new(DuplicateBindingRule),
+        Box::new(ShadowedBindingRule),
+        Box::new(SystemShortcutRule),
+        Box::new(NoModifiersRule),
+        Box::new(LongCommandRule),
+        Box::new(MissingExecutableRule),
+        Box::new(MissingApplicationRule),
+        Box::new(DangerousCommandRule),
+        Box::new(UndeclaredModeRule),
+        Box::new(UnreachableModeRule),
+        Box::new(ModeWithoutExitRule),
+        Box::new(DanglingDirectiveRule),
+    ]
+}
+
+/// Runs lint rules and applies settings and suppressions
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    settings: LintSettings,
+}
+
+impl Linter {
+    /// Linter with the built-in rules
+    pub fn new(settings: LintSettings) -> Self {
+        Self::with_rules(default_rules(), settings)
+    }
+
+    /// Linter with a custom rule set
+    pub fn with_rules(rules: Vec<Box<dyn LintRule>>, settings: LintSettings) -> Self {
+        Self { rules, settings }
+    }
 
-    // Validate each shortcut
-    for shortcut in &config.shortcuts {
-        let shortcut_result = validate_shortcut(shortcut);
-        result.errors.extend(shortcut_result.errors);
-        result.warnings.extend(shortcut_result.warnings);
-    }
-
-    // Use config's own validation for duplicates
-    if let Err(errors) = config.validate() {
-        for error in errors {
-            result.add_error(error);
-        }
-    }
-
-    // Check for common system shortcuts conflicts
-    let system_shortcuts = [
-        ("cmd", "space"),     // Spotlight
-        ("cmd", "tab"),       // App switcher
-        ("cmd", "q"),         // Quit
-        ("cmd + shift", "3"), // Screenshot
-        ("cmd + shift", "4"), // Screenshot selection
-        ("cmd + ctrl", "q"),  // Lock screen
-    ];
-
-    for shortcut in &config.shortcuts {
-        let mods = shortcut.modifiers.join(" + ");
-        for (sys_mod, sys_key) in &system_shortcuts {
-            if mods == *sys_mod && &shortcut.key == sys_key {
-                result.add_warning(format!(
-                    "Shortcut {} - {} conflicts with system shortcut",
-                    mods, shortcut.key
+    /// Rules with their effective configuration
+    pub fn rules(&self) -> Vec<LintRuleInfo> {
+        self.rules
+            .iter()
+            .map(|rule| LintRuleInfo {
+                code: rule.code(),
+                rule: rule.code().name().to_string(),
+                description: rule.description().to_string(),
+                default_severity: rule.default_severity(),
+                level: self.settings.level(rule.code()),
+            })
+            .collect()
+    }
+
+    /// Lint a whole configuration
+    pub fn lint_config(&self, ctx: &LintContext) -> ValidationResult {
+        let mut result = ValidationResult::new();
+
+        // Settings naming unknown rules would otherwise silently do nothing
+        for name in self.settings.rules.keys() {
+            if DiagnosticCode::from_name(name).is_none() {
+                result.add(Diagnostic::warning(
+                    DiagnosticCode::InvalidLintSettings,
+                    format!("Unknown lint rule in {}: {}", LINT_SETTINGS_FILE, name),
                 ));
             }
         }
+
+        for rule in &self.rules {
+            self.apply(rule.as_ref(), rule.check_config(ctx), ctx, &mut result);
+        }
+
+        // Present findings in file order (stable, so rule order breaks ties)
+        result
+            .diagnostics
+            .sort_by_key(|d| d.span.map_or(0, |span| span.line));
+        result
+    }
+
+    /// Lint a single shortcut, without checks that need the rest of the configuration
+    pub fn lint_shortcut(&self, shortcut: &Shortcut, ctx: &LintContext) -> ValidationResult {
+        let mut result = ValidationResult::new();
+        for rule in &self.rules {
+            self.apply(
+                rule.as_ref(),
+                rule.check_shortcut(shortcut, ctx),
+                ctx,
+                &mut result,
+            );
+        }
+        result
+    }
+
+    /// Apply the configured level (or default severity) and `@allow` suppressions
+    /// to a rule's diagnostics
+    fn apply(
+        &self,
+        rule: &dyn LintRule,
+        diagnostics: Vec<Diagnostic>,
+        ctx: &LintContext,
+        result: &mut ValidationResult,
+    ) {
+        let level = self.settings.level(rule.code());
+        if level == Some(RuleLevel::Off) {
+            return;
+        }
+        let severity = match level {
+            Some(level) => level.severity(),
+            None if rule.keeps_own_severity() => None,
+            None => Some(rule.default_severity()),
+        };
+
+        for mut diagnostic in diagnostics {
+            let suppressed = diagnostic
+                .shortcut_id
+                .as_deref()
+                .and_then(|id| ctx.config.find_shortcut(id))
+                .is_some_and(|shortcut| shortcut.allows(rule.code()));
+            if suppressed {
+                continue;
+            }
+
+            if let Some(severity) = severity {
+                diagnostic.severity = severity;
+            }
+            result.add(diagnostic);
+        }
     }
+}
 
-    // Update validity flag
-    result.is_valid = result.errors.is_empty();
+/// Validate a single shortcut
+///
+/// Runs the per-shortcut rules with default settings:
+/// - Key and command are not empty, modifiers are valid and not repeated
+/// - No conflict with macOS system shortcuts
+/// - Modifiers present, command not overly long
+/// - Programs and `open -a` applications in the command exist
+///
+/// System shortcuts include the user's overrides in `config_dir`.
+pub fn validate_shortcut(shortcut: &Shortcut, config_dir: &Path) -> ValidationResult {
+    validate_shortcut_using(shortcut, &load_system_shortcuts(config_dir))
+}
+
+/// Validate a single shortcut against a given system shortcut database
+pub fn validate_shortcut_using(shortcut: &Shortcut, system: &SystemShortcutDb) -> ValidationResult {
+    let mut config = ConfigFile::new(String::new());
+    config.add_shortcut(shortcut.clone());
+    let environment = CommandEnvironment::cached(None);
+    let ctx = LintContext {
+        config: &config,
+        system,
+        environment: &environment,
+    };
+
+    Linter::new(LintSettings::default()).lint_shortcut(shortcut, &ctx)
+}
 
+/// Validate an entire configuration file
+///
+/// Runs every lint rule with the settings file next to the config file, if any.
+/// An unreadable settings file is reported and the defaults are used instead.
+/// Commands are resolved against the settings' `path`, or `$PATH`. System
+/// shortcuts include the user's overrides in `config_dir`.
+pub fn validate_config(config: &ConfigFile, config_dir: &Path) -> ValidationResult {
+    validate_config_using(config, &load_system_shortcuts(config_dir))
+}
+
+/// Validate an entire configuration file against a given system shortcut database
+pub fn validate_config_using(config: &ConfigFile, system: &SystemShortcutDb) -> ValidationResult {
+    let (settings, settings_error) = match LintSettings::for_config(config) {
+        Ok(settings) => (settings, None),
+        Err(e) => (LintSettings::default(), Some(e)),
+    };
+    let environment = CommandEnvironment::cached(settings.path.as_deref());
+
+    let mut result = validate_config_with(config, system, &environment, settings);
+    if let Some(e) = settings_error {
+        result.add(Diagnostic::error(DiagnosticCode::InvalidLintSettings, e));
+    }
     result
 }
 
+/// Validate an entire configuration file with explicit system shortcuts,
+/// command environment and settings
+pub fn validate_config_with(
+    config: &ConfigFile,
+    system: &SystemShortcutDb,
+    environment: &CommandEnvironment,
+    settings: LintSettings,
+) -> ValidationResult {
+    let ctx = LintContext {
+        config,
+        system,
+        environment,
+    };
+    Linter::new(settings).lint_config(&ctx)
+}
+
 /// Validate skhd configuration text
 ///
-/// Parses the text and validates the resulting configuration
-pub fn validate_config_text(text: &str) -> ValidationResult {
+/// Parses the text and validates the resulting configuration. Since the source
+/// text is known, diagnostic spans are narrowed to the key chord of each binding.
+pub fn validate_config_text(text: &str, config_dir: &Path) -> ValidationResult {
+    validate_config_text_with(text, &LintSettings::default(), config_dir)
+}
+
+/// Validate skhd configuration text with explicit settings
+///
+/// With the `skhd` parser, the installed skhd checks the syntax and its errors
+/// replace the built-in parser's. When skhd is missing or fails to run, the
+/// built-in parser is used.
+pub fn validate_config_text_with(
+    text: &str,
+    settings: &LintSettings,
+    config_dir: &Path,
+) -> ValidationResult {
+    validate_config_text_using(text, settings, &load_system_shortcuts(config_dir))
+}
+
+/// Validate skhd configuration text with explicit settings and system shortcut database
+pub fn validate_config_text_using(
+    text: &str,
+    settings: &LintSettings,
+    system: &SystemShortcutDb,
+) -> ValidationResult {
     let mut result = ValidationResult::new();
+    let lines: Vec<&str> = text.lines().collect();
+    let line_length = |line_number: usize| {
+        lines
+            .get(line_number.saturating_sub(1))
+            .map_or(0, |l| l.chars().count())
+    };
+
+    let skhd_errors = match settings.parser {
+        ParserBackend::Builtin => None,
+        ParserBackend::Skhd => SkhdCheck::find(settings.path.as_deref(), settings.skhd_timeout())
+            .and_then(|check| check.run(text).ok()),
+    };
 
     // Try to parse
     match parse_config(text) {
         Ok(parsed) => {
             // Convert to ConfigFile
-            let mut config = ConfigFile::new(String::from("<text>"));
-            for parsed_shortcut in parsed.shortcuts() {
-                let shortcut = Shortcut::new(
-                    parsed_shortcut.modifiers.clone(),
-                    parsed_shortcut.key.clone(),
-                    parsed_shortcut.command.clone(),
-                    parsed_shortcut.line_number,
-                );
-                config.add_shortcut(shortcut);
-            }
+            let config = parsed.to_config_file(String::from("<text>"));
 
             // Validate config
-            let config_result = validate_config(&config);
-            result.errors.extend(config_result.errors);
-            result.warnings.extend(config_result.warnings);
+            let lint = validate_config_with(
+                &config,
+                system,
+                &CommandEnvironment::cached(settings.path.as_deref()),
+                settings.clone(),
+            );
+            for mut diagnostic in lint.diagnostics {
+                // Directive comments have no chord to narrow to
+                if diagnostic.code != DiagnosticCode::DanglingDirective {
+                    diagnostic.span = diagnostic.span.map(|span| chord_span(span, &lines));
+                }
+                for related in &mut diagnostic.related {
+                    related.span = chord_span(related.span, &lines);
+                }
+                result.add(diagnostic);
+            }
         }
+        // skhd's verdict on the syntax takes precedence
+        Err(_) if skhd_errors.is_some() => {}
         Err(parse_errors) => {
             for error in parse_errors {
-                result.add_error(format!("Line {}: {}", error.line_number, error.message));
+                let span = match error.column {
+                    Some(column) => Span::columns(
+                        error.line_number,
+                        column,
+                        line_length(error.line_number) + 1,
+                    ),
+                    None => Span::line(error.line_number),
+                };
+                result.add(
+                    Diagnostic::error(
+                        DiagnosticCode::ParseError,
+                        format!("Line {}: {}", error.line_number, error.message),
+                    )
+                    .with_span(span),
+                );
             }
         }
     }
 
-    result.is_valid = result.errors.is_empty();
+    for error in skhd_errors.unwrap_or_default() {
+        let span = Span::columns(
+            error.line_number,
+            error.column.max(1),
+            line_length(error.line_number) + 1,
+        );
+        result.add(
+            Diagnostic::error(
+                DiagnosticCode::SkhdError,
+                format!("Line {}: skhd: {}", error.line_number, error.message),
+            )
+            .with_span(span),
+        );
+    }
+
     result
 }
 
+/// Narrow a whole-line span to the key chord (`cmd + shift - f`) on that line
+fn chord_span(span: Span, lines: &[&str]) -> Span {
+    if span != Span::line(span.line) {
+        return span;
+    }
+
+    let Some(line) = lines.get(span.line.saturating_sub(1)) else {
+        return span;
+    };
+
+    // The chord ends at the first ':' after the '-' separating the key
+    let chord_end = match line.find('-') {
+        Some(dash) => line[dash + 1..]
+            .find(':')
+            .map(|colon| dash + 1 + colon)
+            .unwrap_or(line.len()),
+        None => line.find(':').unwrap_or(line.len()),
+    };
+    let chord = &line[..chord_end];
+    let start = chord.len() - chord.trim_start().len();
+    let end = chord.trim_end().len();
+    if start >= end {
+        return span;
+    }
+
+    Span::columns(
+        span.line,
+        line[..start].chars().count() + 1,
+        line[..end].chars().count() + 1,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::skhd_check::fake_skhd;
+
+    /// Built-in system shortcuts, unaffected by the user's overrides
+    fn embedded_system() -> SystemShortcutDb {
+        SystemShortcutDb::embedded().unwrap()
+    }
 
     #[test]
     fn test_validate_valid_shortcut() {
@@ -164,9 +1152,9 @@ mod tests {
             1,
         );
 
-        let result = validate_shortcut(&shortcut);
+        let result = validate_shortcut_using(&shortcut, &embedded_system());
         assert!(result.is_valid);
-        assert_eq!(result.errors.len(), 0);
+        assert_eq!(result.errors().count(), 0);
     }
 
     #[test]
@@ -178,9 +1166,9 @@ mod tests {
             1,
         );
 
-        let result = validate_shortcut(&shortcut);
+        let result = validate_shortcut_using(&shortcut, &embedded_system());
         assert!(!result.is_valid);
-        assert!(result.errors.len() > 0);
+        assert!(result.errors().count() > 0);
     }
 
     #[test]
@@ -204,25 +1192,88 @@ mod tests {
         config.add_shortcut(s1);
         config.add_shortcut(s2);
 
-        let result = validate_config(&config);
+        let result = validate_config_using(&config, &embedded_system());
         assert!(!result.is_valid);
-        assert!(result.errors.iter().any(|e| e.contains("Duplicate")));
+        assert!(result.errors().any(|e| e.message.contains("Duplicate")));
+
+        let duplicate = result
+            .diagnostics
+            .iter()
+            .find(|d| d.code == DiagnosticCode::DuplicateBinding)
+            .unwrap();
+        assert_eq!(duplicate.span, Some(Span::line(2)));
+        assert_eq!(duplicate.related[0].span, Span::line(1));
+        assert_eq!(duplicate.fixes.len(), 2);
     }
 
     #[test]
     fn test_validate_config_text() {
         let text = "cmd - return : open -a Terminal\n";
-        let result = validate_config_text(text);
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
         assert!(result.is_valid);
     }
 
     #[test]
     fn test_validate_config_text_invalid() {
         let text = "invalid syntax here\n";
-        let result = validate_config_text(text);
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
         assert!(!result.is_valid);
     }
 
+    #[test]
+    fn test_validate_config_text_dangling_directive() {
+        let text = "# @tags work\n# Terminal\ncmd - return : open -a Terminal\n";
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
+        assert!(result.is_valid);
+        let dangling: Vec<&Diagnostic> = result
+            .warnings()
+            .filter(|d| d.code == DiagnosticCode::DanglingDirective)
+            .collect();
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].span, Some(Span::line(1)));
+
+        let mut settings = LintSettings::default();
+        settings
+            .rules
+            .insert("dangling-directive".to_string(), RuleLevel::Off);
+        let result = validate_config_text_using(text, &settings, &embedded_system());
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.code != DiagnosticCode::DanglingDirective));
+    }
+
+    #[test]
+    fn test_dangling_directive_rule() {
+        let config = parse_config("cmd - a : echo a\n# @allow no-modifiers\n")
+            .unwrap()
+            .to_config_file("/test/skhdrc".to_string());
+        let system = embedded_system();
+        let environment = CommandEnvironment::default();
+
+        // Loaded configs report it like any other rule
+        let result = validate_config_with(&config, &system, &environment, LintSettings::default());
+        let dangling: Vec<&Diagnostic> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == DiagnosticCode::DanglingDirective)
+            .collect();
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].span, Some(Span::line(2)));
+
+        let mut settings = LintSettings::default();
+        settings
+            .rules
+            .insert("SKHD017".to_string(), RuleLevel::Error);
+        let result = validate_config_with(&config, &system, &environment, settings);
+        assert!(!result.is_valid);
+
+        // and it is listed with the other rules
+        assert!(default_rules()
+            .iter()
+            .any(|rule| rule.code() == DiagnosticCode::DanglingDirective));
+    }
+
     #[test]
     fn test_system_shortcut_warning() {
         let mut config = ConfigFile::new("/test/path".to_string());
@@ -237,10 +1288,360 @@ mod tests {
 
         config.add_shortcut(shortcut);
 
-        let result = validate_config(&config);
+        let result = validate_config_using(&config, &embedded_system());
+        assert!(result
+            .warnings()
+            .any(|w| w.code == DiagnosticCode::SystemShortcutConflict
+                && w.message.contains("system shortcut")));
+    }
+
+    #[test]
+    fn test_validate_config_text_spans() {
+        let text = "# comment\n  cmd - f : echo one\ncmd - f : echo two\n";
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
+
+        let duplicate = result
+            .diagnostics
+            .iter()
+            .find(|d| d.code == DiagnosticCode::DuplicateBinding)
+            .unwrap();
+        assert_eq!(duplicate.span, Some(Span::columns(3, 1, 8)));
+        assert_eq!(duplicate.related[0].span, Span::columns(2, 3, 10));
+        // Fixes keep whole-line edits
+        assert_eq!(duplicate.fixes[0].edits[0].span, Span::line(3));
+
+        let result = validate_config_text_using(
+            "invalid syntax here\n",
+            &LintSettings::default(),
+            &embedded_system(),
+        );
+        assert_eq!(result.diagnostics[0].code, DiagnosticCode::ParseError);
+        assert_eq!(result.diagnostics[0].span.unwrap().line, 1);
+    }
+
+    #[test]
+    fn test_system_shortcut_any_modifier_order() {
+        let system = SystemShortcutDb::embedded().unwrap();
+        let environment = CommandEnvironment::default();
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(Shortcut::new(
+            vec!["shift".to_string(), "cmd".to_string()],
+            "4".to_string(),
+            "my_command".to_string(),
+            1,
+        ));
+        config.add_shortcut(Shortcut::new(
+            vec!["ctrl".to_string(), "cmd".to_string()],
+            "q".to_string(),
+            "my_command".to_string(),
+            2,
+        ));
+
+        let result = validate_config_with(&config, &system, &environment, LintSettings::default());
+        let conflicts: Vec<&Diagnostic> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == DiagnosticCode::SystemShortcutConflict)
+            .collect();
+
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts[0].message.contains("Screenshot"));
+        // Built-in conflicts only warn, so existing configs stay valid
+        assert_eq!(conflicts[1].severity, Severity::Warning);
+        assert!(result.is_valid);
+    }
+
+    fn bare_shortcut(key: &str, line: usize) -> Shortcut {
+        Shortcut::new(vec![], key.to_string(), "echo".to_string(), line)
+    }
+
+    fn settings(rules: &[(&str, RuleLevel)]) -> LintSettings {
+        LintSettings {
+            rules: rules
+                .iter()
+                .map(|(name, level)| (name.to_string(), *level))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_levels() {
+        let system = SystemShortcutDb::default();
+        let environment = CommandEnvironment::default();
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(bare_shortcut("f1", 1));
+
+        let result = validate_config_with(&config, &system, &environment, LintSettings::default());
+        assert!(result.is_valid);
+        assert_eq!(result.warnings().count(), 1);
+
+        // Promote to error, as a pre-commit check would
+        let result = validate_config_with(
+            &config,
+            &system,
+            &environment,
+            settings(&[("no-modifiers", RuleLevel::Error)]),
+        );
+        assert!(!result.is_valid);
+        assert_eq!(
+            result.errors().next().unwrap().code,
+            DiagnosticCode::NoModifiers
+        );
+
+        let result = validate_config_with(
+            &config,
+            &system,
+            &environment,
+            settings(&[("SKHD003", RuleLevel::Off)]),
+        );
+        assert!(result.diagnostics.is_empty());
+
+        let result = validate_config_with(
+            &config,
+            &system,
+            &environment,
+            settings(&[("no-modifier", RuleLevel::Off)]),
+        );
+        assert_eq!(
+            result.diagnostics[0].code,
+            DiagnosticCode::InvalidLintSettings
+        );
+    }
+
+    #[test]
+    fn test_allow_directive_suppresses_rule() {
+        let text = "# @allow no-modifiers\n- f1 : echo one\n- f2 : echo two\n";
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
+
+        let lines: Vec<usize> = result
+            .warnings()
+            .filter(|d| d.code == DiagnosticCode::NoModifiers)
+            .map(|d| d.span.unwrap().line)
+            .collect();
+        assert_eq!(lines, vec![3]);
+    }
+
+    #[test]
+    fn test_settings_file_next_to_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(LINT_SETTINGS_FILE),
+            r#"{ "rules": { "long-command": "error" } }"#,
+        )
+        .unwrap();
+
+        let mut config = ConfigFile::new(dir.path().join("skhdrc").to_string_lossy().to_string());
+        let mut shortcut = bare_shortcut("a", 1);
+        shortcut.modifiers = vec!["alt".to_string()];
+        shortcut.command = "x".repeat(MAX_COMMAND_LENGTH + 1);
+        config.add_shortcut(shortcut);
+
+        let result = validate_config_using(&config, &embedded_system());
+        assert!(!result.is_valid);
+        assert_eq!(
+            result.errors().next().unwrap().code,
+            DiagnosticCode::LongCommand
+        );
+
+        std::fs::write(dir.path().join(LINT_SETTINGS_FILE), "not json").unwrap();
+        let result = validate_config_using(&config, &embedded_system());
         assert!(result
-            .warnings
+            .errors()
+            .any(|d| d.code == DiagnosticCode::InvalidLintSettings));
+    }
+
+    #[test]
+    fn test_missing_command_rules() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let bin = dir.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        let open = bin.join("open");
+        std::fs::write(&open, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&open, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+        let environment = CommandEnvironment::new(vec![bin], Some(Vec::new()));
+
+        let system = SystemShortcutDb::default();
+        let mut config = ConfigFile::new("/test/path".to_string());
+        let mut shortcut = bare_shortcut("a", 4);
+        shortcut.modifiers = vec!["alt".to_string()];
+        shortcut.command = "yabai -m space --focus 1 || open -a Terminal".to_string();
+        config.add_shortcut(shortcut);
+
+        let result = validate_config_with(&config, &system, &environment, LintSettings::default());
+        let codes: Vec<DiagnosticCode> = result.diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                DiagnosticCode::MissingExecutable,
+                DiagnosticCode::MissingApplication
+            ]
+        );
+        assert!(result.diagnostics[0].message.contains("yabai"));
+        assert_eq!(result.diagnostics[1].span, Some(Span::line(4)));
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_validate_text_with_skhd() {
+        // Fake skhd rejecting every `cmd - q` binding
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings = LintSettings {
+            parser: ParserBackend::Skhd,
+            path: Some(fake_skhd(dir.path(), "cmd - q", 1, "unknown hotkey")),
+            skhd_timeout_ms: Some(300),
+            ..Default::default()
+        };
+
+        let result = validate_config_text_using(
+            "cmd - a : echo a\ncmd - q : echo q\n",
+            &settings,
+            &embedded_system(),
+        );
+        let skhd: Vec<&Diagnostic> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == DiagnosticCode::SkhdError)
+            .collect();
+        assert_eq!(skhd.len(), 1);
+        assert_eq!(skhd[0].span, Some(Span::columns(2, 1, 17)));
+        assert_eq!(skhd[0].message, "Line 2: skhd: unknown hotkey");
+
+        // skhd accepts what the built-in parser rejects: no parse error
+        let result = validate_config_text_using(
+            "cmd - a : echo a\nnot a binding\n",
+            &settings,
+            &embedded_system(),
+        );
+        assert!(result.is_valid);
+
+        // Without skhd on PATH the built-in parser is used
+        let empty = tempfile::TempDir::new().unwrap();
+        let settings = LintSettings {
+            path: Some(empty.path().to_string_lossy().to_string()),
+            ..settings
+        };
+        let result = validate_config_text_using(
+            "cmd - a : echo a\nnot a binding\n",
+            &settings,
+            &embedded_system(),
+        );
+        assert!(result
+            .errors()
+            .any(|d| d.code == DiagnosticCode::ParseError));
+    }
+
+    #[test]
+    fn test_shadowed_bindings() {
+        let text = "cmd - t : echo global\ncmd - t [\n    \"iTerm2\" : echo term\n]\nfn - left : echo left\n- home : echo home\nhyper - k : echo one\ncmd + alt + shift + ctrl - k : echo two\n";
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
+
+        let shadowed: Vec<&Diagnostic> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == DiagnosticCode::ShadowedBinding)
+            .collect();
+        assert_eq!(shadowed.len(), 2);
+        assert_eq!(shadowed[0].span.unwrap().line, 1);
+        assert_eq!(shadowed[0].related[0].span.line, 3);
+        assert!(shadowed[0].message.contains("while iTerm2 is focused"));
+        assert_eq!(shadowed[1].span.unwrap().line, 5);
+        assert_eq!(shadowed[1].severity, Severity::Warning);
+
+        let alias = result
+            .diagnostics
             .iter()
-            .any(|w| w.contains("system shortcut")));
+            .find(|d| d.code == DiagnosticCode::DuplicateBinding)
+            .unwrap();
+        assert_eq!(alias.span.unwrap().line, 8);
+        assert!(alias
+            .message
+            .contains("both expand to 'cmd + alt + shift + ctrl - k'"));
+    }
+
+    #[test]
+    fn test_mode_graph_rules() {
+        let text = ":: window\n:: orphan\n:: trap\nalt - w ; window\nwindow < - t ; trap\nwindow < - escape ; default\nresize < - h : echo\nalt - r ; resize\n";
+        let result = validate_config_text_using(text, &LintSettings::default(), &embedded_system());
+
+        let found: Vec<(DiagnosticCode, usize)> = result
+            .diagnostics
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.code,
+                    DiagnosticCode::UndeclaredMode
+                        | DiagnosticCode::UnreachableMode
+                        | DiagnosticCode::ModeWithoutExit
+                )
+            })
+            .map(|d| (d.code, d.span.unwrap().line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (DiagnosticCode::UnreachableMode, 2),
+                (DiagnosticCode::ModeWithoutExit, 3),
+                (DiagnosticCode::UndeclaredMode, 7),
+                (DiagnosticCode::UndeclaredMode, 8),
+            ]
+        );
+
+        let trap = result
+            .diagnostics
+            .iter()
+            .find(|d| d.code == DiagnosticCode::ModeWithoutExit)
+            .unwrap();
+        assert_eq!(
+            trap.fixes[0].edits[0].new_text,
+            "trap < - escape ; default\n"
+        );
+        assert_eq!(trap.fixes[0].edits[0].span, Span::point(4, 1));
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        struct NoEchoRule;
+
+        impl LintRule for NoEchoRule {
+            fn code(&self) -> DiagnosticCode {
+                DiagnosticCode::LongCommand
+            }
+
+            fn description(&self) -> &'static str {
+                "No echo"
+            }
+
+            fn default_severity(&self) -> Severity {
+                Severity::Info
+            }
+
+            fn check_shortcut(&self, shortcut: &Shortcut, _ctx: &LintContext) -> Vec<Diagnostic> {
+                if shortcut.command.starts_with("echo") {
+                    vec![Diagnostic::warning(self.code(), "echo")]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+
+        let system = SystemShortcutDb::default();
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(bare_shortcut("f1", 1));
+        let environment = CommandEnvironment::default();
+        let ctx = LintContext {
+            config: &config,
+            system: &system,
+            environment: &environment,
+        };
+
+        let linter = Linter::with_rules(vec![Box::new(NoEchoRule)], LintSettings::default());
+        let result = linter.lint_config(&ctx);
+        assert_eq!(result.diagnostics.len(), 1);
+        // The linter, not the rule, decides the severity
+        assert_eq!(result.diagnostics[0].severity, Severity::Info);
+        assert!(result.is_valid);
+        assert_eq!(linter.rules()[0].default_severity, Severity::Info);
     }
 }
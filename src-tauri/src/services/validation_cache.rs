@@ -0,0 +1,474 @@
+/// Incremental validation of the loaded configuration
+///
+/// Keeps the diagnostics of the last validation together with a key combination
+/// index. After a shortcut is created, updated or deleted, only that shortcut and
+/// the bindings it can collide with (same chord, or its laptop `fn` counterpart)
+/// are linted again; the mode graph is rechecked from the activation bindings alone.
+/// The cache goes stale when the lint settings or system shortcut files change.
+use crate::models::binding_conflict::FN_TRANSLATIONS;
+use crate::models::{ConfigFile, Diagnostic, DiagnosticCode, KeyCombination, Shortcut};
+use crate::services::command_analysis::CommandEnvironment;
+use crate::services::key_index::{KeyIndex, KeyQueryResult};
+use crate::services::system_shortcuts::{load_system_shortcuts, user_file_path, SystemShortcutDb};
+use crate::services::validation::{
+    settings_path, LintContext, LintSettings, Linter, ValidationResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Event carrying a `ValidationDelta` after each shortcut edit
+pub const VALIDATION_EVENT: &str = "validation-changed";
+
+/// Change in diagnostics caused by an edit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationDelta {
+    /// The cache was rebuilt: `added` holds every diagnostic and earlier ones are void
+    pub reset: bool,
+
+    /// Shortcuts whose diagnostics were recomputed (including deleted ones)
+    pub revalidated: Vec<String>,
+
+    pub added: Vec<Diagnostic>,
+    pub removed: Vec<Diagnostic>,
+
+    /// Whether the configuration is free of errors after the edit
+    pub is_valid: bool,
+}
+
+/// Cached diagnostics of a configuration
+pub struct ValidationCache {
+    file_path: String,
+    settings: LintSettings,
+    settings_error: Option<Diagnostic>,
+    system: SystemShortcutDb,
+    environment: CommandEnvironment,
+    index: KeyIndex,
+
+    /// Settings files read by `build`, with their modification times (None if absent)
+    settings_files: Vec<(PathBuf, Option<SystemTime>)>,
+
+    /// Diagnostics attributed to a shortcut, by shortcut ID
+    by_shortcut: HashMap<String, Vec<Diagnostic>>,
+
+    /// Diagnostics not attributed to a shortcut (modes, settings)
+    global: Vec<Diagnostic>,
+}
+
+impl ValidationCache {
+    /// Validate a configuration with its settings file, system shortcuts and `$PATH`
+    ///
+    /// System shortcuts include the user's overrides in `config_dir`.
+    pub fn build(config: &ConfigFile, config_dir: &Path) -> Self {
+        // Stamped before reading, so an edit made while building is not missed
+        let settings_files: Vec<(PathBuf, Option<SystemTime>)> = settings_path(&config.file_path)
+            .into_iter()
+            .chain([user_file_path(config_dir)])
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+
+        let (settings, settings_error) = match LintSettings::for_config(config) {
+            Ok(settings) => (settings, None),
+            Err(e) => (LintSettings::default(), Some(e)),
+        };
+        let environment = CommandEnvironment::cached(settings.path.as_deref());
+
+        let mut cache = Self::build_with(
+            config,
+            load_system_shortcuts(config_dir),
+            environment,
+            settings,
+        );
+        cache.settings_files = settings_files;
+        if let Some(e) = settings_error {
+            let diagnostic = Diagnostic::error(DiagnosticCode::InvalidLintSettings, e);
+            cache.global.push(diagnostic.clone());
+            cache.settings_error = Some(diagnostic);
+        }
+        cache
+    }
+
+    /// Validate a configuration with explicit system shortcuts, environment and settings
+    pub fn build_with(
+        config: &ConfigFile,
+        system: SystemShortcutDb,
+        environment: CommandEnvironment,
+        settings: LintSettings,
+    ) -> Self {
+        let mut cache = Self {
+            file_path: config.file_path.clone(),
+            settings,
+            settings_error: None,
+            system,
+            environment,
+            index: KeyIndex::build(config),
+            settings_files: Vec::new(),
+            by_shortcut: HashMap::new(),
+            global: Vec::new(),
+        };
+
+        for diagnostic in cache.lint(config).diagnostics {
+            match diagnostic.shortcut_id.clone() {
+                Some(id) => cache.by_shortcut.entry(id).or_default().push(diagnostic),
+                None => cache.global.push(diagnostic),
+            }
+        }
+        cache
+    }
+
+    /// Path of the validated configuration
+    pub fn file_path(&self) -> &str {
+        &self.file_path
+    }
+
+    /// Check whether the cache still describes `config`
+    ///
+    /// False for another file, or when a settings file changed since the cache was built.
+    pub fn is_current(&self, config: &ConfigFile) -> bool {
+        self.file_path == config.file_path
+            && self
+                .settings_files
+                .iter()
+                .all(|(path, stamp)| modified(path) == *stamp)
+    }
+
+    /// Bindings for a combination, from the index kept up to date with the edits
+    ///
+    /// `config` must be the configuration the cache describes.
+    pub fn query(&self, combination: &KeyCombination, config: &ConfigFile) -> KeyQueryResult {
+        self.index.query(combination, config)
+    }
+
+    /// All cached diagnostics, in file order
+    pub fn result(&self) -> ValidationResult {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .global
+            .iter()
+            .chain(self.by_shortcut.values().flatten())
+            .cloned()
+            .collect();
+        diagnostics.sort_by(|a, b| {
+            let line = |d: &Diagnostic| d.span.map_or(0, |span| span.line);
+            line(a)
+                .cmp(&line(b))
+                .then_with(|| a.code.code().cmp(b.code.code()))
+                .then_with(|| a.message.cmp(&b.message))
+        });
+
+        let mut result = ValidationResult::new();
+        for diagnostic in diagnostics {
+            result.add(diagnostic);
+        }
+        result
+    }
+
+    /// Delta of a full rebuild: every diagnostic is new
+    pub fn reset_delta(&self) -> ValidationDelta {
+        let result = self.result();
+        ValidationDelta {
+            reset: true,
+            revalidated: self.by_shortcut.keys().cloned().collect(),
+            is_valid: result.is_valid,
+            added: result.diagnostics,
+            removed: Vec::new(),
+        }
+    }
+
+    /// Revalidate after one shortcut changed
+    ///
+    /// `config` is the configuration after the edit; `before` is the shortcut as it
+    /// was (None when created) and `after` as it is now (None when deleted).
+    pub fn apply(
+        &mut self,
+        config: &ConfigFile,
+        before: Option<&Shortcut>,
+        after: Option<&Shortcut>,
+    ) -> ValidationDelta {
+        if let Some(before) = before {
+            self.index.remove(before);
+        }
+        if let Some(after) = after {
+            self.index.insert(after);
+        }
+
+        // The edited shortcut, plus everything bound to a chord it left or joined
+        let mut affected: BTreeSet<String> = before
+            .iter()
+            .chain(after.iter())
+            .map(|s| s.id.clone())
+            .collect();
+        for shortcut in before.iter().chain(after.iter()) {
+            for combination in related_combinations(&KeyCombination::from_shortcut(shortcut)) {
+                affected.extend(self.index.ids_for(&combination).iter().cloned());
+            }
+        }
+
+        // Cross-shortcut rules only need the affected bindings and the declared modes
+        let mut subset = ConfigFile::new(config.file_path.clone());
+        subset.modes = config.modes.clone();
+        subset.shortcuts = config
+            .shortcuts
+            .iter()
+            .filter(|s| affected.contains(&s.id))
+            .cloned()
+            .collect();
+
+        let mut fresh: HashMap<String, Vec<Diagnostic>> = HashMap::new();
+        for diagnostic in self.lint(&subset).diagnostics {
+            if let Some(id) = diagnostic.shortcut_id.clone() {
+                fresh.entry(id).or_default().push(diagnostic);
+            }
+        }
+
+        let mut delta = ValidationDelta {
+            revalidated: affected.iter().cloned().collect(),
+            ..Default::default()
+        };
+        for id in &affected {
+            let old = self.by_shortcut.remove(id).unwrap_or_default();
+            let new = fresh.remove(id).unwrap_or_default();
+            diff(&old, &new, &mut delta);
+            if !new.is_empty() {
+                self.by_shortcut.insert(id.clone(), new);
+            }
+        }
+
+        let global = self.lint_global(config);
+        diff(&self.global, &global, &mut delta);
+        self.global = global;
+
+        delta.is_valid = self.result().is_valid;
+        delta
+    }
+
+    /// Diagnostics not attributed to a shortcut
+    ///
+    /// Mode reachability only depends on the mode declarations and activations.
+    fn lint_global(&self, config: &ConfigFile) -> Vec<Diagnostic> {
+        let mut graph = ConfigFile::new(config.file_path.clone());
+        graph.modes = config.modes.clone();
+        graph.shortcuts = config
+            .shortcuts
+            .iter()
+            .filter(|s| s.activates.is_some())
+            .cloned()
+            .collect();
+
+        self.settings_error
+            .iter()
+            .cloned()
+            .chain(
+                self.lint(&graph)
+                    .diagnostics
+                    .into_iter()
+                    .filter(|d| d.shortcut_id.is_none()),
+            )
+            .collect()
+    }
+
+    fn lint(&self, config: &ConfigFile) -> ValidationResult {
+        let ctx = LintContext {
+            config,
+            system: &self.system,
+            environment: &self.environment,
+        };
+        Linter::new(self.settings.clone()).lint_config(&ctx)
+    }
+}
+
+/// A combination plus the chord laptop keyboards confuse it with
+fn related_combinations(combination: &KeyCombination) -> Vec<KeyCombination> {
+    let mut related = vec![combination.clone()];
+    let has_fn = combination.modifiers.iter().any(|m| m == "fn");
+
+    for (key, sent) in FN_TRANSLATIONS {
+        if has_fn && combination.key == key {
+            let modifiers: Vec<String> = combination
+                .modifiers
+                .iter()
+                .filter(|m| *m != "fn")
+                .cloned()
+                .collect();
+            related.push(KeyCombination::new(&modifiers, sent));
+        } else if !has_fn && combination.key == sent {
+            let mut modifiers = combination.modifiers.clone();
+            modifiers.push("fn".to_string());
+            related.push(KeyCombination::new(&modifiers, key));
+        }
+    }
+    related
+}
+
+/// Record diagnostics present only in `old` as removed and only in `new` as added
+/// Modification time of a file, None if it does not exist
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn diff(old: &[Diagnostic], new: &[Diagnostic], delta: &mut ValidationDelta) {
+    delta
+        .removed
+        .extend(old.iter().filter(|d| !new.contains(d)).cloned());
+    delta
+        .added
+        .extend(new.iter().filter(|d| !old.contains(d)).cloned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModeDeclaration;
+    use crate::services::validation::validate_config_with;
+
+    fn shortcut(modifiers: &[&str], key: &str, line: usize) -> Shortcut {
+        Shortcut::new(
+            modifiers.iter().map(|m| m.to_string()).collect(),
+            key.to_string(),
+            format!("echo {}", line),
+            line,
+        )
+    }
+
+    fn cache(config: &ConfigFile) -> ValidationCache {
+        ValidationCache::build_with(
+            config,
+            SystemShortcutDb::embedded().unwrap(),
+            CommandEnvironment::default(),
+            LintSettings::default(),
+        )
+    }
+
+    /// Full validation of the same configuration, for comparison
+    fn full(config: &ConfigFile) -> Vec<Diagnostic> {
+        let mut diagnostics = validate_config_with(
+            config,
+            &SystemShortcutDb::embedded().unwrap(),
+            &CommandEnvironment::default(),
+            LintSettings::default(),
+        )
+        .diagnostics;
+        diagnostics.sort_by(|a, b| format!("{:?}", a).cmp(&format!("{:?}", b)));
+        diagnostics
+    }
+
+    fn cached(cache: &ValidationCache) -> Vec<Diagnostic> {
+        let mut diagnostics = cache.result().diagnostics;
+        diagnostics.sort_by(|a, b| format!("{:?}", a).cmp(&format!("{:?}", b)));
+        diagnostics
+    }
+
+    #[test]
+    fn test_incremental_edits_match_full_validation() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.add_shortcut(shortcut(&["alt"], "a", 1));
+        config.add_shortcut(shortcut(&["alt"], "b", 2));
+        config.add_shortcut(shortcut(&[], "home", 3));
+        let mut cache = cache(&config);
+        assert!(cache.result().is_valid);
+
+        // Create a duplicate of line 1: only the two bindings on that chord are linted
+        let created = shortcut(&["alt"], "a", 4);
+        config.add_shortcut(created.clone());
+        let delta = cache.apply(&config, None, Some(&created));
+        assert!(!delta.reset);
+        assert_eq!(delta.revalidated.len(), 2);
+        assert!(delta.revalidated.contains(&config.shortcuts[0].id));
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0].code, DiagnosticCode::DuplicateBinding);
+        assert!(delta.removed.is_empty());
+        assert!(!delta.is_valid);
+        assert_eq!(cached(&cache), full(&config));
+
+        // Move it to `fn - left`, shadowed by `- home` on laptops
+        let mut updated = created.clone();
+        updated.modifiers = vec!["fn".to_string()];
+        updated.key = "left".to_string();
+        config.update_shortcut(updated.clone());
+        let delta = cache.apply(&config, Some(&created), Some(&updated));
+        assert_eq!(delta.removed.len(), 1);
+        assert!(delta
+            .added
+            .iter()
+            .any(|d| d.code == DiagnosticCode::ShadowedBinding));
+        assert!(delta.is_valid);
+        assert_eq!(cached(&cache), full(&config));
+
+        // The key index follows the edits
+        let left = KeyCombination::parse("fn - left").unwrap();
+        assert_eq!(cache.query(&left, &config).exact, vec![updated.clone()]);
+        let alt_a = KeyCombination::parse("alt - a").unwrap();
+        assert_eq!(cache.query(&alt_a, &config).exact.len(), 1);
+
+        // Delete it again
+        config.remove_shortcut(&updated.id);
+        let delta = cache.apply(&config, Some(&updated), None);
+        assert!(delta.added.is_empty());
+        assert!(delta
+            .removed
+            .iter()
+            .any(|d| d.code == DiagnosticCode::ShadowedBinding));
+        assert_eq!(cached(&cache), full(&config));
+        assert!(cache.query(&left, &config).exact.is_empty());
+    }
+
+    #[test]
+    fn test_mode_diagnostics_follow_activations() {
+        let mut config = ConfigFile::new("/test/path".to_string());
+        config.modes.push(ModeDeclaration {
+            name: "window".to_string(),
+            capture: false,
+            command: None,
+            line_number: 1,
+        });
+        let mut cache = cache(&config);
+        assert_eq!(cache.result().diagnostics.len(), 1);
+        assert_eq!(
+            cache.result().diagnostics[0].code,
+            DiagnosticCode::UnreachableMode
+        );
+
+        let mut enter = shortcut(&["alt"], "w", 2);
+        enter.command = String::new();
+        enter.activates = Some("window".to_string());
+        config.add_shortcut(enter.clone());
+        let delta = cache.apply(&config, None, Some(&enter));
+
+        assert_eq!(delta.removed[0].code, DiagnosticCode::UnreachableMode);
+        assert!(delta
+            .added
+            .iter()
+            .any(|d| d.code == DiagnosticCode::ModeWithoutExit));
+        assert_eq!(cached(&cache), full(&config));
+    }
+
+    #[test]
+    fn test_settings_changes_make_cache_stale() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = ConfigFile::new(dir.path().join("skhdrc").to_string_lossy().to_string());
+
+        let cache = ValidationCache::build(&config, dir.path());
+        assert!(cache.is_current(&config));
+        assert!(!cache.is_current(&ConfigFile::new("/other/skhdrc".to_string())));
+
+        std::fs::write(
+            dir.path()
+                .join(crate::services::validation::LINT_SETTINGS_FILE),
+            r#"{ "rules": { "dangerous-command": "off" } }"#,
+        )
+        .unwrap();
+        assert!(!cache.is_current(&config));
+        assert!(ValidationCache::build(&config, dir.path()).is_current(&config));
+
+        std::fs::write(
+            dir.path()
+                .join(crate::services::system_shortcuts::USER_SYSTEM_SHORTCUTS_FILE),
+            "{}",
+        )
+        .unwrap();
+        assert!(!cache.is_current(&config));
+    }
+}
@@ -67,11 +67,6 @@ pub fn get_config_dir() -> PathBuf {
     expand_path("~/.config/skhd")
 }
 
-/// Get the directory for application backups
-pub fn get_backup_dir() -> PathBuf {
-    expand_path("~/.config/skhd/backups")
-}
-
 /// Validate that a path is within the allowed skhd config directory
 pub fn is_valid_config_path<P: AsRef<Path>>(path: P) -> bool {
     let expanded = expand_path(path);
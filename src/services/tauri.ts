@@ -4,14 +4,39 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   ConfigFile,
   Shortcut,
   CreateShortcutRequest,
   UpdateShortcutRequest,
+  DuplicateShortcutRequest,
   ValidationResult,
+  ValidationDelta,
   Backup,
+  RetentionPolicy,
+  HistorySettings,
+  AppSettings,
+  ImportResult,
+  AuditReport,
+  BackupIssueKind,
+  QuarantineResult,
+  ConfigDiff,
+  BackupShortcut,
+  RestoreShortcutsRequest,
+  ShortcutRestoreResult,
   TestResult,
+  TagSummary,
+  KeyQueryResult,
+  FreeKeyRequest,
+  FreeKeySuggestion,
+  KeyboardLayout,
+  KeyDescription,
+  LayoutWarning,
+  SystemShortcutCatalog,
+  LintRuleInfo,
+  ShortcutSyntaxError,
+  CommandSafety,
 } from '../types';
 
 /**
@@ -101,6 +126,79 @@ export async function deleteShortcut(id: string): Promise<void> {
   return invoke('delete_shortcut', { id });
 }
 
+/**
+ * Duplicate a shortcut into another mode or modifier set
+ * The copy is inserted directly below the original
+ * @param request Original shortcut ID and target mode/modifiers/key
+ */
+export async function duplicateShortcut(request: DuplicateShortcutRequest): Promise<Shortcut> {
+  return invoke<Shortcut>('duplicate_shortcut', { request });
+}
+
+/**
+ * Tag Commands
+ */
+
+/**
+ * List all tags used in the current configuration with usage counts
+ */
+export async function listTags(): Promise<TagSummary[]> {
+  return invoke<TagSummary[]>('list_tags');
+}
+
+/**
+ * Filter shortcuts by tag
+ * @param tags Tags to filter by (leading @ optional)
+ * @param matchAll Require every tag instead of any one
+ */
+export async function filterShortcutsByTags(tags: string[], matchAll = false): Promise<Shortcut[]> {
+  return invoke<Shortcut[]>('filter_shortcuts_by_tags', { tags, matchAll });
+}
+
+/**
+ * Find every binding for a key chord, plus near-matches differing by one modifier
+ * @param chord Key chord such as "shift + cmd - f" or "hyper - space"
+ */
+export async function findKeyBindings(chord: string): Promise<KeyQueryResult> {
+  return invoke<KeyQueryResult>('find_key_bindings', { chord });
+}
+
+/**
+ * Suggest unused key combinations, best first
+ * @param request Acceptable modifier sets, optional mode, command and limit
+ */
+export async function suggestFreeKeys(request: FreeKeyRequest): Promise<FreeKeySuggestion[]> {
+  return invoke<FreeKeySuggestion[]>('suggest_free_keys', { request });
+}
+
+/**
+ * Keyboard Layout Commands
+ */
+
+/**
+ * Get all known keyboard layouts
+ */
+export async function getKeyboardLayouts(): Promise<KeyboardLayout[]> {
+  return invoke<KeyboardLayout[]>('get_keyboard_layouts');
+}
+
+/**
+ * Describe what a key means on each keyboard layout
+ * @param key Key as written in the config
+ * @param activeLayout ID of the layout skhd runs with
+ */
+export async function describeKeyOnLayouts(key: string, activeLayout: string): Promise<KeyDescription> {
+  return invoke<KeyDescription>('describe_key_on_layouts', { key, activeLayout });
+}
+
+/**
+ * Find shortcuts whose key does not exist on a layout
+ * @param layoutId ID of the layout to check against
+ */
+export async function checkKeyboardLayout(layoutId: string): Promise<LayoutWarning[]> {
+  return invoke<LayoutWarning[]>('check_keyboard_layout', { layoutId });
+}
+
 /**
  * Validation Commands
  */
@@ -121,6 +219,57 @@ export async function validateConfig(config: ConfigFile): Promise<ValidationResu
   return invoke<ValidationResult>('validate_config', { config });
 }
 
+/**
+ * Validate configuration text with the loaded config's lint settings
+ * (uses the installed skhd when the settings select `"parser": "skhd"`)
+ * @param text skhd configuration text
+ */
+export async function validateConfigText(text: string): Promise<ValidationResult> {
+  return invoke<ValidationResult>('validate_config_text', { text });
+}
+
+/**
+ * Get the cached diagnostics of the loaded configuration
+ */
+export async function getConfigDiagnostics(): Promise<ValidationResult> {
+  return invoke<ValidationResult>('get_config_diagnostics');
+}
+
+/**
+ * Listen for diagnostics changed by shortcut edits
+ * @param callback Called with the diagnostics added and removed by each edit
+ * @returns Function to call to stop listening
+ */
+export async function onValidationChanged(
+  callback: (delta: ValidationDelta) => void
+): Promise<UnlistenFn> {
+  return listen<ValidationDelta>('validation-changed', (event) => {
+    callback(event.payload);
+  });
+}
+
+/**
+ * Check the shell syntax of every command in a configuration, without spawning a shell
+ * @param config Configuration to check
+ */
+export async function checkConfigSyntax(config: ConfigFile): Promise<ShortcutSyntaxError[]> {
+  return invoke<ShortcutSyntaxError[]>('check_config_syntax', { config });
+}
+
+/**
+ * Get the system shortcut conflict database, including user entries
+ */
+export async function getSystemShortcuts(): Promise<SystemShortcutCatalog> {
+  return invoke<SystemShortcutCatalog>('get_system_shortcuts');
+}
+
+/**
+ * List lint rules with their levels from the project's settings file
+ */
+export async function getLintRules(): Promise<LintRuleInfo[]> {
+  return invoke<LintRuleInfo[]>('get_lint_rules');
+}
+
 /**
  * Backup Commands
  */
@@ -134,7 +283,8 @@ export async function createBackup(description?: string): Promise<Backup> {
 }
 
 /**
- * List all available backups
+ * List the backups of the loaded configuration, newest first
+ * (all backups when no configuration is loaded)
  */
 export async function listBackups(): Promise<Backup[]> {
   return invoke<Backup[]>('list_backups');
@@ -142,10 +292,115 @@ export async function listBackups(): Promise<Backup[]> {
 
 /**
  * Restore configuration from a backup
- * @param backupPath Path to backup file
+ * @param backupId ID of the backup
+ */
+export async function restoreBackup(backupId: string): Promise<void> {
+  return invoke('restore_backup', { backupId });
+}
+
+/**
+ * Diff two versions of the loaded configuration
+ * @param oldId Backup to diff from (omit for the live config file)
+ * @param newId Backup to diff to (omit for the live config file)
+ * @example Preview a restore: `diffBackups(undefined, backup.id)`
+ */
+export async function diffBackups(oldId?: string, newId?: string): Promise<ConfigDiff> {
+  return invoke<ConfigDiff>('diff_backups', { oldId, newId });
+}
+
+/**
+ * List the bindings of a backup, compared with the loaded configuration
+ * @param backupId ID of the backup
+ */
+export async function getBackupShortcuts(backupId: string): Promise<BackupShortcut[]> {
+  return invoke<BackupShortcut[]>('get_backup_shortcuts', { backupId });
+}
+
+/**
+ * Restore selected bindings of a backup into the loaded configuration (unsaved)
+ * @param request Backup, selected lines and conflict resolutions
  */
-export async function restoreBackup(backupPath: string): Promise<void> {
-  return invoke('restore_backup', { backupPath });
+export async function restoreShortcuts(
+  request: RestoreShortcutsRequest
+): Promise<ShortcutRestoreResult> {
+  return invoke<ShortcutRestoreResult>('restore_shortcuts', { request });
+}
+
+/**
+ * Get the backup retention policy
+ */
+export async function getBackupRetention(): Promise<RetentionPolicy> {
+  return invoke<RetentionPolicy>('get_backup_retention');
+}
+
+/**
+ * Set the backup retention policy
+ * @param policy New retention policy
+ * @returns Backups removed by the new policy
+ */
+export async function setBackupRetention(policy: RetentionPolicy): Promise<Backup[]> {
+  return invoke<Backup[]>('set_backup_retention', { policy });
+}
+
+/**
+ * Export all backups with their metadata to an archive chosen in a save dialog
+ * @returns Path of the written archive
+ */
+export async function exportBackups(): Promise<string> {
+  return invoke<string>('export_backups');
+}
+
+/**
+ * Import the backups of an archive chosen in a file dialog (every checksum is verified first)
+ * @returns Imported backups and the number already present
+ */
+export async function importBackups(): Promise<ImportResult> {
+  return invoke<ImportResult>('import_backups');
+}
+
+/**
+ * Check every backup for missing, corrupted or unparseable content
+ */
+export async function auditBackups(): Promise<AuditReport> {
+  return invoke<AuditReport>('audit_backups');
+}
+
+/**
+ * Move problem backups to a quarantine directory
+ * @param kinds Problems to quarantine (missing, corrupt and orphaned by default)
+ */
+export async function quarantineBackups(kinds?: BackupIssueKind[]): Promise<QuarantineResult> {
+  return invoke<QuarantineResult>('quarantine_backups', { kinds });
+}
+
+/**
+ * Get how the history of config files is kept
+ */
+export async function getHistorySettings(): Promise<HistorySettings> {
+  return invoke<HistorySettings>('get_history_settings');
+}
+
+/**
+ * Set how the history of config files is kept (existing backups stay with their backend)
+ * @param settings New history settings
+ */
+export async function setHistorySettings(settings: HistorySettings): Promise<void> {
+  return invoke('set_history_settings', { settings });
+}
+
+/**
+ * Get the application settings
+ */
+export async function getAppSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>('get_app_settings');
+}
+
+/**
+ * Set the application settings (a new backup directory applies to future backups only)
+ * @param settings New settings
+ */
+export async function setAppSettings(settings: AppSettings): Promise<void> {
+  return invoke('set_app_settings', { settings });
 }
 
 /**
@@ -161,19 +416,31 @@ export async function testShortcut(shortcutId: string): Promise<TestResult> {
 }
 
 /**
- * Execute a shortcut's command in test mode
+ * Preview a shortcut's command in test mode, without executing it
  * @param shortcutId ID of the shortcut to execute
  */
 export async function executeTestCommand(shortcutId: string): Promise<TestResult> {
   return invoke<TestResult>('execute_test_command', { shortcutId });
 }
 
+/**
+ * Assess whether a command is destructive or needs administrator privileges
+ * @param command Command to assess
+ */
+export async function checkCommandSafety(command: string): Promise<CommandSafety> {
+  return invoke<CommandSafety>('check_command_safety', { command });
+}
+
 /**
  * Execute a shortcut's command and return detailed execution results
  * @param shortcutId ID of the shortcut to execute
+ * @param confirmDangerous Must be true to run a command that requires confirmation
  */
-export async function executeShortcutCommand(shortcutId: string): Promise<TestResult> {
-  return invoke<TestResult>('execute_shortcut_command', { shortcutId });
+export async function executeShortcutCommand(
+  shortcutId: string,
+  confirmDangerous = false
+): Promise<TestResult> {
+  return invoke<TestResult>('execute_shortcut_command', { shortcutId, confirmDangerous });
 }
 
 /**
@@ -19,14 +19,161 @@ export interface Shortcut {
   /** Shell command to execute */
   command: string;
 
-  /** Optional mode name for modal shortcuts */
+  /** Optional mode name for modal shortcuts (several modes joined by ", ") */
   mode?: string;
 
+  /** Mode this shortcut switches to (`; <mode>`) instead of running a command */
+  activates?: string | null;
+
+  /** Application of a process list entry (`"iTerm2" : ...`), `*` for any other app */
+  process?: string | null;
+
+  /** Process list entry passing the key through to the application (`"Finder" ~`) */
+  passthrough?: boolean;
+
   /** Optional inline comment */
   comment?: string;
 
   /** Original line number from config file */
   line_number: number;
+
+  /** Tags for grouping and filtering (without the leading @) */
+  tags?: string[];
+
+  /** Optional human-readable description of what the shortcut does */
+  description?: string;
+
+  /** Lint rules suppressed for this shortcut (codes or rule names) */
+  allowed_rules?: string[];
+}
+
+/**
+ * A tag with the number of shortcuts carrying it
+ */
+export interface TagSummary {
+  tag: string;
+  count: number;
+}
+
+/**
+ * Key combination with modifiers expanded (hyper/meh) and in canonical order
+ */
+export interface KeyCombination {
+  modifiers: string[];
+  key: string;
+}
+
+/**
+ * A binding that differs from a queried chord by one modifier
+ */
+export interface NearMatch {
+  shortcut: Shortcut;
+
+  /** Modifier the binding has but the query does not */
+  extra_modifier?: string;
+
+  /** Modifier the query has but the binding does not */
+  missing_modifier?: string;
+}
+
+/**
+ * Result of a "what is bound to X?" query
+ */
+export interface KeyQueryResult {
+  /** Normalized form of the queried chord */
+  combination: KeyCombination;
+
+  /** Bindings for exactly this chord, across all modes */
+  exact: Shortcut[];
+
+  /** Bindings for the same key differing by a single modifier */
+  near: NearMatch[];
+}
+
+/**
+ * Request for free key suggestions
+ */
+export interface FreeKeyRequest {
+  /** Acceptable modifier sets, most preferred first */
+  modifier_sets: string[][];
+
+  /** Mode the shortcut will live in (omit for the default mode) */
+  mode?: string;
+
+  /** Command the shortcut will run, used for mnemonic ranking */
+  command?: string;
+
+  /** Maximum number of suggestions to return */
+  limit?: number;
+}
+
+/**
+ * A suggested unused key combination
+ */
+export interface FreeKeySuggestion {
+  modifiers: string[];
+  key: string;
+
+  /** Display form, e.g. "alt + shift - s" */
+  combination: string;
+
+  /** Ranking score (higher is better) */
+  score: number;
+
+  /** Why this key ranked where it did */
+  reasons: string[];
+}
+
+/**
+ * A keyboard layout: the character each physical key produces without modifiers
+ */
+export interface KeyboardLayout {
+  id: string;
+  name: string;
+
+  /** Physical keyboard type */
+  keyboard: 'ansi' | 'iso';
+
+  /** Character by physical key code (e.g. "BracketLeft") */
+  keys: Record<string, string>;
+}
+
+/**
+ * What a key means on one layout
+ */
+export interface KeyLayoutMeaning {
+  layout_id: string;
+  layout_name: string;
+
+  /** Physical key that types the character on this layout (null if it cannot be typed) */
+  physical_key: string | null;
+
+  /** Character at the physical position the key occupies on the active layout */
+  character_at_same_position: string | null;
+}
+
+/**
+ * Description of a binding's key across all known layouts
+ */
+export interface KeyDescription {
+  key: string;
+
+  /** True for named keys and hex key codes */
+  layout_independent: boolean;
+
+  /** Physical key on the active layout */
+  physical_key: string | null;
+  meanings: KeyLayoutMeaning[];
+}
+
+/**
+ * A shortcut whose key cannot be typed on a layout
+ */
+export interface LayoutWarning {
+  shortcut_id: string;
+  line_number: number;
+  key: string;
+  message: string;
 }
 
 /**
@@ -74,20 +221,57 @@ export interface ConfigFile {
   /** Parse errors encountered (if any) */
   parse_errors: ParseError[];
 
+  /** Modes declared with `:: <name>` */
+  modes?: ModeDeclaration[];
+
+  /** Directives the parser dropped because no shortcut follows them */
+  dangling_directives?: DanglingDirective[];
+
   /** Tracks the currently active file path (where saves will write) */
   current_file_path: string;
 }
 
+/**
+ * A mode declared with `:: <name> [@] [: command]`
+ */
+export interface ModeDeclaration {
+  name: string;
+
+  /** Whether the mode captures all keys (`@`) */
+  capture: boolean;
+
+  /** Command run when the mode is activated */
+  command?: string | null;
+
+  line_number: number;
+}
+
+/**
+ * A directive comment (`# @tags ...`) not directly above a shortcut
+ */
+export interface DanglingDirective {
+  /** Directive text (without the # prefix) */
+  text: string;
+
+  line_number: number;
+}
+
 /**
  * Represents a backup of a configuration file
  */
 export interface Backup {
-  /** Absolute path to the backup file */
+  /** Unique identifier of the backup */
+  id: string;
+
+  /** Absolute path to the file holding the backup content (shared by identical backups) */
   file_path: string;
 
   /** Path to the original config file */
   original_path: string;
 
+  /** Backups of the same config file share a namespace */
+  namespace: string;
+
   /** Timestamp when backup was created (ISO 8601) */
   created_at: string;
 
@@ -99,20 +283,388 @@ export interface Backup {
 
   /** File size in bytes */
   size_bytes: number;
+
+  /** What created the backup */
+  trigger: BackupTrigger;
+}
+
+/**
+ * Why a backup was created ('migrated' for backups found without metadata,
+ * 'saved' for git commits recorded after a save)
+ */
+export type BackupTrigger = 'manual' | 'before_save' | 'before_restore' | 'migrated' | 'saved';
+
+/**
+ * How a line differs between two versions of a config
+ */
+export type DiffLineKind = 'unchanged' | 'added' | 'removed';
+
+/**
+ * A line of a line diff
+ */
+export interface DiffLine {
+  kind: DiffLineKind;
+
+  /** Line number in the old version (absent for added lines) */
+  old_line?: number;
+
+  /** Line number in the new version (absent for removed lines) */
+  new_line?: number;
+
+  text: string;
+}
+
+/**
+ * A binding present in both versions with different settings
+ */
+export interface ShortcutChange {
+  /** Key the versions were matched by (mode, normalized chord, process) */
+  binding: string;
+
+  before: Shortcut;
+  after: Shortcut;
+
+  /** Changed fields ('command', 'description', ...) */
+  fields: string[];
+}
+
+/**
+ * Bindings added, removed and changed between two versions
+ */
+export interface ShortcutDiff {
+  added: Shortcut[];
+  removed: Shortcut[];
+  changed: ShortcutChange[];
+
+  /** Number of bindings identical in both versions */
+  unchanged: number;
+}
+
+/**
+ * Differences between two versions of a config
+ */
+export interface ConfigDiff {
+  lines: DiffLine[];
+
+  /** Binding-level diff (absent when either version does not parse) */
+  shortcuts?: ShortcutDiff;
+}
+
+/**
+ * What to do with a restored binding whose key combination is already used
+ */
+export type ConflictResolution = 'skip' | 'replace' | 'keep_both';
+
+/**
+ * A binding of a backup, compared with the loaded configuration
+ */
+export interface BackupShortcut {
+  /** Binding as in the backup (`line_number` selects it for restoring) */
+  shortcut: Shortcut;
+
+  /** Current binding using the same key combination in the same mode */
+  conflict?: Shortcut;
+
+  /** Whether the conflicting binding is identical, so restoring changes nothing */
+  identical: boolean;
+}
+
+/**
+ * Request to restore selected bindings of a backup
+ */
+export interface RestoreShortcutsRequest {
+  backup_id: string;
+
+  /** Line numbers of the bindings in the backup */
+  line_numbers: number[];
+
+  /** Resolution for bindings whose key combination is already used (default 'skip') */
+  on_conflict?: ConflictResolution;
+
+  /** Resolutions overriding `on_conflict`, by backup line number */
+  resolutions?: Record<number, ConflictResolution>;
+}
+
+/**
+ * Outcome of restoring selected bindings
+ */
+export interface ShortcutRestoreResult {
+  added: Shortcut[];
+
+  /** Current bindings overwritten, as they are after the restore */
+  replaced: Shortcut[];
+
+  /** Selected bindings left out (skipped conflicts, identical bindings) */
+  skipped: Shortcut[];
+
+  /** Mode declarations copied from the backup */
+  modes_added: string[];
+}
+
+/**
+ * Which backups are kept when old ones are pruned (unset limits do not apply)
+ */
+export interface RetentionPolicy {
+  /** Always keep this many of the newest backups */
+  keep_last?: number;
+
+  /** Keep the newest backup of each day for this many days */
+  keep_daily_days?: number;
+
+  /** Drop the oldest backups beyond this total size (the newest is always kept) */
+  max_total_bytes?: number;
+}
+
+/**
+ * Outcome of importing a backup archive
+ */
+export interface ImportResult {
+  /** Backups added to the store */
+  imported: Backup[];
+
+  /** Backups of the archive that were already present */
+  skipped: number;
+}
+
+/**
+ * What is wrong with a backup ('orphaned' for stored content no backup points at)
+ */
+export type BackupIssueKind = 'missing' | 'corrupt' | 'unparseable' | 'orphaned';
+
+/**
+ * A problem found by a backup audit
+ */
+export interface BackupIssue {
+  kind: BackupIssueKind;
+
+  /** Affected backup (absent for orphaned content) */
+  backup?: Backup;
+
+  /** Affected file (absent for missing content) */
+  path?: string;
+
+  message: string;
+}
+
+/**
+ * Result of auditing the backup directory
+ */
+export interface AuditReport {
+  /** Number of backups checked */
+  checked: number;
+
+  issues: BackupIssue[];
+}
+
+/**
+ * Outcome of quarantining problem backups
+ */
+export interface QuarantineResult {
+  /** Directory the files were moved to (absent when nothing was quarantined) */
+  directory?: string;
+
+  /** Problems moved to the quarantine directory */
+  quarantined: BackupIssue[];
+}
+
+/**
+ * Where the history of config files is kept
+ */
+export type HistoryBackend = 'store' | 'git';
+
+/**
+ * How the history of config files is kept
+ */
+export interface HistorySettings {
+  backend: HistoryBackend;
+
+  /** With git, commit to the repository the config is in (if any) */
+  use_config_repo: boolean;
+}
+
+/**
+ * User-configurable application settings
+ */
+export interface AppSettings {
+  /** Directory for backups (`~` is expanded; `~/.config/skhd/backups` if absent) */
+  backup_dir?: string;
+}
+
+/**
+ * Stable diagnostic code
+ */
+export type DiagnosticCode =
+  | 'SKHD001'
+  | 'SKHD002'
+  | 'SKHD003'
+  | 'SKHD004'
+  | 'SKHD005'
+  | 'SKHD006'
+  | 'SKHD007'
+  | 'SKHD008'
+  | 'SKHD009'
+  | 'SKHD010'
+  | 'SKHD011'
+  | 'SKHD012'
+  | 'SKHD013'
+  | 'SKHD014'
+  | 'SKHD015'
+  | 'SKHD016'
+  | 'SKHD017';
+
+/**
+ * Diagnostic severity
+ */
+export type Severity = 'error' | 'warning' | 'info';
+
+/**
+ * A range in the config file (1-based, end exclusive)
+ */
+export interface Span {
+  line: number;
+  column: number;
+  end_line: number;
+  end_column: number;
+}
+
+/**
+ * Another location relevant to a diagnostic
+ */
+export interface RelatedLocation {
+  span: Span;
+  shortcut_id: string | null;
+  message: string;
+}
+
+/**
+ * A text replacement in the config file
+ */
+export interface TextEdit {
+  span: Span;
+  new_text: string;
+}
+
+/**
+ * A machine-applicable fix
+ */
+export interface Fix {
+  title: string;
+  edits: TextEdit[];
+}
+
+/**
+ * A single validation finding
+ */
+export interface Diagnostic {
+  /** Stable code, e.g. "SKHD001" */
+  code: DiagnosticCode;
+
+  /** Rule name, e.g. "duplicate-binding" */
+  rule: string;
+  severity: Severity;
+  message: string;
+
+  /** Shortcut the diagnostic is about */
+  shortcut_id: string | null;
+
+  /** Location in the config file */
+  span: Span | null;
+  related: RelatedLocation[];
+  fixes: Fix[];
+}
+
+/**
+ * A group of system shortcuts
+ */
+export interface SystemShortcutCategory {
+  id: string;
+  name: string;
+}
+
+/**
+ * A shortcut reserved by macOS or commonly used by applications
+ */
+export interface SystemShortcut {
+  id: string;
+  category: string;
+
+  /** Key chord in skhd notation, e.g. "cmd + shift - 4" */
+  chord: string;
+  description: string;
+
+  /** Severity of a conflict with this shortcut */
+  severity: Severity;
+
+  /** False when switched off in the user file */
+  enabled: boolean;
 }
 
 /**
- * Validation result containing errors and warnings
+ * System shortcut conflict database (built-in plus user entries)
+ */
+export interface SystemShortcutCatalog {
+  categories: SystemShortcutCategory[];
+  shortcuts: SystemShortcut[];
+}
+
+/**
+ * Configured level of a lint rule
+ */
+export type RuleLevel = 'off' | 'error' | 'warning' | 'info';
+
+/**
+ * A lint rule and its effective level
+ */
+export interface LintRuleInfo {
+  code: DiagnosticCode;
+  rule: string;
+  description: string;
+  default_severity: Severity;
+
+  /** Level from the project's settings file (null when not configured) */
+  level: RuleLevel | null;
+}
+
+/**
+ * A shell syntax error in one of a config's shortcuts
+ */
+export interface ShortcutSyntaxError {
+  shortcut_id: string;
+
+  /** Line of the shortcut in the config file */
+  line_number: number;
+
+  /** 1-based character column in the command */
+  column: number;
+  message: string;
+}
+
+/**
+ * Validation result containing structured diagnostics
  */
 export interface ValidationResult {
   /** Whether the validation passed (no errors) */
   is_valid: boolean;
 
-  /** List of validation errors */
-  errors: string[];
+  /** Errors and warnings, in check order */
+  diagnostics: Diagnostic[];
+}
+
+/**
+ * Diagnostics changed by a shortcut edit ("validation-changed" event)
+ */
+export interface ValidationDelta {
+  /** Diagnostics were recomputed from scratch: `added` replaces all earlier ones */
+  reset: boolean;
+
+  /** Shortcut IDs whose diagnostics were recomputed */
+  revalidated: string[];
 
-  /** List of validation warnings */
-  warnings: string[];
+  added: Diagnostic[];
+  removed: Diagnostic[];
+
+  /** Whether the configuration is free of errors after the edit */
+  is_valid: boolean;
 }
 
 /**
@@ -123,7 +675,12 @@ export interface CreateShortcutRequest {
   key: string;
   command: string;
   mode?: string;
+  /** Mode to switch to instead of running a command */
+  activates?: string;
   comment?: string;
+  tags?: string[];
+  description?: string;
+  allowed_rules?: string[];
 }
 
 /**
@@ -134,8 +691,72 @@ export interface UpdateShortcutRequest {
   modifiers: string[];
   key: string;
   command: string;
+  /** Mode (keeps the existing one when omitted, `"default"` for the default mode) */
   mode?: string;
+  /** Mode to switch to (keeps the existing one when omitted and `command` is empty) */
+  activates?: string;
   comment?: string;
+  /** Tags (keeps the existing ones when omitted) */
+  tags?: string[];
+  /** Description (keeps the existing one when omitted, cleared when empty) */
+  description?: string;
+  allowed_rules?: string[];
+}
+
+/**
+ * Request to duplicate a shortcut into another mode or key combination
+ */
+export interface DuplicateShortcutRequest {
+  /** ID of the shortcut to copy */
+  id: string;
+
+  /** Target modifiers (keeps the original's when omitted) */
+  modifiers?: string[];
+
+  /** Target key (keeps the original's when omitted) */
+  key?: string;
+
+  /** Target mode (keeps the original's when omitted, "default" for the default mode) */
+  mode?: string;
+}
+
+/**
+ * Kind of risk a command carries
+ */
+export type RiskKind =
+  | 'recursive_delete'
+  | 'privileged'
+  | 'remote_script'
+  | 'kill_processes'
+  | 'power_control'
+  | 'system_path_write'
+  | 'package_removal'
+  | 'file_delete'
+  | 'disk_operation'
+  | 'unanalyzable';
+
+/**
+ * A risky construct found in a command
+ */
+export interface CommandRisk {
+  kind: RiskKind;
+  message: string;
+
+  /** Byte range of the offending word in the command */
+  start: number;
+  end: number;
+}
+
+/**
+ * Safety assessment of a command
+ */
+export interface CommandSafety {
+  /** Command must not run without explicit confirmation */
+  requires_confirmation: boolean;
+
+  /** Command runs with administrator privileges */
+  requires_admin: boolean;
+  risks: CommandRisk[];
 }
 
 /**